@@ -0,0 +1,84 @@
+use crate::api::provider::{static_models_owned_by, Provider};
+use crate::logging;
+use crate::types::{
+    anthropic::{anthropic_request_body, AnthropicResponse, AnthropicStreamTranslator, ANTHROPIC_VERSION},
+    api::OpenAICompletionRequest,
+    response::{OpenAICompletionResponse, OpenAIError, OpenAIModelInfo, ProviderError},
+    stream::{parse_sse_data, StreamAccumulator, StreamEvent},
+};
+
+/// Anthropic's Messages API
+pub struct AnthropicProvider;
+
+impl Provider for AnthropicProvider {
+    fn name(&self) -> &'static str {
+        "Anthropic"
+    }
+
+    fn default_base_url(&self) -> &'static str {
+        "https://api.anthropic.com/v1"
+    }
+
+    fn auth_headers(&self, api_key: &str) -> Vec<(String, String)> {
+        vec![
+            ("x-api-key".to_string(), api_key.to_string()),
+            ("anthropic-version".to_string(), ANTHROPIC_VERSION.to_string()),
+        ]
+    }
+
+    fn completion_path(&self) -> &'static str {
+        "/messages"
+    }
+
+    fn request_body(&self, request: &OpenAICompletionRequest, stream: bool) -> serde_json::Value {
+        let mut request = request.clone();
+        request.stream = stream.then_some(true);
+        anthropic_request_body(&request)
+    }
+
+    fn parse_response(&self, body: &[u8]) -> Result<OpenAICompletionResponse, OpenAIError> {
+        let response: AnthropicResponse = serde_json::from_slice(body)?;
+        Ok(response.into())
+    }
+
+    fn parse_stream(
+        &self,
+        body: &[u8],
+        on_event: &mut dyn FnMut(StreamEvent),
+    ) -> Result<OpenAICompletionResponse, OpenAIError> {
+        let body_str = String::from_utf8_lossy(body);
+
+        let mut translator = AnthropicStreamTranslator::new();
+        let mut accumulator = StreamAccumulator::new();
+        let mut event_count = 0;
+        for data in parse_sse_data(&body_str) {
+            // The event holds completion text, so it is only logged, and only at trace level
+            let event: serde_json::Value = serde_json::from_str(&data).map_err(|e| {
+                logging::trace(&format!("Invalid stream event: {}", logging::body(data.as_bytes())));
+                OpenAIError::InvalidResponse(format!("Invalid stream event after {} events: {}", event_count, e))
+            })?;
+            event_count += 1;
+            let chunk = translator
+                .push_event(event)
+                .map_err(|message| OpenAIError::ApiError(Box::new(ProviderError::from_message(200, message))))?;
+            if let Some(chunk) = chunk {
+                for event in accumulator.push_chunk(chunk)? {
+                    on_event(event);
+                }
+            }
+        }
+
+        logging::debug(&format!("Stream completed after {} events", event_count));
+
+        Ok(accumulator.into_response())
+    }
+
+    fn static_models(&self) -> Vec<OpenAIModelInfo> {
+        static_models_owned_by("anthropic")
+    }
+
+    fn is_retryable_status(&self, status: u16) -> bool {
+        // 529: overloaded
+        matches!(status, 429 | 502 | 503 | 504 | 529)
+    }
+}
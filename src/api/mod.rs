@@ -1,3 +1,7 @@
+pub mod anthropic;
 pub mod openai;
+pub mod provider;
+pub mod rate_limit;
+pub mod transport;
 
-pub use openai::*;
+pub use provider::*;
@@ -1,257 +1,409 @@
-use crate::bindings::theater::simple::http_client::{send_http, HttpRequest};
-use crate::bindings::theater::simple::runtime::log;
-use crate::bindings::theater::simple::timing;
+use crate::api::provider::{static_models_owned_by, Provider};
+use crate::logging;
 use crate::types::{
-    api::OpenAICompletionRequest,
+    api::{OpenAICompletionRequest, OpenAIStreamOptions},
     response::{OpenAICompletionResponse, OpenAIError, OpenAIModelInfo},
-    state::{ContentFormat, RetryConfig},
+    state::ContentFormat,
+    stream::{completion_deltas, parse_sse_data, OpenAIStreamChunk, StreamAccumulator, StreamEvent},
+    tool_schema::{normalize_tools, SchemaDialect},
 };
 
-/// Client for interacting with the OpenAI-compatible API (including Moonshot)
-pub struct OpenAIClient {
-    /// API key
-    api_key: String,
-    /// Base URL for the API
-    base_url: String,
+/// OpenAI Chat Completions
+pub struct OpenAIProvider;
+
+/// Moonshot, which prefers string content and rejects some OpenAI options
+///
+/// Unless `Config.content_format` says otherwise, content is sent as strings,
+/// and requests with images as content parts so vision models receive them.
+pub struct MoonshotProvider {
+    content_format: Option<ContentFormat>,
+}
+
+impl MoonshotProvider {
+    pub fn new(content_format: Option<ContentFormat>) -> Self {
+        Self { content_format }
+    }
+}
+
+/// Any other OpenAI-compatible server (vLLM, Ollama, LiteLLM, ...)
+///
+/// The content shape comes from `Config.content_format`. String format keeps
+/// the Moonshot adjustments it has always implied.
+pub struct CompatibleProvider {
+    content_format: ContentFormat,
+}
+
+impl CompatibleProvider {
+    pub fn new(content_format: ContentFormat) -> Self {
+        Self { content_format }
+    }
+}
+
+fn bearer_auth(api_key: &str) -> Vec<(String, String)> {
+    vec![("authorization".to_string(), format!("Bearer {}", api_key))]
+}
+
+/// Build a Chat Completions body with message content in `format` and tool
+/// schemas in `dialect`
+fn chat_request_body(
+    request: &OpenAICompletionRequest,
+    format: &ContentFormat,
+    dialect: SchemaDialect,
+    stream: bool,
+) -> serde_json::Value {
+    let mut request = request.clone();
+    if let Some(tools) = &mut request.tools {
+        normalize_tools(tools, dialect);
+    }
+    if stream {
+        request.stream = Some(true);
+        if let ContentFormat::Array = format {
+            // OpenAI only reports usage for streams when asked to
+            request.stream_options = Some(OpenAIStreamOptions { include_usage: true });
+        }
+    }
+    request.serialize_for_provider(format)
+}
+
+/// Drop the options Moonshot rejects
+///
+/// `tool_choice: "required"` becomes `"auto"`, the closest choice Moonshot
+/// understands, and `parallel_tool_calls` and `stream_options` are not sent
+/// at all.
+fn apply_moonshot_quirks(body: &mut serde_json::Value) {
+    if body["tool_choice"] == "required" {
+        logging::debug("Provider does not support tool_choice \"required\", sending \"auto\"");
+        body["tool_choice"] = serde_json::json!("auto");
+    }
+    if let Some(body) = body.as_object_mut() {
+        body.remove("parallel_tool_calls");
+        body.remove("stream_options");
+    }
+}
+
+/// Parse a Chat Completions response
+///
+/// Message content is read in whichever shape the provider used (see
+/// `OpenAIContent`), so this doesn't depend on the configured format.
+pub fn parse_chat_completion(body: &[u8]) -> Result<OpenAICompletionResponse, OpenAIError> {
+    logging::trace(&format!("Raw response: {}", logging::body(body)));
+    let completion: OpenAICompletionResponse = serde_json::from_slice(body)?;
+    logging::debug(&format!("Parsed response with {} choices", completion.choices.len()));
+    Ok(completion)
 }
 
-impl OpenAIClient {
-    /// Create a new OpenAI client
-    pub fn new(api_key: String) -> Self {
-        Self::new_with_base_url(api_key, "https://api.openai.com/v1".to_string())
-    }
-    
-    /// Create a new OpenAI client with custom base URL
-    pub fn new_with_base_url(api_key: String, base_url: String) -> Self {
-        Self {
-            api_key,
-            base_url,
+/// Parse a Chat Completions SSE body, reporting each delta to `on_event`
+pub fn parse_chat_stream(
+    body: &[u8],
+    on_event: &mut dyn FnMut(StreamEvent),
+) -> Result<OpenAICompletionResponse, OpenAIError> {
+    let body_str = String::from_utf8_lossy(body);
+
+    // Some compatible servers ignore `stream` and answer with a plain completion
+    if !body_str.trim_start().starts_with("data:") {
+        logging::info("Provider did not stream, falling back to a single delta");
+        let completion = parse_chat_completion(body)?;
+        for event in completion_deltas(&completion) {
+            on_event(event);
         }
+        return Ok(completion);
     }
 
-    /// Check if a status code indicates a retryable error
-    fn is_retryable_error(status: u16) -> bool {
-        match status {
-            429 => true, // Rate limit exceeded
-            502 => true, // Bad gateway
-            503 => true, // Service unavailable
-            504 => true, // Gateway timeout
-            _ => false,
+    let mut accumulator = StreamAccumulator::new();
+    let mut chunk_count = 0;
+    for data in parse_sse_data(&body_str) {
+        // The chunk holds completion text, so it is only logged, and only at trace level
+        let chunk: OpenAIStreamChunk = serde_json::from_str(&data).map_err(|e| {
+            logging::trace(&format!("Invalid stream chunk: {}", logging::body(data.as_bytes())));
+            OpenAIError::InvalidResponse(format!("Invalid stream chunk after {} chunks: {}", chunk_count, e))
+        })?;
+        chunk_count += 1;
+        for event in accumulator.push_chunk(chunk)? {
+            on_event(event);
         }
     }
 
-    /// Execute an HTTP request with exponential backoff retry logic
-    fn execute_with_retry(
+    logging::debug(&format!("Stream completed after {} chunks", chunk_count));
+
+    Ok(accumulator.into_response())
+}
+
+impl Provider for OpenAIProvider {
+    fn name(&self) -> &'static str {
+        "OpenAI"
+    }
+
+    fn default_base_url(&self) -> &'static str {
+        "https://api.openai.com/v1"
+    }
+
+    fn auth_headers(&self, api_key: &str) -> Vec<(String, String)> {
+        bearer_auth(api_key)
+    }
+
+    fn completion_path(&self) -> &'static str {
+        "/chat/completions"
+    }
+
+    fn request_body(&self, request: &OpenAICompletionRequest, stream: bool) -> serde_json::Value {
+        chat_request_body(request, &ContentFormat::Array, SchemaDialect::OpenAI, stream)
+    }
+
+    fn parse_response(&self, body: &[u8]) -> Result<OpenAICompletionResponse, OpenAIError> {
+        parse_chat_completion(body)
+    }
+
+    fn parse_stream(
         &self,
-        request: &HttpRequest,
-        retry_config: &RetryConfig,
-    ) -> Result<crate::bindings::theater::simple::http_client::HttpResponse, OpenAIError> {
-        let start_time = timing::now();
-        let mut current_delay = retry_config.initial_delay_ms;
-        let mut attempt = 0;
-
-        loop {
-            attempt += 1;
-            
-            log(&format!("HTTP request attempt {}/{}", attempt, retry_config.max_retries + 1));
-
-            // Send the request
-            let response = match send_http(request) {
-                Ok(resp) => resp,
-                Err(e) => {
-                    log(&format!("HTTP request failed: {}", e));
-                    if attempt > retry_config.max_retries {
-                        return Err(OpenAIError::HttpError(e));
-                    }
-                    
-                    // Check if we've exceeded the total timeout
-                    let elapsed = timing::now() - start_time;
-                    if elapsed >= retry_config.max_total_timeout_ms as u64 {
-                        log("Total retry timeout exceeded");
-                        return Err(OpenAIError::HttpError(e));
-                    }
-                    
-                    // Wait before retrying
-                    log(&format!("Retrying after {} ms due to HTTP error", current_delay));
-                    let _ = timing::sleep(current_delay as u64);
-                    current_delay = std::cmp::min(
-                        (current_delay as f64 * retry_config.backoff_multiplier) as u32,
-                        retry_config.max_delay_ms
-                    );
-                    continue;
-                }
-            };
-
-            // Check if we got a successful response
-            if response.status == 200 {
-                log(&format!("Request successful on attempt {}", attempt));
-                return Ok(response);
-            }
+        body: &[u8],
+        on_event: &mut dyn FnMut(StreamEvent),
+    ) -> Result<OpenAICompletionResponse, OpenAIError> {
+        parse_chat_stream(body, on_event)
+    }
 
-            // Check if this is a retryable error
-            if !Self::is_retryable_error(response.status) {
-                log(&format!("Non-retryable error: {}", response.status));
-                return Ok(response); // Return the error response to be handled by caller
-            }
+    fn static_models(&self) -> Vec<OpenAIModelInfo> {
+        static_models_owned_by("openai")
+    }
+}
 
-            // Check if we've exhausted our retries
-            if attempt > retry_config.max_retries {
-                log(&format!("Max retries ({}) exceeded", retry_config.max_retries));
-                return Ok(response);
-            }
+impl Provider for MoonshotProvider {
+    fn name(&self) -> &'static str {
+        "Moonshot"
+    }
 
-            // Check if we've exceeded the total timeout
-            let elapsed = timing::now() - start_time;
-            if elapsed >= retry_config.max_total_timeout_ms as u64 {
-                log("Total retry timeout exceeded");
-                return Ok(response);
-            }
+    fn default_base_url(&self) -> &'static str {
+        "https://api.moonshot.ai/v1"
+    }
 
-            // Log the retry attempt
-            let message = String::from_utf8_lossy(&response.body.unwrap_or_default()).to_string();
-            log(&format!(
-                "Retryable error {} on attempt {}: {}",
-                response.status, attempt, message
-            ));
-            log(&format!("Retrying after {} ms", current_delay));
-
-            // Wait before retrying
-            let _ = timing::sleep(current_delay as u64);
-            
-            // Update delay for next attempt (exponential backoff)
-            current_delay = std::cmp::min(
-                (current_delay as f64 * retry_config.backoff_multiplier) as u32,
-                retry_config.max_delay_ms
-            );
-        }
+    fn auth_headers(&self, api_key: &str) -> Vec<(String, String)> {
+        bearer_auth(api_key)
+    }
+
+    fn completion_path(&self) -> &'static str {
+        "/chat/completions"
+    }
+
+    fn request_body(&self, request: &OpenAICompletionRequest, stream: bool) -> serde_json::Value {
+        let format = match &self.content_format {
+            Some(format) => format.clone(),
+            None if request.has_images() => ContentFormat::Array,
+            None => ContentFormat::String,
+        };
+        let mut body = chat_request_body(request, &format, SchemaDialect::Basic, stream);
+        apply_moonshot_quirks(&mut body);
+        body
+    }
+
+    fn parse_response(&self, body: &[u8]) -> Result<OpenAICompletionResponse, OpenAIError> {
+        parse_chat_completion(body)
     }
 
-    /// Parse response in a content-format aware way
-    fn parse_response(
+    fn parse_stream(
+        &self,
         body: &[u8],
-        content_format: &ContentFormat,
+        on_event: &mut dyn FnMut(StreamEvent),
     ) -> Result<OpenAICompletionResponse, OpenAIError> {
-        log(&format!("Parsing response with content format: {:?}", content_format));
-        
-        match content_format {
-            ContentFormat::String => {
-                log("Using String content format for response parsing");
-                // For string format (Moonshot), we need to handle string content
-                let mut raw_response: serde_json::Value = serde_json::from_slice(body)?;
-                
-                log(&format!("Raw response structure: {}", serde_json::to_string_pretty(&raw_response).unwrap_or("<invalid json>".to_string())));
-                
-                // Convert string content to OpenAIContent format
-                if let Some(choices) = raw_response.get_mut("choices").and_then(|c| c.as_array_mut()) {
-                    log(&format!("Found {} choices to process", choices.len()));
-                    for (i, choice) in choices.iter_mut().enumerate() {
-                        if let Some(message) = choice.get_mut("message") {
-                            if let Some(content_str) = message.get("content").and_then(|c| c.as_str()) {
-                                log(&format!("Converting string content in choice {}: {:?}", i, content_str));
-                                // Replace string content with OpenAIContent object
-                                let openai_content = crate::types::conversion::OpenAIContent::from_text(content_str.to_string());
-                                message["content"] = serde_json::to_value(openai_content)?;
-                                log(&format!("Successfully converted content for choice {}", i));
-                            } else {
-                                log(&format!("Choice {} message content is not a string or missing", i));
-                            }
-                        } else {
-                            log(&format!("Choice {} has no message field", i));
-                        }
-                    }
-                } else {
-                    log("No choices array found in response");
-                }
-                
-                log("Attempting to deserialize modified response");
-                // Now deserialize the modified response
-                let completion: OpenAICompletionResponse = serde_json::from_value(raw_response)?;
-                log("Successfully parsed response with String content format");
-                Ok(completion)
-            },
-            ContentFormat::Array => {
-                log("Using Array content format for response parsing");
-                // For array format (OpenAI), deserialize directly
-                let completion: OpenAICompletionResponse = serde_json::from_slice(body)?;
-                log("Successfully parsed response with Array content format");
-                Ok(completion)
-            }
+        parse_chat_stream(body, on_event)
+    }
+
+    fn static_models(&self) -> Vec<OpenAIModelInfo> {
+        static_models_owned_by("moonshot")
+    }
+
+    fn sends_images(&self) -> bool {
+        !matches!(self.content_format, Some(ContentFormat::String))
+    }
+}
+
+impl Provider for CompatibleProvider {
+    fn name(&self) -> &'static str {
+        "OpenAI-compatible"
+    }
+
+    fn default_base_url(&self) -> &'static str {
+        "https://api.openai.com/v1"
+    }
+
+    fn auth_headers(&self, api_key: &str) -> Vec<(String, String)> {
+        bearer_auth(api_key)
+    }
+
+    fn completion_path(&self) -> &'static str {
+        "/chat/completions"
+    }
+
+    fn request_body(&self, request: &OpenAICompletionRequest, stream: bool) -> serde_json::Value {
+        // String format implies a Moonshot-like server, with the same schema subset
+        let dialect = match self.content_format {
+            ContentFormat::String => SchemaDialect::Basic,
+            ContentFormat::Array => SchemaDialect::Full,
+        };
+        let mut body = chat_request_body(request, &self.content_format, dialect, stream);
+        if let ContentFormat::String = self.content_format {
+            apply_moonshot_quirks(&mut body);
         }
+        body
     }
 
-    /// List available models from the OpenAI API
-    pub fn list_models(&self) -> Result<Vec<OpenAIModelInfo>, OpenAIError> {
-        log("Using static OpenAI model list");
-        
-        // OpenAI doesn't provide detailed model information via API, so we return our curated list
-        Ok(OpenAIModelInfo::get_available_models())
+    fn parse_response(&self, body: &[u8]) -> Result<OpenAICompletionResponse, OpenAIError> {
+        parse_chat_completion(body)
     }
 
-    /// Generate a completion using the OpenAI API with retry logic
-    pub fn generate_completion(
+    fn parse_stream(
         &self,
-        request: &OpenAICompletionRequest,
-        retry_config: &RetryConfig,
-        content_format: &ContentFormat,
+        body: &[u8],
+        on_event: &mut dyn FnMut(StreamEvent),
     ) -> Result<OpenAICompletionResponse, OpenAIError> {
-        log(&format!("Generating completion with model: {}", request.model));
+        parse_chat_stream(body, on_event)
+    }
+
+    fn static_models(&self) -> Vec<OpenAIModelInfo> {
+        OpenAIModelInfo::get_available_models()
+    }
+
+    fn sends_images(&self) -> bool {
+        matches!(self.content_format, ContentFormat::Array)
+    }
+}
 
-        // Serialize the request using provider-aware format
-        let request_json = request.serialize_for_provider(content_format);
-        let request_body = serde_json::to_vec(&request_json)?;
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bindings::colinrozzi::genai_types::types::{
+        CompletionRequest, CompletionResponse, Message, MessageContent, MessageRole, StopReason, Tool,
+        ToolChoice, ToolResult,
+    };
 
-        log(&format!(
-            "OpenAI API request: {}",
-            String::from_utf8_lossy(&request_body)
-        ));
+    #[test]
+    fn test_moonshot_quirks() {
+        let request = OpenAICompletionRequest::try_from(CompletionRequest {
+            model: "kimi-k2-0711-preview".to_string(),
+            messages: Vec::new(),
+            max_tokens: 256,
+            temperature: None,
+            system: None,
+            tools: Some(vec![Tool {
+                name: "get_weather".to_string(),
+                description: None,
+                input_schema: br#"{"type":"object"}"#.to_vec(),
+                output_schema: None,
+                annotations: None,
+                meta: None,
+            }]),
+            tool_choice: Some(ToolChoice::Any),
+            disable_parallel_tool_use: Some(true),
+        })
+        .unwrap();
+
+        let body = OpenAIProvider.request_body(&request, true);
+        assert_eq!(body["tool_choice"], serde_json::json!("required"));
+        assert_eq!(body["parallel_tool_calls"], serde_json::json!(false));
+        assert_eq!(body["stream_options"]["include_usage"], serde_json::json!(true));
 
-        let http_request = HttpRequest {
-            method: "POST".to_string(),
-            uri: format!("{}/chat/completions", self.base_url),
-            headers: vec![
-                ("authorization".to_string(), format!("Bearer {}", self.api_key)),
-                ("content-type".to_string(), "application/json".to_string()),
-                ("user-agent".to_string(), "moonshot-proxy/0.1.0".to_string()),
-            ],
-            body: Some(request_body),
+        // Moonshot: no "required", no parallel_tool_calls and no stream_options
+        let moonshot = MoonshotProvider::new(None);
+        let body = moonshot.request_body(&request, true);
+        assert_eq!(body["tool_choice"], serde_json::json!("auto"));
+        assert!(body.get("parallel_tool_calls").is_none());
+        assert!(body.get("stream_options").is_none());
+        assert_eq!(body["stream"], serde_json::json!(true));
+
+        let compatible = CompatibleProvider::new(ContentFormat::Array);
+        assert_eq!(compatible.request_body(&request, false), OpenAIProvider.request_body(&request, false));
+
+        // Moonshot sends string content, except to get images to vision models
+        let text = Message {
+            role: MessageRole::User,
+            content: vec![MessageContent::Text("Describe the screenshot".to_string())],
+        };
+        let screenshot = Message {
+            role: MessageRole::User,
+            content: vec![MessageContent::ToolResult(ToolResult {
+                tool_use_id: "call_1".to_string(),
+                content: serde_json::to_vec(&serde_json::json!([
+                    { "Image": { "type_": "image", "data": "iVBORw0KGgo=", "mime_type": "image/png", "meta": null } }
+                ]))
+                .unwrap(),
+                is_error: false,
+            })],
         };
+        let request = |messages: Vec<Message>| {
+            OpenAICompletionRequest::try_from(CompletionRequest {
+                model: "moonshot-v1-8k-vision-preview".to_string(),
+                messages,
+                max_tokens: 256,
+                temperature: None,
+                system: None,
+                tools: None,
+                tool_choice: None,
+                disable_parallel_tool_use: None,
+            })
+            .unwrap()
+        };
+        let text_only = request(vec![text.clone()]);
+        let with_images = request(vec![text, screenshot]);
 
-        let response = self.execute_with_retry(&http_request, retry_config)?;
-
-        // Check status code
-        if response.status != 200 {
-            let message = String::from_utf8_lossy(&response.body.unwrap_or_default()).to_string();
-            
-            // Handle specific error cases
-            return match response.status {
-                401 => Err(OpenAIError::AuthenticationError(message)),
-                429 => {
-                    // Try to extract retry-after header
-                    let retry_after = None; // TODO: Extract from headers if needed
-                    Err(OpenAIError::RateLimitExceeded { retry_after })
-                }
-                _ => Err(OpenAIError::ApiError {
-                    status: response.status,
-                    message,
-                }),
-            };
-        }
+        assert_eq!(moonshot.request_body(&text_only, false)["messages"][0]["content"], "Describe the screenshot");
+        let body = moonshot.request_body(&with_images, false);
+        assert!(moonshot.sends_images());
+        assert_eq!(body["messages"][0]["content"][0]["text"], "Describe the screenshot");
+        assert!(body.to_string().contains("data:image/png;base64,iVBORw0KGgo="));
+
+        // A configured format is used as it is
+        let string_only = MoonshotProvider::new(Some(ContentFormat::String));
+        assert!(!string_only.sends_images());
+        assert!(string_only.request_body(&with_images, false).to_string().contains("[Image: image/png]"));
+        let array = MoonshotProvider::new(Some(ContentFormat::Array));
+        assert_eq!(array.request_body(&text_only, false)["messages"][0]["content"][0]["text"], "Describe the screenshot");
+    }
 
-        // Parse the response
-        let body = response
-            .body
-            .ok_or_else(|| OpenAIError::InvalidResponse("No response body".to_string()))?;
+    fn parse_fixture(fixture: &str) -> CompletionResponse {
+        let completion: OpenAICompletionResponse = serde_json::from_str(fixture).unwrap();
+        completion.into()
+    }
 
-        log(&format!(
-            "OpenAI API response: {}",
-            String::from_utf8_lossy(&body)
-        ));
+    #[test]
+    fn test_parse_provider_responses() {
+        // OpenAI: null content alongside tool calls, plus fields we don't model
+        let response = parse_fixture(include_str!("../../tests/fixtures/completion.openai_tool_calls.json"));
+        assert!(matches!(response.stop_reason, StopReason::ToolUse));
+        assert_eq!(response.usage.input_tokens, 82);
+        match response.content.as_slice() {
+            [MessageContent::ToolUse(tool_use)] => {
+                assert_eq!(tool_use.name, "get_weather");
+                let input: serde_json::Value = serde_json::from_slice(&tool_use.input).unwrap();
+                assert_eq!(input["location"], "Paris, France");
+            }
+            other => panic!("expected one tool call, got {:?}", other),
+        }
 
-        // Parse the response in a content-format aware way
-        let completion: OpenAICompletionResponse = Self::parse_response(&body, content_format)?;
+        // Moonshot: string content next to tool calls
+        let response = parse_fixture(include_str!("../../tests/fixtures/completion.moonshot.json"));
+        assert!(matches!(
+            response.content.as_slice(),
+            [MessageContent::Text(text), MessageContent::ToolUse(_)] if text.starts_with("I'll check")
+        ));
 
-        log("Completion generated successfully");
+        // Compatible server: content parts, null usage and an unknown finish reason
+        let response = parse_fixture(include_str!("../../tests/fixtures/completion.compatible_parts.json"));
+        assert!(matches!(response.stop_reason, StopReason::EndTurn));
+        assert_eq!(response.usage.output_tokens, 0);
+        let texts: Vec<_> = response
+            .content
+            .iter()
+            .filter_map(|content| match content {
+                MessageContent::Text(text) => Some(text.as_str()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(texts, ["Paris is ", "sunny today."]);
 
-        Ok(completion)
+        // Cached responses keep the `{"items": [...]}` shape they were stored in
+        let cached: OpenAICompletionResponse =
+            serde_json::from_str(include_str!("../../tests/fixtures/completion.moonshot.json")).unwrap();
+        let cached: OpenAICompletionResponse =
+            serde_json::from_value(serde_json::to_value(&cached).unwrap()).unwrap();
+        let content = cached.choices[0].message.content.as_ref().unwrap();
+        assert_eq!(content.items().len(), 1);
     }
 }
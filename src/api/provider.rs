@@ -0,0 +1,249 @@
+// Upstream API abstraction
+//
+// A `Provider` knows how one upstream API shapes requests, authenticates,
+// reports errors and lists its models. `ProviderClient` owns the HTTP flow
+// (deadlines, retries, streaming) and defers every API-specific decision to
+// the provider, so a new backend only has to implement the trait.
+
+use crate::api::anthropic::AnthropicProvider;
+use crate::api::openai::{CompatibleProvider, MoonshotProvider, OpenAIProvider};
+use crate::api::transport::HttpTransport;
+use crate::bindings::theater::simple::http_client::{HttpRequest, HttpResponse};
+use crate::logging;
+use crate::types::{
+    api::OpenAICompletionRequest,
+    response::{OpenAICompletionResponse, OpenAIError, OpenAIModelInfo, OpenAIModelList},
+    state::{ProviderKind, RetryConfig, UpstreamConfig},
+    stream::StreamEvent,
+};
+
+/// Everything that differs between upstream APIs
+pub trait Provider {
+    /// Short name used in logs
+    fn name(&self) -> &'static str;
+
+    /// Base URL used when `Config.base_url` is unset
+    fn default_base_url(&self) -> &'static str;
+
+    /// Headers that authenticate a request
+    fn auth_headers(&self, api_key: &str) -> Vec<(String, String)>;
+
+    /// Path of the completion endpoint, relative to the base URL
+    fn completion_path(&self) -> &'static str;
+
+    /// Build the JSON body for a completion, asking for SSE when `stream` is set
+    fn request_body(&self, request: &OpenAICompletionRequest, stream: bool) -> serde_json::Value;
+
+    /// Parse a successful non-streamed completion
+    fn parse_response(&self, body: &[u8]) -> Result<OpenAICompletionResponse, OpenAIError>;
+
+    /// Parse a successful streamed body, reporting each delta to `on_event`
+    fn parse_stream(
+        &self,
+        body: &[u8],
+        on_event: &mut dyn FnMut(StreamEvent),
+    ) -> Result<OpenAICompletionResponse, OpenAIError>;
+
+    /// Map a non-200 response to the matching error
+    fn error_from_response(&self, response: HttpResponse) -> OpenAIError {
+        HttpTransport::error_from_response(response)
+    }
+
+    /// Parse the `/models` response, merged with our curated metadata
+    fn parse_models(&self, body: &[u8]) -> Result<Vec<OpenAIModelInfo>, OpenAIError> {
+        let model_list: OpenAIModelList = serde_json::from_slice(body)?;
+        logging::debug(&format!("Provider reported {} models", model_list.data.len()));
+        Ok(OpenAIModelInfo::merge_with_known(model_list.data))
+    }
+
+    /// Built-in model list used while `/models` is unavailable
+    fn static_models(&self) -> Vec<OpenAIModelInfo>;
+
+    /// Whether a failed response is worth retrying
+    fn is_retryable_status(&self, status: u16) -> bool {
+        matches!(
+            status,
+            429 // Rate limit exceeded
+            | 502 // Bad gateway
+            | 503 // Service unavailable
+            | 504 // Gateway timeout
+        )
+    }
+
+    /// Whether image parts reach the model, rather than text placeholders
+    fn sends_images(&self) -> bool {
+        true
+    }
+}
+
+/// The built-in models served by `owner`
+pub fn static_models_owned_by(owner: &str) -> Vec<OpenAIModelInfo> {
+    OpenAIModelInfo::get_available_models()
+        .into_iter()
+        .filter(|model| model.owned_by == owner)
+        .collect()
+}
+
+/// The provider an upstream is configured to use
+pub fn provider_for(upstream: &UpstreamConfig) -> Box<dyn Provider> {
+    match upstream.provider {
+        ProviderKind::Compatible => {
+            Box::new(CompatibleProvider::new(upstream.content_format.clone().unwrap_or_default()))
+        }
+        ProviderKind::OpenAI => Box::new(OpenAIProvider),
+        ProviderKind::Moonshot => Box::new(MoonshotProvider::new(upstream.content_format.clone())),
+        ProviderKind::Anthropic => Box::new(AnthropicProvider),
+    }
+}
+
+/// Client for whichever API the actor is configured to use
+pub struct ProviderClient {
+    /// API-specific behaviour
+    provider: Box<dyn Provider>,
+    /// API key
+    api_key: String,
+    /// Base URL for the API
+    base_url: String,
+    /// Retrying HTTP sender
+    transport: HttpTransport,
+}
+
+impl ProviderClient {
+    /// Create a client for the provider's public API
+    pub fn new(provider: Box<dyn Provider>, api_key: String) -> Self {
+        let base_url = provider.default_base_url().to_string();
+        Self {
+            provider,
+            api_key,
+            base_url,
+            transport: HttpTransport::default(),
+        }
+    }
+
+    /// Send requests to a custom base URL
+    pub fn with_base_url(mut self, base_url: String) -> Self {
+        self.base_url = base_url;
+        self
+    }
+
+    /// Bound each HTTP attempt by a deadline
+    pub fn with_timeout(mut self, timeout_ms: u32) -> Self {
+        self.transport = HttpTransport::new(Some(timeout_ms));
+        self
+    }
+
+    pub fn provider(&self) -> &dyn Provider {
+        self.provider.as_ref()
+    }
+
+    fn headers(&self) -> Vec<(String, String)> {
+        let mut headers = self.provider.auth_headers(&self.api_key);
+        headers.push(("content-type".to_string(), "application/json".to_string()));
+        headers.push(("user-agent".to_string(), "moonshot-proxy/0.1.0".to_string()));
+        headers
+    }
+
+    /// List available models from the provider's `/models` endpoint
+    pub fn list_models(&self) -> Result<Vec<OpenAIModelInfo>, OpenAIError> {
+        logging::info(&format!("Fetching models from {}/models", self.base_url));
+
+        let http_request = HttpRequest {
+            method: "GET".to_string(),
+            uri: format!("{}/models", self.base_url),
+            headers: self.headers(),
+            body: None,
+        };
+
+        // A single attempt: callers fall back to the static list on failure
+        let response = self.transport.send_once(&http_request)?;
+
+        if response.status != 200 {
+            return Err(self.provider.error_from_response(response));
+        }
+
+        let body = response
+            .body
+            .ok_or_else(|| OpenAIError::InvalidResponse("No response body".to_string()))?;
+        self.provider.parse_models(&body)
+    }
+
+    /// The body that would be sent for a non-streamed completion
+    pub fn request_body(&self, request: &OpenAICompletionRequest) -> serde_json::Value {
+        self.provider.request_body(request, false)
+    }
+
+    /// Send a completion request and return the successful response body
+    fn send_completion(
+        &self,
+        request: &OpenAICompletionRequest,
+        retry_config: &RetryConfig,
+        stream: bool,
+    ) -> Result<Vec<u8>, OpenAIError> {
+        let request_body = serde_json::to_vec(&self.provider.request_body(request, stream))?;
+
+        logging::debug(&format!(
+            "{} API request: {}",
+            self.provider.name(),
+            logging::body(&request_body)
+        ));
+
+        let http_request = HttpRequest {
+            method: "POST".to_string(),
+            uri: format!("{}{}", self.base_url, self.provider.completion_path()),
+            headers: self.headers(),
+            body: Some(request_body),
+        };
+
+        let response = self.transport.execute_with_retry(&http_request, retry_config, &|status| {
+            self.provider.is_retryable_status(status)
+        })?;
+
+        if response.status != 200 {
+            return Err(self.provider.error_from_response(response));
+        }
+
+        response
+            .body
+            .ok_or_else(|| OpenAIError::InvalidResponse("No response body".to_string()))
+    }
+
+    /// Generate a completion with retry logic
+    pub fn generate_completion(
+        &self,
+        request: &OpenAICompletionRequest,
+        retry_config: &RetryConfig,
+    ) -> Result<OpenAICompletionResponse, OpenAIError> {
+        logging::info(&format!("Generating completion with model: {}", request.model));
+
+        let body = self.send_completion(request, retry_config, false)?;
+
+        logging::debug(&format!(
+            "{} API response: {}",
+            self.provider.name(),
+            logging::body(&body)
+        ));
+
+        let completion = self.provider.parse_response(&body)?;
+
+        logging::info("Completion generated successfully");
+
+        Ok(completion)
+    }
+
+    /// Generate a streamed completion, reporting each delta to `on_event`
+    ///
+    /// The host http-client hands us the whole body once the provider closes the
+    /// stream, so events are emitted as soon as the response arrives rather than
+    /// while it is still being generated. The assembled response is returned.
+    pub fn stream_completion(
+        &self,
+        request: &OpenAICompletionRequest,
+        retry_config: &RetryConfig,
+        on_event: &mut dyn FnMut(StreamEvent),
+    ) -> Result<OpenAICompletionResponse, OpenAIError> {
+        logging::info(&format!("Streaming completion with model: {}", request.model));
+
+        let body = self.send_completion(request, retry_config, true)?;
+        self.provider.parse_stream(&body, on_event)
+    }
+}
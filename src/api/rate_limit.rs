@@ -0,0 +1,229 @@
+// Rate limit header parsing for OpenAI-compatible providers
+//
+// Providers tell us how long to back off in two ways:
+// - `Retry-After`, either as delay-seconds or as an HTTP-date
+// - `x-ratelimit-remaining-*` / `x-ratelimit-reset-*` pairs (OpenAI and Moonshot),
+//   where the reset value is a duration such as "1s", "6m0s" or "20ms"
+
+use crate::date::days_from_civil;
+
+/// Rate limit hints extracted from a response's headers
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RateLimitInfo {
+    /// Delay requested by `Retry-After`, in milliseconds
+    pub retry_after_ms: Option<u64>,
+    pub remaining_requests: Option<u64>,
+    pub remaining_tokens: Option<u64>,
+    /// Time until the request budget resets, in milliseconds
+    pub reset_requests_ms: Option<u64>,
+    /// Time until the token budget resets, in milliseconds
+    pub reset_tokens_ms: Option<u64>,
+}
+
+impl RateLimitInfo {
+    /// Extract rate limit hints from response headers
+    ///
+    /// `now_ms` is the current time in milliseconds since the UNIX epoch and is
+    /// used to turn HTTP-dates into delays.
+    pub fn from_headers(headers: &[(String, String)], now_ms: u64) -> Self {
+        let mut info = Self::default();
+
+        for (name, value) in headers {
+            let value = value.trim();
+            match name.to_ascii_lowercase().as_str() {
+                "retry-after" => info.retry_after_ms = parse_retry_after(value, now_ms),
+                "retry-after-ms" => {
+                    info.retry_after_ms = value.parse::<f64>().ok().map(|ms| ms.max(0.0) as u64)
+                }
+                "x-ratelimit-remaining-requests" => {
+                    info.remaining_requests = value.parse().ok()
+                }
+                "x-ratelimit-remaining-tokens" => info.remaining_tokens = value.parse().ok(),
+                "x-ratelimit-reset-requests" => {
+                    info.reset_requests_ms = parse_reset(value, now_ms)
+                }
+                "x-ratelimit-reset-tokens" => info.reset_tokens_ms = parse_reset(value, now_ms),
+                _ => {}
+            }
+        }
+
+        info
+    }
+
+    /// The delay the provider asked for, if any
+    ///
+    /// `Retry-After` wins when present. Otherwise the reset time of every
+    /// exhausted budget is considered and the longest one is used.
+    pub fn suggested_delay_ms(&self) -> Option<u64> {
+        if self.retry_after_ms.is_some() {
+            return self.retry_after_ms;
+        }
+
+        let exhausted_requests = match self.remaining_requests {
+            Some(0) => self.reset_requests_ms,
+            _ => None,
+        };
+        let exhausted_tokens = match self.remaining_tokens {
+            Some(0) => self.reset_tokens_ms,
+            _ => None,
+        };
+
+        match (exhausted_requests, exhausted_tokens) {
+            (Some(a), Some(b)) => Some(a.max(b)),
+            (a, b) => a.or(b),
+        }
+    }
+
+    /// The suggested delay rounded up to whole seconds
+    pub fn retry_after_secs(&self) -> Option<u64> {
+        self.suggested_delay_ms().map(|ms| ms.div_ceil(1000))
+    }
+}
+
+/// Parse a `Retry-After` value in either delay-seconds or HTTP-date form
+pub fn parse_retry_after(value: &str, now_ms: u64) -> Option<u64> {
+    if let Ok(seconds) = value.parse::<f64>() {
+        return Some((seconds.max(0.0) * 1000.0) as u64);
+    }
+
+    parse_http_date(value).map(|date_ms| date_ms.saturating_sub(now_ms))
+}
+
+/// Parse an `x-ratelimit-reset-*` value
+///
+/// Accepts Go-style durations ("1s", "6m0s", "1h2m3.5s", "20ms"), plain
+/// seconds, and UNIX timestamps in seconds as sent by some compatible servers.
+pub fn parse_reset(value: &str, now_ms: u64) -> Option<u64> {
+    if let Ok(number) = value.parse::<f64>() {
+        // Anything this large is a timestamp rather than a delay
+        if number > 1_000_000_000.0 {
+            return Some(((number * 1000.0) as u64).saturating_sub(now_ms));
+        }
+        return Some((number.max(0.0) * 1000.0) as u64);
+    }
+
+    parse_duration(value)
+}
+
+/// Parse a Go-style duration string into milliseconds
+fn parse_duration(value: &str) -> Option<u64> {
+    let mut total_ms = 0.0;
+    let mut rest = value;
+
+    if rest.is_empty() {
+        return None;
+    }
+
+    while !rest.is_empty() {
+        let number_len = rest
+            .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+            .unwrap_or(rest.len());
+        if number_len == 0 {
+            return None;
+        }
+        let number: f64 = rest[..number_len].parse().ok()?;
+        rest = &rest[number_len..];
+
+        let unit_len = rest
+            .find(|c: char| c.is_ascii_digit() || c == '.')
+            .unwrap_or(rest.len());
+        let multiplier = match &rest[..unit_len] {
+            "h" => 3_600_000.0,
+            "m" => 60_000.0,
+            "s" => 1000.0,
+            "ms" => 1.0,
+            "us" | "µs" => 0.001,
+            "ns" => 0.000_001,
+            _ => return None,
+        };
+        rest = &rest[unit_len..];
+
+        total_ms += number * multiplier;
+    }
+
+    Some(total_ms.ceil() as u64)
+}
+
+/// Parse an IMF-fixdate (`Sun, 06 Nov 1994 08:49:37 GMT`) into milliseconds since the epoch
+fn parse_http_date(value: &str) -> Option<u64> {
+    let parts: Vec<&str> = value.split_whitespace().collect();
+    if parts.len() != 6 || parts[5] != "GMT" {
+        return None;
+    }
+
+    let day: u32 = parts[1].parse().ok()?;
+    let month = match parts[2] {
+        "Jan" => 1,
+        "Feb" => 2,
+        "Mar" => 3,
+        "Apr" => 4,
+        "May" => 5,
+        "Jun" => 6,
+        "Jul" => 7,
+        "Aug" => 8,
+        "Sep" => 9,
+        "Oct" => 10,
+        "Nov" => 11,
+        "Dec" => 12,
+        _ => return None,
+    };
+    let year: i64 = parts[3].parse().ok()?;
+
+    let time: Vec<u64> = parts[4]
+        .split(':')
+        .map(|part| part.parse().ok())
+        .collect::<Option<Vec<u64>>>()?;
+    if time.len() != 3 || time[0] > 23 || time[1] > 59 || time[2] > 60 || day == 0 || day > 31 {
+        return None;
+    }
+
+    let days = days_from_civil(year, month, day);
+    if days < 0 {
+        return None;
+    }
+
+    let seconds = days as u64 * 86_400 + time[0] * 3600 + time[1] * 60 + time[2];
+    Some(seconds * 1000)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_retry_after_forms() {
+        assert_eq!(parse_retry_after("120", 0), Some(120_000));
+        // 1994-11-06T08:49:37Z is 784111777 seconds after the epoch
+        let now_ms = 784_111_777_000 - 5_000;
+        assert_eq!(
+            parse_retry_after("Sun, 06 Nov 1994 08:49:37 GMT", now_ms),
+            Some(5_000)
+        );
+        assert_eq!(parse_retry_after("soon", 0), None);
+    }
+
+    #[test]
+    fn test_reset_durations() {
+        assert_eq!(parse_reset("1s", 0), Some(1000));
+        assert_eq!(parse_reset("6m0s", 0), Some(360_000));
+        assert_eq!(parse_reset("20ms", 0), Some(20));
+        assert_eq!(parse_reset("1h2m3.5s", 0), Some(3_723_500));
+        assert_eq!(parse_reset("1.5", 0), Some(1500));
+    }
+
+    #[test]
+    fn test_suggested_delay_uses_exhausted_budgets() {
+        let headers = vec![
+            ("x-ratelimit-remaining-requests".to_string(), "0".to_string()),
+            ("x-ratelimit-reset-requests".to_string(), "2s".to_string()),
+            ("x-ratelimit-remaining-tokens".to_string(), "1500".to_string()),
+            ("x-ratelimit-reset-tokens".to_string(), "30s".to_string()),
+        ];
+        let info = RateLimitInfo::from_headers(&headers, 0);
+        assert_eq!(info.suggested_delay_ms(), Some(2000));
+        assert_eq!(info.retry_after_secs(), Some(2));
+
+        let headers = vec![("Retry-After".to_string(), "7".to_string())];
+        assert_eq!(RateLimitInfo::from_headers(&headers, 0).suggested_delay_ms(), Some(7000));
+    }
+}
@@ -0,0 +1,306 @@
+// Shared HTTP plumbing for provider clients
+//
+// Every backend sends its requests through the same retry loop, so deadlines,
+// backoff and rate-limit handling behave identically whichever API is used.
+
+use crate::api::rate_limit::RateLimitInfo;
+use crate::bindings::theater::simple::http_client::{send_http, HttpRequest, HttpResponse};
+use crate::bindings::theater::simple::random;
+use crate::bindings::theater::simple::timing;
+use crate::logging;
+use crate::types::{
+    response::{OpenAIError, ProviderError},
+    state::{JitterStrategy, RetryConfig},
+};
+
+/// Sends HTTP requests with per-attempt deadlines and retries
+#[derive(Debug, Clone, Default)]
+pub struct HttpTransport {
+    /// Per-attempt request deadline in milliseconds
+    timeout_ms: Option<u32>,
+}
+
+impl HttpTransport {
+    pub fn new(timeout_ms: Option<u32>) -> Self {
+        Self { timeout_ms }
+    }
+
+    /// Send a request once, without retrying
+    ///
+    /// The host http-client can't be cancelled mid-request, so the deadline is
+    /// checked when the attempt returns. A late successful response is kept,
+    /// since the completion has already been generated and billed; a late
+    /// failure fails with `Timeout`, like one the host reports as timed out.
+    pub fn send_once(&self, request: &HttpRequest) -> Result<HttpResponse, OpenAIError> {
+        let attempt_start = timing::now();
+        let result = send_http(request);
+        let elapsed_ms = timing::now() - attempt_start;
+        match result {
+            Ok(response) if self.past_deadline(elapsed_ms) => {
+                if (200..300).contains(&response.status) {
+                    logging::info(&format!(
+                        "Response {} arrived after {} ms, past the deadline; keeping it",
+                        response.status, elapsed_ms
+                    ));
+                    Ok(response)
+                } else {
+                    logging::info(&format!(
+                        "Discarding response {} that arrived after {} ms, past the deadline",
+                        response.status, elapsed_ms
+                    ));
+                    Err(self.timeout(elapsed_ms))
+                }
+            }
+            Ok(response) => Ok(response),
+            Err(e) => Err(self.transport_error(e, elapsed_ms)),
+        }
+    }
+
+    fn past_deadline(&self, elapsed_ms: u64) -> bool {
+        self.timeout_ms
+            .is_some_and(|timeout_ms| elapsed_ms >= timeout_ms as u64)
+    }
+
+    fn timeout(&self, elapsed_ms: u64) -> OpenAIError {
+        OpenAIError::Timeout {
+            timeout_ms: self.timeout_ms.map(|t| t as u64),
+            elapsed_ms,
+        }
+    }
+
+    /// Classify a transport failure, separating timeouts from other HTTP errors
+    ///
+    /// An attempt counts as timed out when the host reports a timeout or when
+    /// it failed after the deadline had already passed.
+    pub fn transport_error(&self, message: String, elapsed_ms: u64) -> OpenAIError {
+        let lowered = message.to_lowercase();
+        let host_timeout = lowered.contains("timed out") || lowered.contains("timeout");
+
+        if host_timeout || self.past_deadline(elapsed_ms) {
+            self.timeout(elapsed_ms)
+        } else {
+            OpenAIError::HttpError(message)
+        }
+    }
+
+    /// Execute an HTTP request with jittered exponential backoff
+    ///
+    /// `is_retryable_status` is the provider's default status policy; see
+    /// `is_retryable_response` for how the retry config refines it.
+    /// `Retry-After` and `x-ratelimit-*` headers on retryable responses override
+    /// the backoff schedule, bounded by `max_total_timeout_ms`.
+    pub fn execute_with_retry(
+        &self,
+        request: &HttpRequest,
+        retry_config: &RetryConfig,
+        is_retryable_status: &dyn Fn(u16) -> bool,
+    ) -> Result<HttpResponse, OpenAIError> {
+        let start_time = timing::now();
+        let mut current_delay = retry_config.initial_delay_ms;
+        let mut previous_delay = retry_config.initial_delay_ms as u64;
+        let mut attempt = 0;
+
+        loop {
+            attempt += 1;
+            
+            logging::debug(&format!("HTTP request attempt {}/{}", attempt, retry_config.max_retries + 1));
+            logging::debug(&format!(
+                "{} {} ({})",
+                request.method,
+                request.uri,
+                logging::headers(&request.headers)
+            ));
+
+            // Send the request; late failures come back as `Timeout` and are retried
+            let response = match self.send_once(request) {
+                Ok(resp) => resp,
+                Err(error) => {
+                    logging::error(&format!("HTTP request failed: {}", error));
+                    if attempt > retry_config.max_retries {
+                        return Err(error);
+                    }
+                    
+                    // Check if we've exceeded the total timeout
+                    let elapsed = timing::now() - start_time;
+                    if elapsed >= retry_config.max_total_timeout_ms as u64 {
+                        logging::error("Total retry timeout exceeded");
+                        return Err(error);
+                    }
+                    
+                    // Wait before retrying
+                    let delay = backoff_delay(retry_config, current_delay, previous_delay);
+                    logging::info(&format!("Retrying after {} ms due to HTTP error", delay));
+                    let _ = timing::sleep(delay);
+                    previous_delay = delay;
+                    current_delay = std::cmp::min(
+                        (current_delay as f64 * retry_config.backoff_multiplier) as u32,
+                        retry_config.max_delay_ms
+                    );
+                    continue;
+                }
+            };
+
+            // Check if we got a successful response
+            if response.status == 200 {
+                logging::debug(&format!("Request successful on attempt {}", attempt));
+                return Ok(response);
+            }
+
+            // Check if this is a retryable error
+            if !is_retryable_response(&response, retry_config, is_retryable_status) {
+                logging::error(&format!("Non-retryable error: {}", response.status));
+                return Ok(response); // Return the error response to be handled by caller
+            }
+
+            // Check if we've exhausted our retries
+            if attempt > retry_config.max_retries {
+                logging::error(&format!("Max retries ({}) exceeded", retry_config.max_retries));
+                return Ok(response);
+            }
+
+            // Check if we've exceeded the total timeout
+            let elapsed = timing::now() - start_time;
+            if elapsed >= retry_config.max_total_timeout_ms as u64 {
+                logging::error("Total retry timeout exceeded");
+                return Ok(response);
+            }
+
+            // Prefer the delay the provider asked for over our own backoff schedule
+            let rate_limit = RateLimitInfo::from_headers(&response.headers, timing::now());
+            let delay = match rate_limit.suggested_delay_ms() {
+                Some(requested) => {
+                    logging::info(&format!("Provider requested a delay of {} ms", requested));
+                    requested
+                }
+                None => backoff_delay(retry_config, current_delay, previous_delay),
+            };
+
+            // Don't sleep past the total budget; the caller gets the error instead
+            let remaining = retry_config.max_total_timeout_ms as u64 - elapsed;
+            if delay > remaining {
+                logging::info(&format!(
+                    "Retry delay of {} ms exceeds remaining budget of {} ms",
+                    delay, remaining
+                ));
+                return Ok(response);
+            }
+
+            // Log the retry attempt
+            let message = String::from_utf8_lossy(&response.body.unwrap_or_default()).to_string();
+            logging::info(&format!(
+                "Retryable error {} on attempt {}: {}",
+                response.status, attempt, logging::body(message.as_bytes())
+            ));
+            logging::info(&format!("Retrying after {} ms", delay));
+
+            // Wait before retrying
+            let _ = timing::sleep(delay);
+            previous_delay = delay;
+            
+            // Update delay for next attempt (exponential backoff)
+            current_delay = std::cmp::min(
+                (current_delay as f64 * retry_config.backoff_multiplier) as u32,
+                retry_config.max_delay_ms
+            );
+        }
+    }
+
+    /// Map a non-200 response to the matching error
+    pub fn error_from_response(response: HttpResponse) -> OpenAIError {
+        let rate_limit = RateLimitInfo::from_headers(&response.headers, timing::now());
+        let error = Box::new(ProviderError::from_response(
+            response.status,
+            &response.headers,
+            &response.body.unwrap_or_default(),
+        ));
+
+        // Handle specific error cases
+        match response.status {
+            401 => OpenAIError::AuthenticationError(error),
+            429 => {
+                let retry_after = rate_limit.retry_after_secs();
+                logging::info(&format!("Rate limited, retry after: {:?} seconds", retry_after));
+                OpenAIError::RateLimitExceeded { retry_after, error }
+            }
+            _ => OpenAIError::ApiError(error),
+        }
+    }
+}
+
+/// The next backoff delay with the configured jitter applied
+fn backoff_delay(retry_config: &RetryConfig, current_delay: u32, previous_delay: u64) -> u64 {
+    let plain_delay = (current_delay as u64).min(retry_config.max_delay_ms as u64);
+    if retry_config.jitter == JitterStrategy::None {
+        return plain_delay;
+    }
+
+    // Without a random source, fall back to the plain backoff delay
+    match random::random_float() {
+        Ok(sample) => retry_config.jittered_delay(current_delay as u64, previous_delay, sample),
+        Err(e) => {
+            logging::debug(&format!("Random source unavailable, not jittering: {}", e));
+            plain_delay
+        }
+    }
+}
+
+/// Whether a failed response should be retried
+///
+/// `retryable_statuses`, when set, replaces the provider's status policy.
+/// Responses whose error `type` or `code` is in `retryable_error_types` (such
+/// as Moonshot's `engine_overloaded_error`) are retried whatever their status.
+fn is_retryable_response(
+    response: &HttpResponse,
+    retry_config: &RetryConfig,
+    is_retryable_status: &dyn Fn(u16) -> bool,
+) -> bool {
+    let status_retryable = match &retry_config.retryable_statuses {
+        Some(statuses) => statuses.contains(&response.status),
+        None => is_retryable_status(response.status),
+    };
+    if status_retryable {
+        return true;
+    }
+
+    let error = ProviderError::from_response(
+        response.status,
+        &response.headers,
+        response.body.as_deref().unwrap_or_default(),
+    );
+    [error.error_type, error.code]
+        .into_iter()
+        .flatten()
+        .any(|class| retry_config.retryable_error_types.contains(&class))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn response(status: u16, body: &str) -> HttpResponse {
+        HttpResponse {
+            status,
+            headers: Vec::new(),
+            body: Some(body.as_bytes().to_vec()),
+        }
+    }
+
+    #[test]
+    fn test_retryable_response() {
+        let provider_default = |status: u16| matches!(status, 429 | 502 | 503 | 504);
+        let mut config = RetryConfig::default();
+
+        assert!(is_retryable_response(&response(503, ""), &config, &provider_default));
+        assert!(!is_retryable_response(&response(500, ""), &config, &provider_default));
+
+        let overloaded = r#"{"error":{"message":"The engine is currently overloaded","type":"engine_overloaded_error"}}"#;
+        assert!(is_retryable_response(&response(400, overloaded), &config, &provider_default));
+
+        config.retryable_statuses = Some(vec![500, 408]);
+        assert!(is_retryable_response(&response(500, ""), &config, &provider_default));
+        assert!(!is_retryable_response(&response(503, ""), &config, &provider_default));
+
+        let quota = r#"{"error":{"type":"exceeded_current_quota_error"}}"#;
+        assert!(!is_retryable_response(&response(429, quota), &config, &provider_default));
+    }
+}
@@ -2083,6 +2083,2924 @@ pub mod theater {
                 }
             }
         }
+        /// # Message Server Host Interface
+        ///
+        /// Provides functions for actors to send messages to other actors and manage communication channels.
+        ///
+        /// ## Purpose
+        ///
+        /// This interface enables actors to initiate various types of communication:
+        /// - Send one-way messages to other actors
+        /// - Make request-response interactions with other actors
+        /// - Establish and use bidirectional communication channels
+        ///
+        /// These functions allow actors to collaborate, share data, and coordinate their activities
+        /// within the Theater system.
+        ///
+        /// ## Example
+        ///
+        /// ```rust
+        /// use ntwk::theater::message_server_host;
+        /// use ntwk::theater::types::actor_id;
+        /// use serde_json::json;
+        ///
+        /// async fn example() -> Result<(), String> {
+        /// // Get the target actor ID (in a real scenario)
+        /// let target_actor = actor_id { id: "actor-123".to_string() };
+        ///
+        /// // Send a one-way message
+        /// let message = json!({"action": "update", "value": 42});
+        /// message_server_host::send(target_actor.clone(), message)?;
+        ///
+        /// // Make a request and get a response
+        /// let request = json!({"action": "query", "key": "user-profile"});
+        /// let response = message_server_host::request(target_actor.clone(), request)?;
+        /// println!("Received response: {}", response);
+        ///
+        /// // Open a channel for ongoing communication
+        /// let initial_msg = json!({"action": "subscribe", "topic": "updates"});
+        /// let channel_id = message_server_host::open_channel(target_actor, initial_msg)?;
+        ///
+        /// // Send messages on the channel
+        /// message_server_host::send_on_channel(channel_id.clone(), json!({"update": 1}))?;
+        /// message_server_host::send_on_channel(channel_id.clone(), json!({"update": 2}))?;
+        ///
+        /// // Close the channel when done
+        /// message_server_host::close_channel(channel_id)?;
+        ///
+        /// Ok(())
+        /// }
+        /// ```
+        ///
+        /// ## Security
+        ///
+        /// The message server enforces security boundaries to ensure that:
+        /// - Actors can only communicate with actors they have permission to access
+        /// - Messages are delivered reliably and in order
+        /// - Channel operations are authenticated
+        ///
+        /// All message operations are tracked in the actor's event chain for complete auditability.
+        ///
+        /// ## Implementation Notes
+        ///
+        /// The message server operations are asynchronous but appear synchronous to the WebAssembly
+        /// component. The runtime suspends the actor's execution as needed without blocking the
+        /// entire system.
+        #[allow(dead_code, async_fn_in_trait, unused_imports, clippy::all)]
+        pub mod message_server_host {
+            #[used]
+            #[doc(hidden)]
+            static __FORCE_SECTION_REF: fn() = super::super::super::__link_custom_section_describing_imports;
+            use super::super::super::_rt;
+            pub type ActorId = super::super::super::theater::simple::types::ActorId;
+            pub type ChannelId = super::super::super::theater::simple::types::ChannelId;
+            #[allow(unused_unsafe, clippy::all)]
+            /// # Send one-way message
+            ///
+            /// Sends a message to another actor without waiting for a response.
+            ///
+            /// ## Parameters
+            ///
+            /// * `actor-id` - ID of the target actor
+            /// * `msg` - JSON message payload to send
+            ///
+            /// ## Returns
+            ///
+            /// * `Ok(_)` - Message was successfully sent
+            /// * `Err(string)` - Error message if send fails
+            ///
+            /// ## Example
+            ///
+            /// ```rust
+            /// use ntwk::theater::message_server_host;
+            /// use ntwk::theater::types::actor_id;
+            /// use serde_json::json;
+            ///
+            /// // Send a notification
+            /// let target = actor_id { id: "logging-service".to_string() };
+            /// let log_msg = json!({
+            /// "level": "info",
+            /// "message": "User logged in",
+            /// "timestamp": 1625097600000
+            /// });
+            /// message_server_host::send(target, log_msg)?;
+            /// ```
+            ///
+            /// ## Security
+            ///
+            /// The runtime verifies that the sender has permission to send messages to the
+            /// target actor before delivery.
+            pub fn send(actor_id: &str, msg: &[u8]) -> Result<(), _rt::String> {
+                unsafe {
+                    #[cfg_attr(target_pointer_width = "64", repr(align(8)))]
+                    #[cfg_attr(target_pointer_width = "32", repr(align(4)))]
+                    struct RetArea(
+                        [::core::mem::MaybeUninit<
+                            u8,
+                        >; 3 * ::core::mem::size_of::<*const u8>()],
+                    );
+                    let mut ret_area = RetArea(
+                        [::core::mem::MaybeUninit::uninit(); 3
+                            * ::core::mem::size_of::<*const u8>()],
+                    );
+                    let vec0 = actor_id;
+                    let ptr0 = vec0.as_ptr().cast::<u8>();
+                    let len0 = vec0.len();
+                    let vec1 = msg;
+                    let ptr1 = vec1.as_ptr().cast::<u8>();
+                    let len1 = vec1.len();
+                    let ptr2 = ret_area.0.as_mut_ptr().cast::<u8>();
+                    #[cfg(target_arch = "wasm32")]
+                    #[link(wasm_import_module = "theater:simple/message-server-host")]
+                    unsafe extern "C" {
+                        #[link_name = "send"]
+                        fn wit_import3(
+                            _: *mut u8,
+                            _: usize,
+                            _: *mut u8,
+                            _: usize,
+                            _: *mut u8,
+                        );
+                    }
+                    #[cfg(not(target_arch = "wasm32"))]
+                    unsafe extern "C" fn wit_import3(
+                        _: *mut u8,
+                        _: usize,
+                        _: *mut u8,
+                        _: usize,
+                        _: *mut u8,
+                    ) {
+                        unreachable!()
+                    }
+                    unsafe {
+                        wit_import3(ptr0.cast_mut(), len0, ptr1.cast_mut(), len1, ptr2)
+                    };
+                    let l4 = i32::from(*ptr2.add(0).cast::<u8>());
+                    let result8 = match l4 {
+                        0 => {
+                            let e = ();
+                            Ok(e)
+                        }
+                        1 => {
+                            let e = {
+                                let l5 = *ptr2
+                                    .add(::core::mem::size_of::<*const u8>())
+                                    .cast::<*mut u8>();
+                                let l6 = *ptr2
+                                    .add(2 * ::core::mem::size_of::<*const u8>())
+                                    .cast::<usize>();
+                                let len7 = l6;
+                                let bytes7 = _rt::Vec::from_raw_parts(
+                                    l5.cast(),
+                                    len7,
+                                    len7,
+                                );
+                                _rt::string_lift(bytes7)
+                            };
+                            Err(e)
+                        }
+                        _ => _rt::invalid_enum_discriminant(),
+                    };
+                    result8
+                }
+            }
+            #[allow(unused_unsafe, clippy::all)]
+            /// # Send request and await response
+            ///
+            /// Sends a message to another actor and waits for a response.
+            ///
+            /// ## Parameters
+            ///
+            /// * `actor-id` - ID of the target actor
+            /// * `msg` - JSON request payload to send
+            ///
+            /// ## Returns
+            ///
+            /// * `Ok(json)` - The response from the target actor
+            /// * `Err(string)` - Error message if the request fails
+            ///
+            /// ## Example
+            ///
+            /// ```rust
+            /// use ntwk::theater::message_server_host;
+            /// use ntwk::theater::types::actor_id;
+            /// use serde_json::json;
+            ///
+            /// // Query a data service
+            /// let data_service = actor_id { id: "data-service".to_string() };
+            /// let query = json!({
+            /// "query": "SELECT * FROM users WHERE id = ?",
+            /// "parameters": [42]
+            /// });
+            /// let result = message_server_host::request(data_service, query)?;
+            /// ```
+            ///
+            /// ## Implementation Notes
+            ///
+            /// This function suspends the calling actor's execution until a response is received
+            /// or a timeout occurs. The runtime handles the suspension efficiently without
+            /// blocking other actors.
+            pub fn request(
+                actor_id: &str,
+                msg: &[u8],
+            ) -> Result<_rt::Vec<u8>, _rt::String> {
+                unsafe {
+                    #[cfg_attr(target_pointer_width = "64", repr(align(8)))]
+                    #[cfg_attr(target_pointer_width = "32", repr(align(4)))]
+                    struct RetArea(
+                        [::core::mem::MaybeUninit<
+                            u8,
+                        >; 3 * ::core::mem::size_of::<*const u8>()],
+                    );
+                    let mut ret_area = RetArea(
+                        [::core::mem::MaybeUninit::uninit(); 3
+                            * ::core::mem::size_of::<*const u8>()],
+                    );
+                    let vec0 = actor_id;
+                    let ptr0 = vec0.as_ptr().cast::<u8>();
+                    let len0 = vec0.len();
+                    let vec1 = msg;
+                    let ptr1 = vec1.as_ptr().cast::<u8>();
+                    let len1 = vec1.len();
+                    let ptr2 = ret_area.0.as_mut_ptr().cast::<u8>();
+                    #[cfg(target_arch = "wasm32")]
+                    #[link(wasm_import_module = "theater:simple/message-server-host")]
+                    unsafe extern "C" {
+                        #[link_name = "request"]
+                        fn wit_import3(
+                            _: *mut u8,
+                            _: usize,
+                            _: *mut u8,
+                            _: usize,
+                            _: *mut u8,
+                        );
+                    }
+                    #[cfg(not(target_arch = "wasm32"))]
+                    unsafe extern "C" fn wit_import3(
+                        _: *mut u8,
+                        _: usize,
+                        _: *mut u8,
+                        _: usize,
+                        _: *mut u8,
+                    ) {
+                        unreachable!()
+                    }
+                    unsafe {
+                        wit_import3(ptr0.cast_mut(), len0, ptr1.cast_mut(), len1, ptr2)
+                    };
+                    let l4 = i32::from(*ptr2.add(0).cast::<u8>());
+                    let result11 = match l4 {
+                        0 => {
+                            let e = {
+                                let l5 = *ptr2
+                                    .add(::core::mem::size_of::<*const u8>())
+                                    .cast::<*mut u8>();
+                                let l6 = *ptr2
+                                    .add(2 * ::core::mem::size_of::<*const u8>())
+                                    .cast::<usize>();
+                                let len7 = l6;
+                                _rt::Vec::from_raw_parts(l5.cast(), len7, len7)
+                            };
+                            Ok(e)
+                        }
+                        1 => {
+                            let e = {
+                                let l8 = *ptr2
+                                    .add(::core::mem::size_of::<*const u8>())
+                                    .cast::<*mut u8>();
+                                let l9 = *ptr2
+                                    .add(2 * ::core::mem::size_of::<*const u8>())
+                                    .cast::<usize>();
+                                let len10 = l9;
+                                let bytes10 = _rt::Vec::from_raw_parts(
+                                    l8.cast(),
+                                    len10,
+                                    len10,
+                                );
+                                _rt::string_lift(bytes10)
+                            };
+                            Err(e)
+                        }
+                        _ => _rt::invalid_enum_discriminant(),
+                    };
+                    result11
+                }
+            }
+            #[allow(unused_unsafe, clippy::all)]
+            /// # Open communication channel
+            ///
+            /// Establishes a bidirectional communication channel with another actor.
+            ///
+            /// ## Parameters
+            ///
+            /// * `actor-id` - ID of the target actor
+            /// * `initial-msg` - JSON message sent as part of channel establishment
+            ///
+            /// ## Returns
+            ///
+            /// * `Ok(channel-id)` - ID of the established channel
+            /// * `Err(string)` - Error message if channel establishment fails
+            ///
+            /// ## Example
+            ///
+            /// ```rust
+            /// use ntwk::theater::message_server_host;
+            /// use ntwk::theater::types::actor_id;
+            /// use serde_json::json;
+            ///
+            /// // Open a channel to a streaming service
+            /// let streaming_service = actor_id { id: "data-stream".to_string() };
+            /// let subscription = json!({
+            /// "action": "subscribe",
+            /// "topics": ["market-data", "news-feed"],
+            /// "options": {"buffer_size": 100}
+            /// });
+            /// let channel = message_server_host::open_channel(streaming_service, subscription)?;
+            /// ```
+            ///
+            /// ## Security
+            ///
+            /// Channel establishment requires mutual consent:
+            /// 1. The initiator requests the channel by calling this function
+            /// 2. The target actor explicitly accepts or rejects the channel
+            ///
+            /// This provides a security checkpoint to prevent unwanted channels.
+            pub fn open_channel(
+                actor_id: &str,
+                initial_msg: &[u8],
+            ) -> Result<ChannelId, _rt::String> {
+                unsafe {
+                    #[cfg_attr(target_pointer_width = "64", repr(align(8)))]
+                    #[cfg_attr(target_pointer_width = "32", repr(align(4)))]
+                    struct RetArea(
+                        [::core::mem::MaybeUninit<
+                            u8,
+                        >; 3 * ::core::mem::size_of::<*const u8>()],
+                    );
+                    let mut ret_area = RetArea(
+                        [::core::mem::MaybeUninit::uninit(); 3
+                            * ::core::mem::size_of::<*const u8>()],
+                    );
+                    let vec0 = actor_id;
+                    let ptr0 = vec0.as_ptr().cast::<u8>();
+                    let len0 = vec0.len();
+                    let vec1 = initial_msg;
+                    let ptr1 = vec1.as_ptr().cast::<u8>();
+                    let len1 = vec1.len();
+                    let ptr2 = ret_area.0.as_mut_ptr().cast::<u8>();
+                    #[cfg(target_arch = "wasm32")]
+                    #[link(wasm_import_module = "theater:simple/message-server-host")]
+                    unsafe extern "C" {
+                        #[link_name = "open-channel"]
+                        fn wit_import3(
+                            _: *mut u8,
+                            _: usize,
+                            _: *mut u8,
+                            _: usize,
+                            _: *mut u8,
+                        );
+                    }
+                    #[cfg(not(target_arch = "wasm32"))]
+                    unsafe extern "C" fn wit_import3(
+                        _: *mut u8,
+                        _: usize,
+                        _: *mut u8,
+                        _: usize,
+                        _: *mut u8,
+                    ) {
+                        unreachable!()
+                    }
+                    unsafe {
+                        wit_import3(ptr0.cast_mut(), len0, ptr1.cast_mut(), len1, ptr2)
+                    };
+                    let l4 = i32::from(*ptr2.add(0).cast::<u8>());
+                    let result11 = match l4 {
+                        0 => {
+                            let e = {
+                                let l5 = *ptr2
+                                    .add(::core::mem::size_of::<*const u8>())
+                                    .cast::<*mut u8>();
+                                let l6 = *ptr2
+                                    .add(2 * ::core::mem::size_of::<*const u8>())
+                                    .cast::<usize>();
+                                let len7 = l6;
+                                let bytes7 = _rt::Vec::from_raw_parts(
+                                    l5.cast(),
+                                    len7,
+                                    len7,
+                                );
+                                _rt::string_lift(bytes7)
+                            };
+                            Ok(e)
+                        }
+                        1 => {
+                            let e = {
+                                let l8 = *ptr2
+                                    .add(::core::mem::size_of::<*const u8>())
+                                    .cast::<*mut u8>();
+                                let l9 = *ptr2
+                                    .add(2 * ::core::mem::size_of::<*const u8>())
+                                    .cast::<usize>();
+                                let len10 = l9;
+                                let bytes10 = _rt::Vec::from_raw_parts(
+                                    l8.cast(),
+                                    len10,
+                                    len10,
+                                );
+                                _rt::string_lift(bytes10)
+                            };
+                            Err(e)
+                        }
+                        _ => _rt::invalid_enum_discriminant(),
+                    };
+                    result11
+                }
+            }
+            #[allow(unused_unsafe, clippy::all)]
+            /// # Send message on channel
+            ///
+            /// Sends a message through an established channel.
+            ///
+            /// ## Parameters
+            ///
+            /// * `channel-id` - ID of the channel to send on
+            /// * `msg` - JSON message payload to send
+            ///
+            /// ## Returns
+            ///
+            /// * `Ok(_)` - Message was successfully sent
+            /// * `Err(string)` - Error message if send fails
+            ///
+            /// ## Example
+            ///
+            /// ```rust
+            /// use ntwk::theater::message_server_host;
+            /// use serde_json::json;
+            ///
+            /// // Send a message on an established channel
+            /// let update = json!({
+            /// "type": "position-update",
+            /// "x": 10.5,
+            /// "y": 20.3,
+            /// "timestamp": 1625097600000
+            /// });
+            /// message_server_host::send_on_channel(channel_id, update)?;
+            /// ```
+            ///
+            /// ## Implementation Notes
+            ///
+            /// Messages sent on a channel are delivered in order. If the channel is closed
+            /// or invalid, this function will return an error.
+            pub fn send_on_channel(
+                channel_id: &str,
+                msg: &[u8],
+            ) -> Result<(), _rt::String> {
+                unsafe {
+                    #[cfg_attr(target_pointer_width = "64", repr(align(8)))]
+                    #[cfg_attr(target_pointer_width = "32", repr(align(4)))]
+                    struct RetArea(
+                        [::core::mem::MaybeUninit<
+                            u8,
+                        >; 3 * ::core::mem::size_of::<*const u8>()],
+                    );
+                    let mut ret_area = RetArea(
+                        [::core::mem::MaybeUninit::uninit(); 3
+                            * ::core::mem::size_of::<*const u8>()],
+                    );
+                    let vec0 = channel_id;
+                    let ptr0 = vec0.as_ptr().cast::<u8>();
+                    let len0 = vec0.len();
+                    let vec1 = msg;
+                    let ptr1 = vec1.as_ptr().cast::<u8>();
+                    let len1 = vec1.len();
+                    let ptr2 = ret_area.0.as_mut_ptr().cast::<u8>();
+                    #[cfg(target_arch = "wasm32")]
+                    #[link(wasm_import_module = "theater:simple/message-server-host")]
+                    unsafe extern "C" {
+                        #[link_name = "send-on-channel"]
+                        fn wit_import3(
+                            _: *mut u8,
+                            _: usize,
+                            _: *mut u8,
+                            _: usize,
+                            _: *mut u8,
+                        );
+                    }
+                    #[cfg(not(target_arch = "wasm32"))]
+                    unsafe extern "C" fn wit_import3(
+                        _: *mut u8,
+                        _: usize,
+                        _: *mut u8,
+                        _: usize,
+                        _: *mut u8,
+                    ) {
+                        unreachable!()
+                    }
+                    unsafe {
+                        wit_import3(ptr0.cast_mut(), len0, ptr1.cast_mut(), len1, ptr2)
+                    };
+                    let l4 = i32::from(*ptr2.add(0).cast::<u8>());
+                    let result8 = match l4 {
+                        0 => {
+                            let e = ();
+                            Ok(e)
+                        }
+                        1 => {
+                            let e = {
+                                let l5 = *ptr2
+                                    .add(::core::mem::size_of::<*const u8>())
+                                    .cast::<*mut u8>();
+                                let l6 = *ptr2
+                                    .add(2 * ::core::mem::size_of::<*const u8>())
+                                    .cast::<usize>();
+                                let len7 = l6;
+                                let bytes7 = _rt::Vec::from_raw_parts(
+                                    l5.cast(),
+                                    len7,
+                                    len7,
+                                );
+                                _rt::string_lift(bytes7)
+                            };
+                            Err(e)
+                        }
+                        _ => _rt::invalid_enum_discriminant(),
+                    };
+                    result8
+                }
+            }
+            #[allow(unused_unsafe, clippy::all)]
+            /// # Close channel
+            ///
+            /// Closes an open communication channel.
+            ///
+            /// ## Parameters
+            ///
+            /// * `channel-id` - ID of the channel to close
+            ///
+            /// ## Returns
+            ///
+            /// * `Ok(_)` - Channel was successfully closed
+            /// * `Err(string)` - Error message if close fails
+            ///
+            /// ## Example
+            ///
+            /// ```rust
+            /// use ntwk::theater::message_server_host;
+            ///
+            /// // Close a channel when done with it
+            /// message_server_host::close_channel(channel_id)?;
+            /// ```
+            ///
+            /// ## Implementation Notes
+            ///
+            /// Closing a channel is a final operation - once closed, a channel cannot be reopened.
+            /// Both participants receive a notification when a channel is closed.
+            pub fn close_channel(channel_id: &str) -> Result<(), _rt::String> {
+                unsafe {
+                    #[cfg_attr(target_pointer_width = "64", repr(align(8)))]
+                    #[cfg_attr(target_pointer_width = "32", repr(align(4)))]
+                    struct RetArea(
+                        [::core::mem::MaybeUninit<
+                            u8,
+                        >; 3 * ::core::mem::size_of::<*const u8>()],
+                    );
+                    let mut ret_area = RetArea(
+                        [::core::mem::MaybeUninit::uninit(); 3
+                            * ::core::mem::size_of::<*const u8>()],
+                    );
+                    let vec0 = channel_id;
+                    let ptr0 = vec0.as_ptr().cast::<u8>();
+                    let len0 = vec0.len();
+                    let ptr1 = ret_area.0.as_mut_ptr().cast::<u8>();
+                    #[cfg(target_arch = "wasm32")]
+                    #[link(wasm_import_module = "theater:simple/message-server-host")]
+                    unsafe extern "C" {
+                        #[link_name = "close-channel"]
+                        fn wit_import2(_: *mut u8, _: usize, _: *mut u8);
+                    }
+                    #[cfg(not(target_arch = "wasm32"))]
+                    unsafe extern "C" fn wit_import2(_: *mut u8, _: usize, _: *mut u8) {
+                        unreachable!()
+                    }
+                    unsafe { wit_import2(ptr0.cast_mut(), len0, ptr1) };
+                    let l3 = i32::from(*ptr1.add(0).cast::<u8>());
+                    let result7 = match l3 {
+                        0 => {
+                            let e = ();
+                            Ok(e)
+                        }
+                        1 => {
+                            let e = {
+                                let l4 = *ptr1
+                                    .add(::core::mem::size_of::<*const u8>())
+                                    .cast::<*mut u8>();
+                                let l5 = *ptr1
+                                    .add(2 * ::core::mem::size_of::<*const u8>())
+                                    .cast::<usize>();
+                                let len6 = l5;
+                                let bytes6 = _rt::Vec::from_raw_parts(
+                                    l4.cast(),
+                                    len6,
+                                    len6,
+                                );
+                                _rt::string_lift(bytes6)
+                            };
+                            Err(e)
+                        }
+                        _ => _rt::invalid_enum_discriminant(),
+                    };
+                    result7
+                }
+            }
+            #[allow(unused_unsafe, clippy::all)]
+            /// # List outstanding requests
+            ///
+            /// Retrieves a list of all pending request IDs that haven't been responded to yet.
+            ///
+            /// ## Returns
+            ///
+            /// * `list<string>` - List of outstanding request IDs
+            ///
+            /// ## Example
+            ///
+            /// ```rust
+            /// use ntwk::theater::message_server_host;
+            ///
+            /// // Get all pending requests
+            /// let pending_requests = message_server_host::list_outstanding_requests();
+            /// for request_id in pending_requests {
+            /// println!("Pending request: {}", request_id);
+            /// }
+            /// ```
+            ///
+            /// ## Implementation Notes
+            ///
+            /// This function is useful for actors that need to track and manage asynchronous
+            /// request processing. It allows actors to check for pending requests and decide
+            /// which ones to process next.
+            pub fn list_outstanding_requests() -> _rt::Vec<_rt::String> {
+                unsafe {
+                    #[cfg_attr(target_pointer_width = "64", repr(align(8)))]
+                    #[cfg_attr(target_pointer_width = "32", repr(align(4)))]
+                    struct RetArea(
+                        [::core::mem::MaybeUninit<
+                            u8,
+                        >; 2 * ::core::mem::size_of::<*const u8>()],
+                    );
+                    let mut ret_area = RetArea(
+                        [::core::mem::MaybeUninit::uninit(); 2
+                            * ::core::mem::size_of::<*const u8>()],
+                    );
+                    let ptr0 = ret_area.0.as_mut_ptr().cast::<u8>();
+                    #[cfg(target_arch = "wasm32")]
+                    #[link(wasm_import_module = "theater:simple/message-server-host")]
+                    unsafe extern "C" {
+                        #[link_name = "list-outstanding-requests"]
+                        fn wit_import1(_: *mut u8);
+                    }
+                    #[cfg(not(target_arch = "wasm32"))]
+                    unsafe extern "C" fn wit_import1(_: *mut u8) {
+                        unreachable!()
+                    }
+                    unsafe { wit_import1(ptr0) };
+                    let l2 = *ptr0.add(0).cast::<*mut u8>();
+                    let l3 = *ptr0
+                        .add(::core::mem::size_of::<*const u8>())
+                        .cast::<usize>();
+                    let base7 = l2;
+                    let len7 = l3;
+                    let mut result7 = _rt::Vec::with_capacity(len7);
+                    for i in 0..len7 {
+                        let base = base7
+                            .add(i * (2 * ::core::mem::size_of::<*const u8>()));
+                        let e7 = {
+                            let l4 = *base.add(0).cast::<*mut u8>();
+                            let l5 = *base
+                                .add(::core::mem::size_of::<*const u8>())
+                                .cast::<usize>();
+                            let len6 = l5;
+                            let bytes6 = _rt::Vec::from_raw_parts(l4.cast(), len6, len6);
+                            _rt::string_lift(bytes6)
+                        };
+                        result7.push(e7);
+                    }
+                    _rt::cabi_dealloc(
+                        base7,
+                        len7 * (2 * ::core::mem::size_of::<*const u8>()),
+                        ::core::mem::size_of::<*const u8>(),
+                    );
+                    let result8 = result7;
+                    result8
+                }
+            }
+            #[allow(unused_unsafe, clippy::all)]
+            /// # Respond to a specific request
+            ///
+            /// Sends a response to a previously received request identified by its ID.
+            ///
+            /// ## Parameters
+            ///
+            /// * `request-id` - ID of the request to respond to
+            /// * `response` - JSON response payload to send
+            ///
+            /// ## Returns
+            ///
+            /// * `Ok(_)` - Response was successfully sent
+            /// * `Err(string)` - Error message if response fails
+            ///
+            /// ## Example
+            ///
+            /// ```rust
+            /// use ntwk::theater::message_server_host;
+            /// use serde_json::json;
+            ///
+            /// // Respond to a specific request
+            /// let response = json!({
+            /// "status": "success",
+            /// "data": {
+            /// "result": 42
+            /// }
+            /// });
+            /// message_server_host::respond_to_request("req-123", response)?;
+            /// ```
+            ///
+            /// ## Implementation Notes
+            ///
+            /// This function allows actors to implement asynchronous request handling patterns,
+            /// where requests are received, processed in the background, and responded to later.
+            /// If the request ID is not found, an error is returned.
+            pub fn respond_to_request(
+                request_id: &str,
+                response: &[u8],
+            ) -> Result<(), _rt::String> {
+                unsafe {
+                    #[cfg_attr(target_pointer_width = "64", repr(align(8)))]
+                    #[cfg_attr(target_pointer_width = "32", repr(align(4)))]
+                    struct RetArea(
+                        [::core::mem::MaybeUninit<
+                            u8,
+                        >; 3 * ::core::mem::size_of::<*const u8>()],
+                    );
+                    let mut ret_area = RetArea(
+                        [::core::mem::MaybeUninit::uninit(); 3
+                            * ::core::mem::size_of::<*const u8>()],
+                    );
+                    let vec0 = request_id;
+                    let ptr0 = vec0.as_ptr().cast::<u8>();
+                    let len0 = vec0.len();
+                    let vec1 = response;
+                    let ptr1 = vec1.as_ptr().cast::<u8>();
+                    let len1 = vec1.len();
+                    let ptr2 = ret_area.0.as_mut_ptr().cast::<u8>();
+                    #[cfg(target_arch = "wasm32")]
+                    #[link(wasm_import_module = "theater:simple/message-server-host")]
+                    unsafe extern "C" {
+                        #[link_name = "respond-to-request"]
+                        fn wit_import3(
+                            _: *mut u8,
+                            _: usize,
+                            _: *mut u8,
+                            _: usize,
+                            _: *mut u8,
+                        );
+                    }
+                    #[cfg(not(target_arch = "wasm32"))]
+                    unsafe extern "C" fn wit_import3(
+                        _: *mut u8,
+                        _: usize,
+                        _: *mut u8,
+                        _: usize,
+                        _: *mut u8,
+                    ) {
+                        unreachable!()
+                    }
+                    unsafe {
+                        wit_import3(ptr0.cast_mut(), len0, ptr1.cast_mut(), len1, ptr2)
+                    };
+                    let l4 = i32::from(*ptr2.add(0).cast::<u8>());
+                    let result8 = match l4 {
+                        0 => {
+                            let e = ();
+                            Ok(e)
+                        }
+                        1 => {
+                            let e = {
+                                let l5 = *ptr2
+                                    .add(::core::mem::size_of::<*const u8>())
+                                    .cast::<*mut u8>();
+                                let l6 = *ptr2
+                                    .add(2 * ::core::mem::size_of::<*const u8>())
+                                    .cast::<usize>();
+                                let len7 = l6;
+                                let bytes7 = _rt::Vec::from_raw_parts(
+                                    l5.cast(),
+                                    len7,
+                                    len7,
+                                );
+                                _rt::string_lift(bytes7)
+                            };
+                            Err(e)
+                        }
+                        _ => _rt::invalid_enum_discriminant(),
+                    };
+                    result8
+                }
+            }
+            #[allow(unused_unsafe, clippy::all)]
+            /// # Cancel a pending request
+            ///
+            /// Explicitly cancels a pending request without sending a response.
+            ///
+            /// ## Parameters
+            ///
+            /// * `request-id` - ID of the request to cancel
+            ///
+            /// ## Returns
+            ///
+            /// * `Ok(_)` - Request was successfully canceled
+            /// * `Err(string)` - Error message if cancellation fails
+            ///
+            /// ## Example
+            ///
+            /// ```rust
+            /// use ntwk::theater::message_server_host;
+            ///
+            /// // Cancel a request that can't be fulfilled
+            /// message_server_host::cancel_request("req-123")?;
+            /// ```
+            ///
+            /// ## Implementation Notes
+            ///
+            /// Canceling a request causes the requester to receive an error indicating that
+            /// the request was canceled. This is useful for cleanup operations and handling
+            /// error cases where a proper response cannot be generated.
+            pub fn cancel_request(request_id: &str) -> Result<(), _rt::String> {
+                unsafe {
+                    #[cfg_attr(target_pointer_width = "64", repr(align(8)))]
+                    #[cfg_attr(target_pointer_width = "32", repr(align(4)))]
+                    struct RetArea(
+                        [::core::mem::MaybeUninit<
+                            u8,
+                        >; 3 * ::core::mem::size_of::<*const u8>()],
+                    );
+                    let mut ret_area = RetArea(
+                        [::core::mem::MaybeUninit::uninit(); 3
+                            * ::core::mem::size_of::<*const u8>()],
+                    );
+                    let vec0 = request_id;
+                    let ptr0 = vec0.as_ptr().cast::<u8>();
+                    let len0 = vec0.len();
+                    let ptr1 = ret_area.0.as_mut_ptr().cast::<u8>();
+                    #[cfg(target_arch = "wasm32")]
+                    #[link(wasm_import_module = "theater:simple/message-server-host")]
+                    unsafe extern "C" {
+                        #[link_name = "cancel-request"]
+                        fn wit_import2(_: *mut u8, _: usize, _: *mut u8);
+                    }
+                    #[cfg(not(target_arch = "wasm32"))]
+                    unsafe extern "C" fn wit_import2(_: *mut u8, _: usize, _: *mut u8) {
+                        unreachable!()
+                    }
+                    unsafe { wit_import2(ptr0.cast_mut(), len0, ptr1) };
+                    let l3 = i32::from(*ptr1.add(0).cast::<u8>());
+                    let result7 = match l3 {
+                        0 => {
+                            let e = ();
+                            Ok(e)
+                        }
+                        1 => {
+                            let e = {
+                                let l4 = *ptr1
+                                    .add(::core::mem::size_of::<*const u8>())
+                                    .cast::<*mut u8>();
+                                let l5 = *ptr1
+                                    .add(2 * ::core::mem::size_of::<*const u8>())
+                                    .cast::<usize>();
+                                let len6 = l5;
+                                let bytes6 = _rt::Vec::from_raw_parts(
+                                    l4.cast(),
+                                    len6,
+                                    len6,
+                                );
+                                _rt::string_lift(bytes6)
+                            };
+                            Err(e)
+                        }
+                        _ => _rt::invalid_enum_discriminant(),
+                    };
+                    result7
+                }
+            }
+        }
+        /// # Content Store
+        ///
+        /// Provides a content-addressable storage system for actors to store and retrieve data.
+        ///
+        /// ## Purpose
+        ///
+        /// The store interface allows actors to save and retrieve content using content-addressed
+        /// storage, where each piece of content is referenced by a hash of its data. This provides
+        /// immutability, deduplication, and integrity verification for all stored content.
+        ///
+        /// Additionally, the store supports a labeling system that allows human-readable names
+        /// to be attached to content references, making it easier to locate and manage content.
+        ///
+        /// ## Example
+        ///
+        /// ```rust
+        /// use ntwk::theater::store;
+        ///
+        /// // Create a new store
+        /// let store_id = store::new()?;
+        ///
+        /// // Store some content
+        /// let content = "Hello, Theater!".as_bytes().to_vec();
+        /// let content_ref = store::store(store_id, content)?;
+        ///
+        /// // Retrieve it by its content reference
+        /// let retrieved = store::get(store_id, content_ref.clone())?;
+        /// assert_eq!(retrieved, "Hello, Theater!".as_bytes());
+        ///
+        /// // Label the content for easier access
+        /// store::label(store_id, "greeting", content_ref.clone())?;
+        ///
+        /// // Later, retrieve by label
+        /// let label_ref = store::get_by_label(store_id, "greeting")?.unwrap();
+        /// let greeting = store::get(store_id, label_ref)?;
+        /// ```
+        ///
+        /// ## Security
+        ///
+        /// The content store is isolated per actor, preventing direct access to other actors' data.
+        /// All store operations are tracked in the actor's event chain, providing a complete
+        /// audit trail of data operations.
+        ///
+        /// ## Implementation Notes
+        ///
+        /// The store uses content-based addressing where the reference to content is derived from
+        /// a cryptographic hash of the content itself. This ensures:
+        ///
+        /// - Content cannot be modified without changing its reference
+        /// - Identical content is stored only once (automatic deduplication)
+        /// - Content integrity can be verified
+        #[allow(dead_code, async_fn_in_trait, unused_imports, clippy::all)]
+        pub mod store {
+            #[used]
+            #[doc(hidden)]
+            static __FORCE_SECTION_REF: fn() = super::super::super::__link_custom_section_describing_imports;
+            use super::super::super::_rt;
+            /// # Content Reference
+            ///
+            /// A reference to content stored in the content-addressable store.
+            ///
+            /// ## Purpose
+            ///
+            /// ContentRef provides a stable, immutable reference to content based on its hash,
+            /// enabling content-addressable storage where data is referenced by its cryptographic hash
+            /// rather than by location or name.
+            ///
+            /// ## Example
+            ///
+            /// ```rust
+            /// use ntwk::theater::store::{content_ref, store};
+            ///
+            /// // Store content and get its reference
+            /// let store_id = store::new()?;
+            /// let data = b"Some important data".to_vec();
+            /// let ref = store::store(store_id, data)?;
+            ///
+            /// // The hash in the content ref is a SHA-256 digest
+            /// println!("Stored content with hash: {}", ref.hash);
+            /// ```
+            ///
+            /// ## Security
+            ///
+            /// Content references use cryptographic hashes that are collision-resistant,
+            /// ensuring that distinct content will have distinct references. This provides
+            /// integrity verification for all stored content.
+            #[derive(Clone, serde::Deserialize, serde::Serialize)]
+            pub struct ContentRef {
+                /// Cryptographic hash of the content (SHA-256 in hexadecimal format)
+                pub hash: _rt::String,
+            }
+            impl ::core::fmt::Debug for ContentRef {
+                fn fmt(
+                    &self,
+                    f: &mut ::core::fmt::Formatter<'_>,
+                ) -> ::core::fmt::Result {
+                    f.debug_struct("ContentRef").field("hash", &self.hash).finish()
+                }
+            }
+            #[allow(unused_unsafe, clippy::all)]
+            /// # Create a new store
+            ///
+            /// Creates a new content-addressable store instance.
+            ///
+            /// ## Returns
+            ///
+            /// * `Ok(string)` - The ID of the newly created store
+            /// * `Err(string)` - Error message if store creation fails
+            ///
+            /// ## Example
+            ///
+            /// ```rust
+            /// use ntwk::theater::store;
+            ///
+            /// // Create a new store
+            /// let store_id = store::new()?;
+            /// ```
+            ///
+            /// ## Implementation Notes
+            ///
+            /// Each actor has access to its own isolated store instances. Store IDs are only
+            /// valid within the context of the actor that created them.
+            pub fn new() -> Result<_rt::String, _rt::String> {
+                unsafe {
+                    #[cfg_attr(target_pointer_width = "64", repr(align(8)))]
+                    #[cfg_attr(target_pointer_width = "32", repr(align(4)))]
+                    struct RetArea(
+                        [::core::mem::MaybeUninit<
+                            u8,
+                        >; 3 * ::core::mem::size_of::<*const u8>()],
+                    );
+                    let mut ret_area = RetArea(
+                        [::core::mem::MaybeUninit::uninit(); 3
+                            * ::core::mem::size_of::<*const u8>()],
+                    );
+                    let ptr0 = ret_area.0.as_mut_ptr().cast::<u8>();
+                    #[cfg(target_arch = "wasm32")]
+                    #[link(wasm_import_module = "theater:simple/store")]
+                    unsafe extern "C" {
+                        #[link_name = "new"]
+                        fn wit_import1(_: *mut u8);
+                    }
+                    #[cfg(not(target_arch = "wasm32"))]
+                    unsafe extern "C" fn wit_import1(_: *mut u8) {
+                        unreachable!()
+                    }
+                    unsafe { wit_import1(ptr0) };
+                    let l2 = i32::from(*ptr0.add(0).cast::<u8>());
+                    let result9 = match l2 {
+                        0 => {
+                            let e = {
+                                let l3 = *ptr0
+                                    .add(::core::mem::size_of::<*const u8>())
+                                    .cast::<*mut u8>();
+                                let l4 = *ptr0
+                                    .add(2 * ::core::mem::size_of::<*const u8>())
+                                    .cast::<usize>();
+                                let len5 = l4;
+                                let bytes5 = _rt::Vec::from_raw_parts(
+                                    l3.cast(),
+                                    len5,
+                                    len5,
+                                );
+                                _rt::string_lift(bytes5)
+                            };
+                            Ok(e)
+                        }
+                        1 => {
+                            let e = {
+                                let l6 = *ptr0
+                                    .add(::core::mem::size_of::<*const u8>())
+                                    .cast::<*mut u8>();
+                                let l7 = *ptr0
+                                    .add(2 * ::core::mem::size_of::<*const u8>())
+                                    .cast::<usize>();
+                                let len8 = l7;
+                                let bytes8 = _rt::Vec::from_raw_parts(
+                                    l6.cast(),
+                                    len8,
+                                    len8,
+                                );
+                                _rt::string_lift(bytes8)
+                            };
+                            Err(e)
+                        }
+                        _ => _rt::invalid_enum_discriminant(),
+                    };
+                    result9
+                }
+            }
+            #[allow(unused_unsafe, clippy::all)]
+            /// # Store content
+            ///
+            /// Stores content in the content-addressable store and returns a reference to it.
+            ///
+            /// ## Parameters
+            ///
+            /// * `store-id` - ID of the store to use
+            /// * `content` - The content bytes to store
+            ///
+            /// ## Returns
+            ///
+            /// * `Ok(content-ref)` - Reference to the stored content
+            /// * `Err(string)` - Error message if storage fails
+            ///
+            /// ## Example
+            ///
+            /// ```rust
+            /// use ntwk::theater::store;
+            ///
+            /// // Store some content
+            /// let data = serde_json::to_vec(&my_data)?;
+            /// let content_ref = store::store(store_id, data)?;
+            /// ```
+            ///
+            /// ## Implementation Notes
+            ///
+            /// If identical content already exists in the store, the existing content reference
+            /// will be returned without storing a duplicate copy.
+            pub fn store(
+                store_id: &str,
+                content: &[u8],
+            ) -> Result<ContentRef, _rt::String> {
+                unsafe {
+                    #[cfg_attr(target_pointer_width = "64", repr(align(8)))]
+                    #[cfg_attr(target_pointer_width = "32", repr(align(4)))]
+                    struct RetArea(
+                        [::core::mem::MaybeUninit<
+                            u8,
+                        >; 3 * ::core::mem::size_of::<*const u8>()],
+                    );
+                    let mut ret_area = RetArea(
+                        [::core::mem::MaybeUninit::uninit(); 3
+                            * ::core::mem::size_of::<*const u8>()],
+                    );
+                    let vec0 = store_id;
+                    let ptr0 = vec0.as_ptr().cast::<u8>();
+                    let len0 = vec0.len();
+                    let vec1 = content;
+                    let ptr1 = vec1.as_ptr().cast::<u8>();
+                    let len1 = vec1.len();
+                    let ptr2 = ret_area.0.as_mut_ptr().cast::<u8>();
+                    #[cfg(target_arch = "wasm32")]
+                    #[link(wasm_import_module = "theater:simple/store")]
+                    unsafe extern "C" {
+                        #[link_name = "store"]
+                        fn wit_import3(
+                            _: *mut u8,
+                            _: usize,
+                            _: *mut u8,
+                            _: usize,
+                            _: *mut u8,
+                        );
+                    }
+                    #[cfg(not(target_arch = "wasm32"))]
+                    unsafe extern "C" fn wit_import3(
+                        _: *mut u8,
+                        _: usize,
+                        _: *mut u8,
+                        _: usize,
+                        _: *mut u8,
+                    ) {
+                        unreachable!()
+                    }
+                    unsafe {
+                        wit_import3(ptr0.cast_mut(), len0, ptr1.cast_mut(), len1, ptr2)
+                    };
+                    let l4 = i32::from(*ptr2.add(0).cast::<u8>());
+                    let result11 = match l4 {
+                        0 => {
+                            let e = {
+                                let l5 = *ptr2
+                                    .add(::core::mem::size_of::<*const u8>())
+                                    .cast::<*mut u8>();
+                                let l6 = *ptr2
+                                    .add(2 * ::core::mem::size_of::<*const u8>())
+                                    .cast::<usize>();
+                                let len7 = l6;
+                                let bytes7 = _rt::Vec::from_raw_parts(
+                                    l5.cast(),
+                                    len7,
+                                    len7,
+                                );
+                                ContentRef {
+                                    hash: _rt::string_lift(bytes7),
+                                }
+                            };
+                            Ok(e)
+                        }
+                        1 => {
+                            let e = {
+                                let l8 = *ptr2
+                                    .add(::core::mem::size_of::<*const u8>())
+                                    .cast::<*mut u8>();
+                                let l9 = *ptr2
+                                    .add(2 * ::core::mem::size_of::<*const u8>())
+                                    .cast::<usize>();
+                                let len10 = l9;
+                                let bytes10 = _rt::Vec::from_raw_parts(
+                                    l8.cast(),
+                                    len10,
+                                    len10,
+                                );
+                                _rt::string_lift(bytes10)
+                            };
+                            Err(e)
+                        }
+                        _ => _rt::invalid_enum_discriminant(),
+                    };
+                    result11
+                }
+            }
+            #[allow(unused_unsafe, clippy::all)]
+            /// # Retrieve content
+            ///
+            /// Retrieves content from the store using its content reference.
+            ///
+            /// ## Parameters
+            ///
+            /// * `store-id` - ID of the store to use
+            /// * `content-ref` - Reference to the content to retrieve
+            ///
+            /// ## Returns
+            ///
+            /// * `Ok(list<u8>)` - The retrieved content bytes
+            /// * `Err(string)` - Error message if retrieval fails
+            ///
+            /// ## Example
+            ///
+            /// ```rust
+            /// use ntwk::theater::store;
+            ///
+            /// // Retrieve content
+            /// let content = store::get(store_id, content_ref)?;
+            /// let my_data: MyData = serde_json::from_slice(&content)?;
+            /// ```
+            pub fn get(
+                store_id: &str,
+                content_ref: &ContentRef,
+            ) -> Result<_rt::Vec<u8>, _rt::String> {
+                unsafe {
+                    #[cfg_attr(target_pointer_width = "64", repr(align(8)))]
+                    #[cfg_attr(target_pointer_width = "32", repr(align(4)))]
+                    struct RetArea(
+                        [::core::mem::MaybeUninit<
+                            u8,
+                        >; 3 * ::core::mem::size_of::<*const u8>()],
+                    );
+                    let mut ret_area = RetArea(
+                        [::core::mem::MaybeUninit::uninit(); 3
+                            * ::core::mem::size_of::<*const u8>()],
+                    );
+                    let vec0 = store_id;
+                    let ptr0 = vec0.as_ptr().cast::<u8>();
+                    let len0 = vec0.len();
+                    let ContentRef { hash: hash1 } = content_ref;
+                    let vec2 = hash1;
+                    let ptr2 = vec2.as_ptr().cast::<u8>();
+                    let len2 = vec2.len();
+                    let ptr3 = ret_area.0.as_mut_ptr().cast::<u8>();
+                    #[cfg(target_arch = "wasm32")]
+                    #[link(wasm_import_module = "theater:simple/store")]
+                    unsafe extern "C" {
+                        #[link_name = "get"]
+                        fn wit_import4(
+                            _: *mut u8,
+                            _: usize,
+                            _: *mut u8,
+                            _: usize,
+                            _: *mut u8,
+                        );
+                    }
+                    #[cfg(not(target_arch = "wasm32"))]
+                    unsafe extern "C" fn wit_import4(
+                        _: *mut u8,
+                        _: usize,
+                        _: *mut u8,
+                        _: usize,
+                        _: *mut u8,
+                    ) {
+                        unreachable!()
+                    }
+                    unsafe {
+                        wit_import4(ptr0.cast_mut(), len0, ptr2.cast_mut(), len2, ptr3)
+                    };
+                    let l5 = i32::from(*ptr3.add(0).cast::<u8>());
+                    let result12 = match l5 {
+                        0 => {
+                            let e = {
+                                let l6 = *ptr3
+                                    .add(::core::mem::size_of::<*const u8>())
+                                    .cast::<*mut u8>();
+                                let l7 = *ptr3
+                                    .add(2 * ::core::mem::size_of::<*const u8>())
+                                    .cast::<usize>();
+                                let len8 = l7;
+                                _rt::Vec::from_raw_parts(l6.cast(), len8, len8)
+                            };
+                            Ok(e)
+                        }
+                        1 => {
+                            let e = {
+                                let l9 = *ptr3
+                                    .add(::core::mem::size_of::<*const u8>())
+                                    .cast::<*mut u8>();
+                                let l10 = *ptr3
+                                    .add(2 * ::core::mem::size_of::<*const u8>())
+                                    .cast::<usize>();
+                                let len11 = l10;
+                                let bytes11 = _rt::Vec::from_raw_parts(
+                                    l9.cast(),
+                                    len11,
+                                    len11,
+                                );
+                                _rt::string_lift(bytes11)
+                            };
+                            Err(e)
+                        }
+                        _ => _rt::invalid_enum_discriminant(),
+                    };
+                    result12
+                }
+            }
+            #[allow(unused_unsafe, clippy::all)]
+            /// # Check if content exists
+            ///
+            /// Checks if a particular content reference exists in the store.
+            ///
+            /// ## Parameters
+            ///
+            /// * `store-id` - ID of the store to check
+            /// * `content-ref` - Reference to check for
+            ///
+            /// ## Returns
+            ///
+            /// * `Ok(bool)` - True if the content exists, false otherwise
+            /// * `Err(string)` - Error message if the check fails
+            ///
+            /// ## Example
+            ///
+            /// ```rust
+            /// use ntwk::theater::store;
+            ///
+            /// // Check if content exists before attempting to retrieve it
+            /// if store::exists(store_id, content_ref)? {
+            /// let content = store::get(store_id, content_ref)?;
+            /// // Process content...
+            /// } else {
+            /// // Handle missing content case
+            /// }
+            /// ```
+            pub fn exists(
+                store_id: &str,
+                content_ref: &ContentRef,
+            ) -> Result<bool, _rt::String> {
+                unsafe {
+                    #[cfg_attr(target_pointer_width = "64", repr(align(8)))]
+                    #[cfg_attr(target_pointer_width = "32", repr(align(4)))]
+                    struct RetArea(
+                        [::core::mem::MaybeUninit<
+                            u8,
+                        >; 3 * ::core::mem::size_of::<*const u8>()],
+                    );
+                    let mut ret_area = RetArea(
+                        [::core::mem::MaybeUninit::uninit(); 3
+                            * ::core::mem::size_of::<*const u8>()],
+                    );
+                    let vec0 = store_id;
+                    let ptr0 = vec0.as_ptr().cast::<u8>();
+                    let len0 = vec0.len();
+                    let ContentRef { hash: hash1 } = content_ref;
+                    let vec2 = hash1;
+                    let ptr2 = vec2.as_ptr().cast::<u8>();
+                    let len2 = vec2.len();
+                    let ptr3 = ret_area.0.as_mut_ptr().cast::<u8>();
+                    #[cfg(target_arch = "wasm32")]
+                    #[link(wasm_import_module = "theater:simple/store")]
+                    unsafe extern "C" {
+                        #[link_name = "exists"]
+                        fn wit_import4(
+                            _: *mut u8,
+                            _: usize,
+                            _: *mut u8,
+                            _: usize,
+                            _: *mut u8,
+                        );
+                    }
+                    #[cfg(not(target_arch = "wasm32"))]
+                    unsafe extern "C" fn wit_import4(
+                        _: *mut u8,
+                        _: usize,
+                        _: *mut u8,
+                        _: usize,
+                        _: *mut u8,
+                    ) {
+                        unreachable!()
+                    }
+                    unsafe {
+                        wit_import4(ptr0.cast_mut(), len0, ptr2.cast_mut(), len2, ptr3)
+                    };
+                    let l5 = i32::from(*ptr3.add(0).cast::<u8>());
+                    let result10 = match l5 {
+                        0 => {
+                            let e = {
+                                let l6 = i32::from(
+                                    *ptr3.add(::core::mem::size_of::<*const u8>()).cast::<u8>(),
+                                );
+                                _rt::bool_lift(l6 as u8)
+                            };
+                            Ok(e)
+                        }
+                        1 => {
+                            let e = {
+                                let l7 = *ptr3
+                                    .add(::core::mem::size_of::<*const u8>())
+                                    .cast::<*mut u8>();
+                                let l8 = *ptr3
+                                    .add(2 * ::core::mem::size_of::<*const u8>())
+                                    .cast::<usize>();
+                                let len9 = l8;
+                                let bytes9 = _rt::Vec::from_raw_parts(
+                                    l7.cast(),
+                                    len9,
+                                    len9,
+                                );
+                                _rt::string_lift(bytes9)
+                            };
+                            Err(e)
+                        }
+                        _ => _rt::invalid_enum_discriminant(),
+                    };
+                    result10
+                }
+            }
+            #[allow(unused_unsafe, clippy::all)]
+            /// # Attach a label to content
+            ///
+            /// Associates a human-readable label with a content reference.
+            ///
+            /// ## Parameters
+            ///
+            /// * `store-id` - ID of the store to use
+            /// * `label` - The human-readable label to attach
+            /// * `content-ref` - Reference to the content to label
+            ///
+            /// ## Returns
+            ///
+            /// * `Ok(_)` - Label was successfully attached
+            /// * `Err(string)` - Error message if labeling fails
+            ///
+            /// ## Example
+            ///
+            /// ```rust
+            /// use ntwk::theater::store;
+            ///
+            /// // Store and label config data
+            /// let config_data = serde_json::to_vec(&my_config)?;
+            /// let ref = store::store(store_id, config_data)?;
+            /// store::label(store_id, "current-config", ref)?;
+            /// ```
+            ///
+            /// ## Implementation Notes
+            ///
+            /// A label can point to multiple content references, effectively acting as a collection.
+            /// Each call to this function adds the content reference to the label without removing
+            /// previous references.
+            pub fn label(
+                store_id: &str,
+                label: &str,
+                content_ref: &ContentRef,
+            ) -> Result<(), _rt::String> {
+                unsafe {
+                    #[cfg_attr(target_pointer_width = "64", repr(align(8)))]
+                    #[cfg_attr(target_pointer_width = "32", repr(align(4)))]
+                    struct RetArea(
+                        [::core::mem::MaybeUninit<
+                            u8,
+                        >; 3 * ::core::mem::size_of::<*const u8>()],
+                    );
+                    let mut ret_area = RetArea(
+                        [::core::mem::MaybeUninit::uninit(); 3
+                            * ::core::mem::size_of::<*const u8>()],
+                    );
+                    let vec0 = store_id;
+                    let ptr0 = vec0.as_ptr().cast::<u8>();
+                    let len0 = vec0.len();
+                    let vec1 = label;
+                    let ptr1 = vec1.as_ptr().cast::<u8>();
+                    let len1 = vec1.len();
+                    let ContentRef { hash: hash2 } = content_ref;
+                    let vec3 = hash2;
+                    let ptr3 = vec3.as_ptr().cast::<u8>();
+                    let len3 = vec3.len();
+                    let ptr4 = ret_area.0.as_mut_ptr().cast::<u8>();
+                    #[cfg(target_arch = "wasm32")]
+                    #[link(wasm_import_module = "theater:simple/store")]
+                    unsafe extern "C" {
+                        #[link_name = "label"]
+                        fn wit_import5(
+                            _: *mut u8,
+                            _: usize,
+                            _: *mut u8,
+                            _: usize,
+                            _: *mut u8,
+                            _: usize,
+                            _: *mut u8,
+                        );
+                    }
+                    #[cfg(not(target_arch = "wasm32"))]
+                    unsafe extern "C" fn wit_import5(
+                        _: *mut u8,
+                        _: usize,
+                        _: *mut u8,
+                        _: usize,
+                        _: *mut u8,
+                        _: usize,
+                        _: *mut u8,
+                    ) {
+                        unreachable!()
+                    }
+                    unsafe {
+                        wit_import5(
+                            ptr0.cast_mut(),
+                            len0,
+                            ptr1.cast_mut(),
+                            len1,
+                            ptr3.cast_mut(),
+                            len3,
+                            ptr4,
+                        )
+                    };
+                    let l6 = i32::from(*ptr4.add(0).cast::<u8>());
+                    let result10 = match l6 {
+                        0 => {
+                            let e = ();
+                            Ok(e)
+                        }
+                        1 => {
+                            let e = {
+                                let l7 = *ptr4
+                                    .add(::core::mem::size_of::<*const u8>())
+                                    .cast::<*mut u8>();
+                                let l8 = *ptr4
+                                    .add(2 * ::core::mem::size_of::<*const u8>())
+                                    .cast::<usize>();
+                                let len9 = l8;
+                                let bytes9 = _rt::Vec::from_raw_parts(
+                                    l7.cast(),
+                                    len9,
+                                    len9,
+                                );
+                                _rt::string_lift(bytes9)
+                            };
+                            Err(e)
+                        }
+                        _ => _rt::invalid_enum_discriminant(),
+                    };
+                    result10
+                }
+            }
+            #[allow(unused_unsafe, clippy::all)]
+            /// # Get content reference by label
+            ///
+            /// Retrieves a content reference associated with a label.
+            ///
+            /// ## Parameters
+            ///
+            /// * `store-id` - ID of the store to use
+            /// * `label` - The label to look up
+            ///
+            /// ## Returns
+            ///
+            /// * `Ok(option<content-ref>)` - The content reference if found, None if the label doesn't exist
+            /// * `Err(string)` - Error message if the lookup fails
+            ///
+            /// ## Example
+            ///
+            /// ```rust
+            /// use ntwk::theater::store;
+            ///
+            /// // Retrieve the current configuration
+            /// if let Some(ref) = store::get_by_label(store_id, "current-config")? {
+            /// let config_data = store::get(store_id, ref)?;
+            /// let config: MyConfig = serde_json::from_slice(&config_data)?;
+            /// // Use configuration...
+            /// } else {
+            /// // No configuration found
+            /// }
+            /// ```
+            ///
+            /// ## Implementation Notes
+            ///
+            /// If a label points to multiple content references, this function returns the most
+            /// recently added reference.
+            pub fn get_by_label(
+                store_id: &str,
+                label: &str,
+            ) -> Result<Option<ContentRef>, _rt::String> {
+                unsafe {
+                    #[cfg_attr(target_pointer_width = "64", repr(align(8)))]
+                    #[cfg_attr(target_pointer_width = "32", repr(align(4)))]
+                    struct RetArea(
+                        [::core::mem::MaybeUninit<
+                            u8,
+                        >; 4 * ::core::mem::size_of::<*const u8>()],
+                    );
+                    let mut ret_area = RetArea(
+                        [::core::mem::MaybeUninit::uninit(); 4
+                            * ::core::mem::size_of::<*const u8>()],
+                    );
+                    let vec0 = store_id;
+                    let ptr0 = vec0.as_ptr().cast::<u8>();
+                    let len0 = vec0.len();
+                    let vec1 = label;
+                    let ptr1 = vec1.as_ptr().cast::<u8>();
+                    let len1 = vec1.len();
+                    let ptr2 = ret_area.0.as_mut_ptr().cast::<u8>();
+                    #[cfg(target_arch = "wasm32")]
+                    #[link(wasm_import_module = "theater:simple/store")]
+                    unsafe extern "C" {
+                        #[link_name = "get-by-label"]
+                        fn wit_import3(
+                            _: *mut u8,
+                            _: usize,
+                            _: *mut u8,
+                            _: usize,
+                            _: *mut u8,
+                        );
+                    }
+                    #[cfg(not(target_arch = "wasm32"))]
+                    unsafe extern "C" fn wit_import3(
+                        _: *mut u8,
+                        _: usize,
+                        _: *mut u8,
+                        _: usize,
+                        _: *mut u8,
+                    ) {
+                        unreachable!()
+                    }
+                    unsafe {
+                        wit_import3(ptr0.cast_mut(), len0, ptr1.cast_mut(), len1, ptr2)
+                    };
+                    let l4 = i32::from(*ptr2.add(0).cast::<u8>());
+                    let result12 = match l4 {
+                        0 => {
+                            let e = {
+                                let l5 = i32::from(
+                                    *ptr2.add(::core::mem::size_of::<*const u8>()).cast::<u8>(),
+                                );
+                                match l5 {
+                                    0 => None,
+                                    1 => {
+                                        let e = {
+                                            let l6 = *ptr2
+                                                .add(2 * ::core::mem::size_of::<*const u8>())
+                                                .cast::<*mut u8>();
+                                            let l7 = *ptr2
+                                                .add(3 * ::core::mem::size_of::<*const u8>())
+                                                .cast::<usize>();
+                                            let len8 = l7;
+                                            let bytes8 = _rt::Vec::from_raw_parts(
+                                                l6.cast(),
+                                                len8,
+                                                len8,
+                                            );
+                                            ContentRef {
+                                                hash: _rt::string_lift(bytes8),
+                                            }
+                                        };
+                                        Some(e)
+                                    }
+                                    _ => _rt::invalid_enum_discriminant(),
+                                }
+                            };
+                            Ok(e)
+                        }
+                        1 => {
+                            let e = {
+                                let l9 = *ptr2
+                                    .add(::core::mem::size_of::<*const u8>())
+                                    .cast::<*mut u8>();
+                                let l10 = *ptr2
+                                    .add(2 * ::core::mem::size_of::<*const u8>())
+                                    .cast::<usize>();
+                                let len11 = l10;
+                                let bytes11 = _rt::Vec::from_raw_parts(
+                                    l9.cast(),
+                                    len11,
+                                    len11,
+                                );
+                                _rt::string_lift(bytes11)
+                            };
+                            Err(e)
+                        }
+                        _ => _rt::invalid_enum_discriminant(),
+                    };
+                    result12
+                }
+            }
+            #[allow(unused_unsafe, clippy::all)]
+            /// # Remove a label
+            ///
+            /// Deletes a label and its associations with content references.
+            ///
+            /// ## Parameters
+            ///
+            /// * `store-id` - ID of the store to use
+            /// * `label` - The label to remove
+            ///
+            /// ## Returns
+            ///
+            /// * `Ok(_)` - Label was successfully removed
+            /// * `Err(string)` - Error message if removal fails
+            ///
+            /// ## Example
+            ///
+            /// ```rust
+            /// use ntwk::theater::store;
+            ///
+            /// // Remove an obsolete label
+            /// store::remove_label(store_id, "old-config")?;
+            /// ```
+            ///
+            /// ## Implementation Notes
+            ///
+            /// Removing a label does not delete the content it points to, only the association
+            /// between the label and the content references.
+            pub fn remove_label(store_id: &str, label: &str) -> Result<(), _rt::String> {
+                unsafe {
+                    #[cfg_attr(target_pointer_width = "64", repr(align(8)))]
+                    #[cfg_attr(target_pointer_width = "32", repr(align(4)))]
+                    struct RetArea(
+                        [::core::mem::MaybeUninit<
+                            u8,
+                        >; 3 * ::core::mem::size_of::<*const u8>()],
+                    );
+                    let mut ret_area = RetArea(
+                        [::core::mem::MaybeUninit::uninit(); 3
+                            * ::core::mem::size_of::<*const u8>()],
+                    );
+                    let vec0 = store_id;
+                    let ptr0 = vec0.as_ptr().cast::<u8>();
+                    let len0 = vec0.len();
+                    let vec1 = label;
+                    let ptr1 = vec1.as_ptr().cast::<u8>();
+                    let len1 = vec1.len();
+                    let ptr2 = ret_area.0.as_mut_ptr().cast::<u8>();
+                    #[cfg(target_arch = "wasm32")]
+                    #[link(wasm_import_module = "theater:simple/store")]
+                    unsafe extern "C" {
+                        #[link_name = "remove-label"]
+                        fn wit_import3(
+                            _: *mut u8,
+                            _: usize,
+                            _: *mut u8,
+                            _: usize,
+                            _: *mut u8,
+                        );
+                    }
+                    #[cfg(not(target_arch = "wasm32"))]
+                    unsafe extern "C" fn wit_import3(
+                        _: *mut u8,
+                        _: usize,
+                        _: *mut u8,
+                        _: usize,
+                        _: *mut u8,
+                    ) {
+                        unreachable!()
+                    }
+                    unsafe {
+                        wit_import3(ptr0.cast_mut(), len0, ptr1.cast_mut(), len1, ptr2)
+                    };
+                    let l4 = i32::from(*ptr2.add(0).cast::<u8>());
+                    let result8 = match l4 {
+                        0 => {
+                            let e = ();
+                            Ok(e)
+                        }
+                        1 => {
+                            let e = {
+                                let l5 = *ptr2
+                                    .add(::core::mem::size_of::<*const u8>())
+                                    .cast::<*mut u8>();
+                                let l6 = *ptr2
+                                    .add(2 * ::core::mem::size_of::<*const u8>())
+                                    .cast::<usize>();
+                                let len7 = l6;
+                                let bytes7 = _rt::Vec::from_raw_parts(
+                                    l5.cast(),
+                                    len7,
+                                    len7,
+                                );
+                                _rt::string_lift(bytes7)
+                            };
+                            Err(e)
+                        }
+                        _ => _rt::invalid_enum_discriminant(),
+                    };
+                    result8
+                }
+            }
+            #[allow(unused_unsafe, clippy::all)]
+            /// # Remove a specific content reference from a label
+            ///
+            /// Removes the association between a label and a specific content reference.
+            ///
+            /// ## Parameters
+            ///
+            /// * `store-id` - ID of the store to use
+            /// * `label` - The label to modify
+            /// * `content-ref` - The content reference to remove from the label
+            ///
+            /// ## Returns
+            ///
+            /// * `Ok(_)` - Reference was successfully removed from the label
+            /// * `Err(string)` - Error message if removal fails
+            ///
+            /// ## Example
+            ///
+            /// ```rust
+            /// use ntwk::theater::store;
+            ///
+            /// // Remove a specific version from the "historical-configs" label
+            /// store::remove_from_label(store_id, "historical-configs", outdated_ref)?;
+            /// ```
+            ///
+            /// ## Implementation Notes
+            ///
+            /// This operation only removes the association between the label and the content reference.
+            /// It does not delete the content itself.
+            pub fn remove_from_label(
+                store_id: &str,
+                label: &str,
+                content_ref: &ContentRef,
+            ) -> Result<(), _rt::String> {
+                unsafe {
+                    #[cfg_attr(target_pointer_width = "64", repr(align(8)))]
+                    #[cfg_attr(target_pointer_width = "32", repr(align(4)))]
+                    struct RetArea(
+                        [::core::mem::MaybeUninit<
+                            u8,
+                        >; 3 * ::core::mem::size_of::<*const u8>()],
+                    );
+                    let mut ret_area = RetArea(
+                        [::core::mem::MaybeUninit::uninit(); 3
+                            * ::core::mem::size_of::<*const u8>()],
+                    );
+                    let vec0 = store_id;
+                    let ptr0 = vec0.as_ptr().cast::<u8>();
+                    let len0 = vec0.len();
+                    let vec1 = label;
+                    let ptr1 = vec1.as_ptr().cast::<u8>();
+                    let len1 = vec1.len();
+                    let ContentRef { hash: hash2 } = content_ref;
+                    let vec3 = hash2;
+                    let ptr3 = vec3.as_ptr().cast::<u8>();
+                    let len3 = vec3.len();
+                    let ptr4 = ret_area.0.as_mut_ptr().cast::<u8>();
+                    #[cfg(target_arch = "wasm32")]
+                    #[link(wasm_import_module = "theater:simple/store")]
+                    unsafe extern "C" {
+                        #[link_name = "remove-from-label"]
+                        fn wit_import5(
+                            _: *mut u8,
+                            _: usize,
+                            _: *mut u8,
+                            _: usize,
+                            _: *mut u8,
+                            _: usize,
+                            _: *mut u8,
+                        );
+                    }
+                    #[cfg(not(target_arch = "wasm32"))]
+                    unsafe extern "C" fn wit_import5(
+                        _: *mut u8,
+                        _: usize,
+                        _: *mut u8,
+                        _: usize,
+                        _: *mut u8,
+                        _: usize,
+                        _: *mut u8,
+                    ) {
+                        unreachable!()
+                    }
+                    unsafe {
+                        wit_import5(
+                            ptr0.cast_mut(),
+                            len0,
+                            ptr1.cast_mut(),
+                            len1,
+                            ptr3.cast_mut(),
+                            len3,
+                            ptr4,
+                        )
+                    };
+                    let l6 = i32::from(*ptr4.add(0).cast::<u8>());
+                    let result10 = match l6 {
+                        0 => {
+                            let e = ();
+                            Ok(e)
+                        }
+                        1 => {
+                            let e = {
+                                let l7 = *ptr4
+                                    .add(::core::mem::size_of::<*const u8>())
+                                    .cast::<*mut u8>();
+                                let l8 = *ptr4
+                                    .add(2 * ::core::mem::size_of::<*const u8>())
+                                    .cast::<usize>();
+                                let len9 = l8;
+                                let bytes9 = _rt::Vec::from_raw_parts(
+                                    l7.cast(),
+                                    len9,
+                                    len9,
+                                );
+                                _rt::string_lift(bytes9)
+                            };
+                            Err(e)
+                        }
+                        _ => _rt::invalid_enum_discriminant(),
+                    };
+                    result10
+                }
+            }
+            #[allow(unused_unsafe, clippy::all)]
+            /// # Store content and immediately label it
+            ///
+            /// Stores content and associates it with a label in a single operation.
+            ///
+            /// ## Parameters
+            ///
+            /// * `store-id` - ID of the store to use
+            /// * `label` - The label to attach to the content
+            /// * `content` - The content bytes to store
+            ///
+            /// ## Returns
+            ///
+            /// * `Ok(content-ref)` - Reference to the stored content
+            /// * `Err(string)` - Error message if the operation fails
+            ///
+            /// ## Example
+            ///
+            /// ```rust
+            /// use ntwk::theater::store;
+            ///
+            /// // Store and label user data in one operation
+            /// let user_data = serde_json::to_vec(&user)?;
+            /// let ref = store::store_at_label(store_id, "user-profile", user_data)?;
+            /// ```
+            ///
+            /// ## Implementation Notes
+            ///
+            /// This is a convenience function that combines `store` and `label` operations.
+            /// The label will point to the new content reference in addition to any existing
+            /// content references it may already point to.
+            pub fn store_at_label(
+                store_id: &str,
+                label: &str,
+                content: &[u8],
+            ) -> Result<ContentRef, _rt::String> {
+                unsafe {
+                    #[cfg_attr(target_pointer_width = "64", repr(align(8)))]
+                    #[cfg_attr(target_pointer_width = "32", repr(align(4)))]
+                    struct RetArea(
+                        [::core::mem::MaybeUninit<
+                            u8,
+                        >; 3 * ::core::mem::size_of::<*const u8>()],
+                    );
+                    let mut ret_area = RetArea(
+                        [::core::mem::MaybeUninit::uninit(); 3
+                            * ::core::mem::size_of::<*const u8>()],
+                    );
+                    let vec0 = store_id;
+                    let ptr0 = vec0.as_ptr().cast::<u8>();
+                    let len0 = vec0.len();
+                    let vec1 = label;
+                    let ptr1 = vec1.as_ptr().cast::<u8>();
+                    let len1 = vec1.len();
+                    let vec2 = content;
+                    let ptr2 = vec2.as_ptr().cast::<u8>();
+                    let len2 = vec2.len();
+                    let ptr3 = ret_area.0.as_mut_ptr().cast::<u8>();
+                    #[cfg(target_arch = "wasm32")]
+                    #[link(wasm_import_module = "theater:simple/store")]
+                    unsafe extern "C" {
+                        #[link_name = "store-at-label"]
+                        fn wit_import4(
+                            _: *mut u8,
+                            _: usize,
+                            _: *mut u8,
+                            _: usize,
+                            _: *mut u8,
+                            _: usize,
+                            _: *mut u8,
+                        );
+                    }
+                    #[cfg(not(target_arch = "wasm32"))]
+                    unsafe extern "C" fn wit_import4(
+                        _: *mut u8,
+                        _: usize,
+                        _: *mut u8,
+                        _: usize,
+                        _: *mut u8,
+                        _: usize,
+                        _: *mut u8,
+                    ) {
+                        unreachable!()
+                    }
+                    unsafe {
+                        wit_import4(
+                            ptr0.cast_mut(),
+                            len0,
+                            ptr1.cast_mut(),
+                            len1,
+                            ptr2.cast_mut(),
+                            len2,
+                            ptr3,
+                        )
+                    };
+                    let l5 = i32::from(*ptr3.add(0).cast::<u8>());
+                    let result12 = match l5 {
+                        0 => {
+                            let e = {
+                                let l6 = *ptr3
+                                    .add(::core::mem::size_of::<*const u8>())
+                                    .cast::<*mut u8>();
+                                let l7 = *ptr3
+                                    .add(2 * ::core::mem::size_of::<*const u8>())
+                                    .cast::<usize>();
+                                let len8 = l7;
+                                let bytes8 = _rt::Vec::from_raw_parts(
+                                    l6.cast(),
+                                    len8,
+                                    len8,
+                                );
+                                ContentRef {
+                                    hash: _rt::string_lift(bytes8),
+                                }
+                            };
+                            Ok(e)
+                        }
+                        1 => {
+                            let e = {
+                                let l9 = *ptr3
+                                    .add(::core::mem::size_of::<*const u8>())
+                                    .cast::<*mut u8>();
+                                let l10 = *ptr3
+                                    .add(2 * ::core::mem::size_of::<*const u8>())
+                                    .cast::<usize>();
+                                let len11 = l10;
+                                let bytes11 = _rt::Vec::from_raw_parts(
+                                    l9.cast(),
+                                    len11,
+                                    len11,
+                                );
+                                _rt::string_lift(bytes11)
+                            };
+                            Err(e)
+                        }
+                        _ => _rt::invalid_enum_discriminant(),
+                    };
+                    result12
+                }
+            }
+            #[allow(unused_unsafe, clippy::all)]
+            /// # Replace content at a label
+            ///
+            /// Stores new content and makes the label point exclusively to it, removing any
+            /// previous associations.
+            ///
+            /// ## Parameters
+            ///
+            /// * `store-id` - ID of the store to use
+            /// * `label` - The label to update
+            /// * `content` - The new content bytes to store
+            ///
+            /// ## Returns
+            ///
+            /// * `Ok(content-ref)` - Reference to the stored content
+            /// * `Err(string)` - Error message if the operation fails
+            ///
+            /// ## Example
+            ///
+            /// ```rust
+            /// use ntwk::theater::store;
+            ///
+            /// // Update configuration with new values
+            /// let new_config = serde_json::to_vec(&updated_config)?;
+            /// let ref = store::replace_content_at_label(store_id, "current-config", new_config)?;
+            /// ```
+            ///
+            /// ## Implementation Notes
+            ///
+            /// This operation is atomic - the label will either point to the new content reference
+            /// or remain unchanged if the operation fails.
+            pub fn replace_content_at_label(
+                store_id: &str,
+                label: &str,
+                content: &[u8],
+            ) -> Result<ContentRef, _rt::String> {
+                unsafe {
+                    #[cfg_attr(target_pointer_width = "64", repr(align(8)))]
+                    #[cfg_attr(target_pointer_width = "32", repr(align(4)))]
+                    struct RetArea(
+                        [::core::mem::MaybeUninit<
+                            u8,
+                        >; 3 * ::core::mem::size_of::<*const u8>()],
+                    );
+                    let mut ret_area = RetArea(
+                        [::core::mem::MaybeUninit::uninit(); 3
+                            * ::core::mem::size_of::<*const u8>()],
+                    );
+                    let vec0 = store_id;
+                    let ptr0 = vec0.as_ptr().cast::<u8>();
+                    let len0 = vec0.len();
+                    let vec1 = label;
+                    let ptr1 = vec1.as_ptr().cast::<u8>();
+                    let len1 = vec1.len();
+                    let vec2 = content;
+                    let ptr2 = vec2.as_ptr().cast::<u8>();
+                    let len2 = vec2.len();
+                    let ptr3 = ret_area.0.as_mut_ptr().cast::<u8>();
+                    #[cfg(target_arch = "wasm32")]
+                    #[link(wasm_import_module = "theater:simple/store")]
+                    unsafe extern "C" {
+                        #[link_name = "replace-content-at-label"]
+                        fn wit_import4(
+                            _: *mut u8,
+                            _: usize,
+                            _: *mut u8,
+                            _: usize,
+                            _: *mut u8,
+                            _: usize,
+                            _: *mut u8,
+                        );
+                    }
+                    #[cfg(not(target_arch = "wasm32"))]
+                    unsafe extern "C" fn wit_import4(
+                        _: *mut u8,
+                        _: usize,
+                        _: *mut u8,
+                        _: usize,
+                        _: *mut u8,
+                        _: usize,
+                        _: *mut u8,
+                    ) {
+                        unreachable!()
+                    }
+                    unsafe {
+                        wit_import4(
+                            ptr0.cast_mut(),
+                            len0,
+                            ptr1.cast_mut(),
+                            len1,
+                            ptr2.cast_mut(),
+                            len2,
+                            ptr3,
+                        )
+                    };
+                    let l5 = i32::from(*ptr3.add(0).cast::<u8>());
+                    let result12 = match l5 {
+                        0 => {
+                            let e = {
+                                let l6 = *ptr3
+                                    .add(::core::mem::size_of::<*const u8>())
+                                    .cast::<*mut u8>();
+                                let l7 = *ptr3
+                                    .add(2 * ::core::mem::size_of::<*const u8>())
+                                    .cast::<usize>();
+                                let len8 = l7;
+                                let bytes8 = _rt::Vec::from_raw_parts(
+                                    l6.cast(),
+                                    len8,
+                                    len8,
+                                );
+                                ContentRef {
+                                    hash: _rt::string_lift(bytes8),
+                                }
+                            };
+                            Ok(e)
+                        }
+                        1 => {
+                            let e = {
+                                let l9 = *ptr3
+                                    .add(::core::mem::size_of::<*const u8>())
+                                    .cast::<*mut u8>();
+                                let l10 = *ptr3
+                                    .add(2 * ::core::mem::size_of::<*const u8>())
+                                    .cast::<usize>();
+                                let len11 = l10;
+                                let bytes11 = _rt::Vec::from_raw_parts(
+                                    l9.cast(),
+                                    len11,
+                                    len11,
+                                );
+                                _rt::string_lift(bytes11)
+                            };
+                            Err(e)
+                        }
+                        _ => _rt::invalid_enum_discriminant(),
+                    };
+                    result12
+                }
+            }
+            #[allow(unused_unsafe, clippy::all)]
+            /// # Replace label with specific content reference
+            ///
+            /// Updates a label to point exclusively to an existing content reference.
+            ///
+            /// ## Parameters
+            ///
+            /// * `store-id` - ID of the store to use
+            /// * `label` - The label to update
+            /// * `content-ref` - The content reference the label should point to
+            ///
+            /// ## Returns
+            ///
+            /// * `Ok(_)` - Label was successfully updated
+            /// * `Err(string)` - Error message if the update fails
+            ///
+            /// ## Example
+            ///
+            /// ```rust
+            /// use ntwk::theater::store;
+            ///
+            /// // Revert to a previous version
+            /// store::replace_at_label(store_id, "current-config", previous_version_ref)?;
+            /// ```
+            ///
+            /// ## Implementation Notes
+            ///
+            /// This operation removes any existing associations between the label and other
+            /// content references. After this operation, the label will point only to the
+            /// specified content reference.
+            pub fn replace_at_label(
+                store_id: &str,
+                label: &str,
+                content_ref: &ContentRef,
+            ) -> Result<(), _rt::String> {
+                unsafe {
+                    #[cfg_attr(target_pointer_width = "64", repr(align(8)))]
+                    #[cfg_attr(target_pointer_width = "32", repr(align(4)))]
+                    struct RetArea(
+                        [::core::mem::MaybeUninit<
+                            u8,
+                        >; 3 * ::core::mem::size_of::<*const u8>()],
+                    );
+                    let mut ret_area = RetArea(
+                        [::core::mem::MaybeUninit::uninit(); 3
+                            * ::core::mem::size_of::<*const u8>()],
+                    );
+                    let vec0 = store_id;
+                    let ptr0 = vec0.as_ptr().cast::<u8>();
+                    let len0 = vec0.len();
+                    let vec1 = label;
+                    let ptr1 = vec1.as_ptr().cast::<u8>();
+                    let len1 = vec1.len();
+                    let ContentRef { hash: hash2 } = content_ref;
+                    let vec3 = hash2;
+                    let ptr3 = vec3.as_ptr().cast::<u8>();
+                    let len3 = vec3.len();
+                    let ptr4 = ret_area.0.as_mut_ptr().cast::<u8>();
+                    #[cfg(target_arch = "wasm32")]
+                    #[link(wasm_import_module = "theater:simple/store")]
+                    unsafe extern "C" {
+                        #[link_name = "replace-at-label"]
+                        fn wit_import5(
+                            _: *mut u8,
+                            _: usize,
+                            _: *mut u8,
+                            _: usize,
+                            _: *mut u8,
+                            _: usize,
+                            _: *mut u8,
+                        );
+                    }
+                    #[cfg(not(target_arch = "wasm32"))]
+                    unsafe extern "C" fn wit_import5(
+                        _: *mut u8,
+                        _: usize,
+                        _: *mut u8,
+                        _: usize,
+                        _: *mut u8,
+                        _: usize,
+                        _: *mut u8,
+                    ) {
+                        unreachable!()
+                    }
+                    unsafe {
+                        wit_import5(
+                            ptr0.cast_mut(),
+                            len0,
+                            ptr1.cast_mut(),
+                            len1,
+                            ptr3.cast_mut(),
+                            len3,
+                            ptr4,
+                        )
+                    };
+                    let l6 = i32::from(*ptr4.add(0).cast::<u8>());
+                    let result10 = match l6 {
+                        0 => {
+                            let e = ();
+                            Ok(e)
+                        }
+                        1 => {
+                            let e = {
+                                let l7 = *ptr4
+                                    .add(::core::mem::size_of::<*const u8>())
+                                    .cast::<*mut u8>();
+                                let l8 = *ptr4
+                                    .add(2 * ::core::mem::size_of::<*const u8>())
+                                    .cast::<usize>();
+                                let len9 = l8;
+                                let bytes9 = _rt::Vec::from_raw_parts(
+                                    l7.cast(),
+                                    len9,
+                                    len9,
+                                );
+                                _rt::string_lift(bytes9)
+                            };
+                            Err(e)
+                        }
+                        _ => _rt::invalid_enum_discriminant(),
+                    };
+                    result10
+                }
+            }
+            #[allow(unused_unsafe, clippy::all)]
+            /// # List all labels
+            ///
+            /// Retrieves a list of all labels in the store.
+            ///
+            /// ## Parameters
+            ///
+            /// * `store-id` - ID of the store to query
+            ///
+            /// ## Returns
+            ///
+            /// * `Ok(list<string>)` - List of all labels in the store
+            /// * `Err(string)` - Error message if the operation fails
+            ///
+            /// ## Example
+            ///
+            /// ```rust
+            /// use ntwk::theater::store;
+            ///
+            /// // Get all available labels
+            /// let labels = store::list_labels(store_id)?;
+            /// for label in labels {
+            /// println!("Found label: {}", label);
+            /// }
+            /// ```
+            pub fn list_labels(
+                store_id: &str,
+            ) -> Result<_rt::Vec<_rt::String>, _rt::String> {
+                unsafe {
+                    #[cfg_attr(target_pointer_width = "64", repr(align(8)))]
+                    #[cfg_attr(target_pointer_width = "32", repr(align(4)))]
+                    struct RetArea(
+                        [::core::mem::MaybeUninit<
+                            u8,
+                        >; 3 * ::core::mem::size_of::<*const u8>()],
+                    );
+                    let mut ret_area = RetArea(
+                        [::core::mem::MaybeUninit::uninit(); 3
+                            * ::core::mem::size_of::<*const u8>()],
+                    );
+                    let vec0 = store_id;
+                    let ptr0 = vec0.as_ptr().cast::<u8>();
+                    let len0 = vec0.len();
+                    let ptr1 = ret_area.0.as_mut_ptr().cast::<u8>();
+                    #[cfg(target_arch = "wasm32")]
+                    #[link(wasm_import_module = "theater:simple/store")]
+                    unsafe extern "C" {
+                        #[link_name = "list-labels"]
+                        fn wit_import2(_: *mut u8, _: usize, _: *mut u8);
+                    }
+                    #[cfg(not(target_arch = "wasm32"))]
+                    unsafe extern "C" fn wit_import2(_: *mut u8, _: usize, _: *mut u8) {
+                        unreachable!()
+                    }
+                    unsafe { wit_import2(ptr0.cast_mut(), len0, ptr1) };
+                    let l3 = i32::from(*ptr1.add(0).cast::<u8>());
+                    let result13 = match l3 {
+                        0 => {
+                            let e = {
+                                let l4 = *ptr1
+                                    .add(::core::mem::size_of::<*const u8>())
+                                    .cast::<*mut u8>();
+                                let l5 = *ptr1
+                                    .add(2 * ::core::mem::size_of::<*const u8>())
+                                    .cast::<usize>();
+                                let base9 = l4;
+                                let len9 = l5;
+                                let mut result9 = _rt::Vec::with_capacity(len9);
+                                for i in 0..len9 {
+                                    let base = base9
+                                        .add(i * (2 * ::core::mem::size_of::<*const u8>()));
+                                    let e9 = {
+                                        let l6 = *base.add(0).cast::<*mut u8>();
+                                        let l7 = *base
+                                            .add(::core::mem::size_of::<*const u8>())
+                                            .cast::<usize>();
+                                        let len8 = l7;
+                                        let bytes8 = _rt::Vec::from_raw_parts(
+                                            l6.cast(),
+                                            len8,
+                                            len8,
+                                        );
+                                        _rt::string_lift(bytes8)
+                                    };
+                                    result9.push(e9);
+                                }
+                                _rt::cabi_dealloc(
+                                    base9,
+                                    len9 * (2 * ::core::mem::size_of::<*const u8>()),
+                                    ::core::mem::size_of::<*const u8>(),
+                                );
+                                result9
+                            };
+                            Ok(e)
+                        }
+                        1 => {
+                            let e = {
+                                let l10 = *ptr1
+                                    .add(::core::mem::size_of::<*const u8>())
+                                    .cast::<*mut u8>();
+                                let l11 = *ptr1
+                                    .add(2 * ::core::mem::size_of::<*const u8>())
+                                    .cast::<usize>();
+                                let len12 = l11;
+                                let bytes12 = _rt::Vec::from_raw_parts(
+                                    l10.cast(),
+                                    len12,
+                                    len12,
+                                );
+                                _rt::string_lift(bytes12)
+                            };
+                            Err(e)
+                        }
+                        _ => _rt::invalid_enum_discriminant(),
+                    };
+                    result13
+                }
+            }
+            #[allow(unused_unsafe, clippy::all)]
+            /// # List all content references
+            ///
+            /// Retrieves a list of all content references in the store.
+            ///
+            /// ## Parameters
+            ///
+            /// * `store-id` - ID of the store to query
+            ///
+            /// ## Returns
+            ///
+            /// * `Ok(list<content-ref>)` - List of all content references in the store
+            /// * `Err(string)` - Error message if the operation fails
+            ///
+            /// ## Example
+            ///
+            /// ```rust
+            /// use ntwk::theater::store;
+            ///
+            /// // Get all content references
+            /// let refs = store::list_all_content(store_id)?;
+            /// println!("Store contains {} content items", refs.len());
+            /// ```
+            ///
+            /// ## Implementation Notes
+            ///
+            /// This operation may be expensive for stores with a large amount of content.
+            /// Consider using labels to organize and access content more efficiently.
+            pub fn list_all_content(
+                store_id: &str,
+            ) -> Result<_rt::Vec<ContentRef>, _rt::String> {
+                unsafe {
+                    #[cfg_attr(target_pointer_width = "64", repr(align(8)))]
+                    #[cfg_attr(target_pointer_width = "32", repr(align(4)))]
+                    struct RetArea(
+                        [::core::mem::MaybeUninit<
+                            u8,
+                        >; 3 * ::core::mem::size_of::<*const u8>()],
+                    );
+                    let mut ret_area = RetArea(
+                        [::core::mem::MaybeUninit::uninit(); 3
+                            * ::core::mem::size_of::<*const u8>()],
+                    );
+                    let vec0 = store_id;
+                    let ptr0 = vec0.as_ptr().cast::<u8>();
+                    let len0 = vec0.len();
+                    let ptr1 = ret_area.0.as_mut_ptr().cast::<u8>();
+                    #[cfg(target_arch = "wasm32")]
+                    #[link(wasm_import_module = "theater:simple/store")]
+                    unsafe extern "C" {
+                        #[link_name = "list-all-content"]
+                        fn wit_import2(_: *mut u8, _: usize, _: *mut u8);
+                    }
+                    #[cfg(not(target_arch = "wasm32"))]
+                    unsafe extern "C" fn wit_import2(_: *mut u8, _: usize, _: *mut u8) {
+                        unreachable!()
+                    }
+                    unsafe { wit_import2(ptr0.cast_mut(), len0, ptr1) };
+                    let l3 = i32::from(*ptr1.add(0).cast::<u8>());
+                    let result13 = match l3 {
+                        0 => {
+                            let e = {
+                                let l4 = *ptr1
+                                    .add(::core::mem::size_of::<*const u8>())
+                                    .cast::<*mut u8>();
+                                let l5 = *ptr1
+                                    .add(2 * ::core::mem::size_of::<*const u8>())
+                                    .cast::<usize>();
+                                let base9 = l4;
+                                let len9 = l5;
+                                let mut result9 = _rt::Vec::with_capacity(len9);
+                                for i in 0..len9 {
+                                    let base = base9
+                                        .add(i * (2 * ::core::mem::size_of::<*const u8>()));
+                                    let e9 = {
+                                        let l6 = *base.add(0).cast::<*mut u8>();
+                                        let l7 = *base
+                                            .add(::core::mem::size_of::<*const u8>())
+                                            .cast::<usize>();
+                                        let len8 = l7;
+                                        let bytes8 = _rt::Vec::from_raw_parts(
+                                            l6.cast(),
+                                            len8,
+                                            len8,
+                                        );
+                                        ContentRef {
+                                            hash: _rt::string_lift(bytes8),
+                                        }
+                                    };
+                                    result9.push(e9);
+                                }
+                                _rt::cabi_dealloc(
+                                    base9,
+                                    len9 * (2 * ::core::mem::size_of::<*const u8>()),
+                                    ::core::mem::size_of::<*const u8>(),
+                                );
+                                result9
+                            };
+                            Ok(e)
+                        }
+                        1 => {
+                            let e = {
+                                let l10 = *ptr1
+                                    .add(::core::mem::size_of::<*const u8>())
+                                    .cast::<*mut u8>();
+                                let l11 = *ptr1
+                                    .add(2 * ::core::mem::size_of::<*const u8>())
+                                    .cast::<usize>();
+                                let len12 = l11;
+                                let bytes12 = _rt::Vec::from_raw_parts(
+                                    l10.cast(),
+                                    len12,
+                                    len12,
+                                );
+                                _rt::string_lift(bytes12)
+                            };
+                            Err(e)
+                        }
+                        _ => _rt::invalid_enum_discriminant(),
+                    };
+                    result13
+                }
+            }
+            #[allow(unused_unsafe, clippy::all)]
+            /// # Calculate total size
+            ///
+            /// Calculates the total size of all content in the store.
+            ///
+            /// ## Parameters
+            ///
+            /// * `store-id` - ID of the store to query
+            ///
+            /// ## Returns
+            ///
+            /// * `Ok(u64)` - Total size in bytes
+            /// * `Err(string)` - Error message if the calculation fails
+            ///
+            /// ## Example
+            ///
+            /// ```rust
+            /// use ntwk::theater::store;
+            ///
+            /// // Check store size
+            /// let total_bytes = store::calculate_total_size(store_id)?;
+            /// println!("Store contains {} bytes of data", total_bytes);
+            ///
+            /// // Format as human-readable size
+            /// let size_mb = total_bytes as f64 / (1024.0 * 1024.0);
+            /// println!("Store size: {:.2} MB", size_mb);
+            /// ```
+            ///
+            /// ## Implementation Notes
+            ///
+            /// This operation calculates the actual storage space used, accounting for
+            /// deduplication of identical content.
+            pub fn calculate_total_size(store_id: &str) -> Result<u64, _rt::String> {
+                unsafe {
+                    #[repr(align(8))]
+                    struct RetArea(
+                        [::core::mem::MaybeUninit<
+                            u8,
+                        >; 8 + 2 * ::core::mem::size_of::<*const u8>()],
+                    );
+                    let mut ret_area = RetArea(
+                        [::core::mem::MaybeUninit::uninit(); 8
+                            + 2 * ::core::mem::size_of::<*const u8>()],
+                    );
+                    let vec0 = store_id;
+                    let ptr0 = vec0.as_ptr().cast::<u8>();
+                    let len0 = vec0.len();
+                    let ptr1 = ret_area.0.as_mut_ptr().cast::<u8>();
+                    #[cfg(target_arch = "wasm32")]
+                    #[link(wasm_import_module = "theater:simple/store")]
+                    unsafe extern "C" {
+                        #[link_name = "calculate-total-size"]
+                        fn wit_import2(_: *mut u8, _: usize, _: *mut u8);
+                    }
+                    #[cfg(not(target_arch = "wasm32"))]
+                    unsafe extern "C" fn wit_import2(_: *mut u8, _: usize, _: *mut u8) {
+                        unreachable!()
+                    }
+                    unsafe { wit_import2(ptr0.cast_mut(), len0, ptr1) };
+                    let l3 = i32::from(*ptr1.add(0).cast::<u8>());
+                    let result8 = match l3 {
+                        0 => {
+                            let e = {
+                                let l4 = *ptr1.add(8).cast::<i64>();
+                                l4 as u64
+                            };
+                            Ok(e)
+                        }
+                        1 => {
+                            let e = {
+                                let l5 = *ptr1.add(8).cast::<*mut u8>();
+                                let l6 = *ptr1
+                                    .add(8 + 1 * ::core::mem::size_of::<*const u8>())
+                                    .cast::<usize>();
+                                let len7 = l6;
+                                let bytes7 = _rt::Vec::from_raw_parts(
+                                    l5.cast(),
+                                    len7,
+                                    len7,
+                                );
+                                _rt::string_lift(bytes7)
+                            };
+                            Err(e)
+                        }
+                        _ => _rt::invalid_enum_discriminant(),
+                    };
+                    result8
+                }
+            }
+        }
+        #[allow(dead_code, async_fn_in_trait, unused_imports, clippy::all)]
+        pub mod random {
+            #[used]
+            #[doc(hidden)]
+            static __FORCE_SECTION_REF: fn() = super::super::super::__link_custom_section_describing_imports;
+            use super::super::super::_rt;
+            #[allow(unused_unsafe, clippy::all)]
+            pub fn random_bytes(length: u32) -> Result<_rt::Vec<u8>, _rt::String> {
+                unsafe {
+                    #[cfg_attr(target_pointer_width = "64", repr(align(8)))]
+                    #[cfg_attr(target_pointer_width = "32", repr(align(4)))]
+                    struct RetArea(
+                        [::core::mem::MaybeUninit<
+                            u8,
+                        >; 3 * ::core::mem::size_of::<*const u8>()],
+                    );
+                    let mut ret_area = RetArea(
+                        [::core::mem::MaybeUninit::uninit(); 3
+                            * ::core::mem::size_of::<*const u8>()],
+                    );
+                    let ptr0 = ret_area.0.as_mut_ptr().cast::<u8>();
+                    #[cfg(target_arch = "wasm32")]
+                    #[link(wasm_import_module = "theater:simple/random")]
+                    unsafe extern "C" {
+                        #[link_name = "random-bytes"]
+                        fn wit_import1(_: i32, _: *mut u8);
+                    }
+                    #[cfg(not(target_arch = "wasm32"))]
+                    unsafe extern "C" fn wit_import1(_: i32, _: *mut u8) {
+                        unreachable!()
+                    }
+                    unsafe { wit_import1(_rt::as_i32(&length), ptr0) };
+                    let l2 = i32::from(*ptr0.add(0).cast::<u8>());
+                    let result9 = match l2 {
+                        0 => {
+                            let e = {
+                                let l3 = *ptr0
+                                    .add(::core::mem::size_of::<*const u8>())
+                                    .cast::<*mut u8>();
+                                let l4 = *ptr0
+                                    .add(2 * ::core::mem::size_of::<*const u8>())
+                                    .cast::<usize>();
+                                let len5 = l4;
+                                _rt::Vec::from_raw_parts(l3.cast(), len5, len5)
+                            };
+                            Ok(e)
+                        }
+                        1 => {
+                            let e = {
+                                let l6 = *ptr0
+                                    .add(::core::mem::size_of::<*const u8>())
+                                    .cast::<*mut u8>();
+                                let l7 = *ptr0
+                                    .add(2 * ::core::mem::size_of::<*const u8>())
+                                    .cast::<usize>();
+                                let len8 = l7;
+                                let bytes8 = _rt::Vec::from_raw_parts(
+                                    l6.cast(),
+                                    len8,
+                                    len8,
+                                );
+                                _rt::string_lift(bytes8)
+                            };
+                            Err(e)
+                        }
+                        _ => _rt::invalid_enum_discriminant(),
+                    };
+                    result9
+                }
+            }
+            #[allow(unused_unsafe, clippy::all)]
+            pub fn random_range(min: u64, max: u64) -> Result<u64, _rt::String> {
+                unsafe {
+                    #[repr(align(8))]
+                    struct RetArea(
+                        [::core::mem::MaybeUninit<
+                            u8,
+                        >; 8 + 2 * ::core::mem::size_of::<*const u8>()],
+                    );
+                    let mut ret_area = RetArea(
+                        [::core::mem::MaybeUninit::uninit(); 8
+                            + 2 * ::core::mem::size_of::<*const u8>()],
+                    );
+                    let ptr0 = ret_area.0.as_mut_ptr().cast::<u8>();
+                    #[cfg(target_arch = "wasm32")]
+                    #[link(wasm_import_module = "theater:simple/random")]
+                    unsafe extern "C" {
+                        #[link_name = "random-range"]
+                        fn wit_import1(_: i64, _: i64, _: *mut u8);
+                    }
+                    #[cfg(not(target_arch = "wasm32"))]
+                    unsafe extern "C" fn wit_import1(_: i64, _: i64, _: *mut u8) {
+                        unreachable!()
+                    }
+                    unsafe { wit_import1(_rt::as_i64(&min), _rt::as_i64(&max), ptr0) };
+                    let l2 = i32::from(*ptr0.add(0).cast::<u8>());
+                    let result7 = match l2 {
+                        0 => {
+                            let e = {
+                                let l3 = *ptr0.add(8).cast::<i64>();
+                                l3 as u64
+                            };
+                            Ok(e)
+                        }
+                        1 => {
+                            let e = {
+                                let l4 = *ptr0.add(8).cast::<*mut u8>();
+                                let l5 = *ptr0
+                                    .add(8 + 1 * ::core::mem::size_of::<*const u8>())
+                                    .cast::<usize>();
+                                let len6 = l5;
+                                let bytes6 = _rt::Vec::from_raw_parts(
+                                    l4.cast(),
+                                    len6,
+                                    len6,
+                                );
+                                _rt::string_lift(bytes6)
+                            };
+                            Err(e)
+                        }
+                        _ => _rt::invalid_enum_discriminant(),
+                    };
+                    result7
+                }
+            }
+            #[allow(unused_unsafe, clippy::all)]
+            pub fn random_float() -> Result<f64, _rt::String> {
+                unsafe {
+                    #[repr(align(8))]
+                    struct RetArea(
+                        [::core::mem::MaybeUninit<
+                            u8,
+                        >; 8 + 2 * ::core::mem::size_of::<*const u8>()],
+                    );
+                    let mut ret_area = RetArea(
+                        [::core::mem::MaybeUninit::uninit(); 8
+                            + 2 * ::core::mem::size_of::<*const u8>()],
+                    );
+                    let ptr0 = ret_area.0.as_mut_ptr().cast::<u8>();
+                    #[cfg(target_arch = "wasm32")]
+                    #[link(wasm_import_module = "theater:simple/random")]
+                    unsafe extern "C" {
+                        #[link_name = "random-float"]
+                        fn wit_import1(_: *mut u8);
+                    }
+                    #[cfg(not(target_arch = "wasm32"))]
+                    unsafe extern "C" fn wit_import1(_: *mut u8) {
+                        unreachable!()
+                    }
+                    unsafe { wit_import1(ptr0) };
+                    let l2 = i32::from(*ptr0.add(0).cast::<u8>());
+                    let result7 = match l2 {
+                        0 => {
+                            let e = {
+                                let l3 = *ptr0.add(8).cast::<f64>();
+                                l3
+                            };
+                            Ok(e)
+                        }
+                        1 => {
+                            let e = {
+                                let l4 = *ptr0.add(8).cast::<*mut u8>();
+                                let l5 = *ptr0
+                                    .add(8 + 1 * ::core::mem::size_of::<*const u8>())
+                                    .cast::<usize>();
+                                let len6 = l5;
+                                let bytes6 = _rt::Vec::from_raw_parts(
+                                    l4.cast(),
+                                    len6,
+                                    len6,
+                                );
+                                _rt::string_lift(bytes6)
+                            };
+                            Err(e)
+                        }
+                        _ => _rt::invalid_enum_discriminant(),
+                    };
+                    result7
+                }
+            }
+            #[allow(unused_unsafe, clippy::all)]
+            pub fn generate_uuid() -> Result<_rt::String, _rt::String> {
+                unsafe {
+                    #[cfg_attr(target_pointer_width = "64", repr(align(8)))]
+                    #[cfg_attr(target_pointer_width = "32", repr(align(4)))]
+                    struct RetArea(
+                        [::core::mem::MaybeUninit<
+                            u8,
+                        >; 3 * ::core::mem::size_of::<*const u8>()],
+                    );
+                    let mut ret_area = RetArea(
+                        [::core::mem::MaybeUninit::uninit(); 3
+                            * ::core::mem::size_of::<*const u8>()],
+                    );
+                    let ptr0 = ret_area.0.as_mut_ptr().cast::<u8>();
+                    #[cfg(target_arch = "wasm32")]
+                    #[link(wasm_import_module = "theater:simple/random")]
+                    unsafe extern "C" {
+                        #[link_name = "generate-uuid"]
+                        fn wit_import1(_: *mut u8);
+                    }
+                    #[cfg(not(target_arch = "wasm32"))]
+                    unsafe extern "C" fn wit_import1(_: *mut u8) {
+                        unreachable!()
+                    }
+                    unsafe { wit_import1(ptr0) };
+                    let l2 = i32::from(*ptr0.add(0).cast::<u8>());
+                    let result9 = match l2 {
+                        0 => {
+                            let e = {
+                                let l3 = *ptr0
+                                    .add(::core::mem::size_of::<*const u8>())
+                                    .cast::<*mut u8>();
+                                let l4 = *ptr0
+                                    .add(2 * ::core::mem::size_of::<*const u8>())
+                                    .cast::<usize>();
+                                let len5 = l4;
+                                let bytes5 = _rt::Vec::from_raw_parts(
+                                    l3.cast(),
+                                    len5,
+                                    len5,
+                                );
+                                _rt::string_lift(bytes5)
+                            };
+                            Ok(e)
+                        }
+                        1 => {
+                            let e = {
+                                let l6 = *ptr0
+                                    .add(::core::mem::size_of::<*const u8>())
+                                    .cast::<*mut u8>();
+                                let l7 = *ptr0
+                                    .add(2 * ::core::mem::size_of::<*const u8>())
+                                    .cast::<usize>();
+                                let len8 = l7;
+                                let bytes8 = _rt::Vec::from_raw_parts(
+                                    l6.cast(),
+                                    len8,
+                                    len8,
+                                );
+                                _rt::string_lift(bytes8)
+                            };
+                            Err(e)
+                        }
+                        _ => _rt::invalid_enum_discriminant(),
+                    };
+                    result9
+                }
+            }
+        }
     }
 }
 #[rustfmt::skip]
@@ -3360,6 +6278,65 @@ mod _rt {
         }
     }
     pub use alloc_crate::alloc;
+    pub fn as_i32<T: AsI32>(t: T) -> i32 {
+        t.as_i32()
+    }
+    pub trait AsI32 {
+        fn as_i32(self) -> i32;
+    }
+    impl<'a, T: Copy + AsI32> AsI32 for &'a T {
+        fn as_i32(self) -> i32 {
+            (*self).as_i32()
+        }
+    }
+    impl AsI32 for i32 {
+        #[inline]
+        fn as_i32(self) -> i32 {
+            self as i32
+        }
+    }
+    impl AsI32 for u32 {
+        #[inline]
+        fn as_i32(self) -> i32 {
+            self as i32
+        }
+    }
+    impl AsI32 for i16 {
+        #[inline]
+        fn as_i32(self) -> i32 {
+            self as i32
+        }
+    }
+    impl AsI32 for u16 {
+        #[inline]
+        fn as_i32(self) -> i32 {
+            self as i32
+        }
+    }
+    impl AsI32 for i8 {
+        #[inline]
+        fn as_i32(self) -> i32 {
+            self as i32
+        }
+    }
+    impl AsI32 for u8 {
+        #[inline]
+        fn as_i32(self) -> i32 {
+            self as i32
+        }
+    }
+    impl AsI32 for char {
+        #[inline]
+        fn as_i32(self) -> i32 {
+            self as i32
+        }
+    }
+    impl AsI32 for usize {
+        #[inline]
+        fn as_i32(self) -> i32 {
+            self as i32
+        }
+    }
     #[cfg(target_arch = "wasm32")]
     pub fn run_ctors_once() {
         wit_bindgen_rt::run_ctors_once();
@@ -3405,35 +6382,35 @@ pub(crate) use __export_default_impl as export;
 )]
 #[doc(hidden)]
 #[allow(clippy::octal_escapes)]
-pub static __WIT_BINDGEN_COMPONENT_TYPE: [u8; 4291] = *b"\
-\0asm\x0d\0\x01\0\0\x19\x16wit-component-encoding\x04\0\x07\xc5\x20\x01A\x02\x01\
-A\x1d\x01B<\x01p}\x04\0\x09json-data\x03\0\0\x01s\x04\0\x0eprogress-token\x03\0\x02\
-\x01s\x04\0\x06cursor\x03\0\x04\x01s\x04\0\x0arequest-id\x03\0\x06\x01ks\x01k\x01\
-\x01r\x03\x04names\x0bdescription\x08\x04meta\x09\x04\0\x0dbase-metadata\x03\0\x0a\
-\x01r\x04\x07jsonrpcs\x06methods\x06params\x09\x02id\x07\x04\0\x0fjsonrpc-reques\
-t\x03\0\x0c\x01r\x03\x07jsonrpcs\x02id\x07\x06result\x01\x04\0\x10jsonrpc-respon\
-se\x03\0\x0e\x01r\x03\x07jsonrpcs\x06methods\x06params\x09\x04\0\x14jsonrpc-noti\
-fication\x03\0\x10\x01r\x03\x04codez\x07messages\x04data\x09\x04\0\x0cerror-obje\
-ct\x03\0\x12\x01r\x03\x07jsonrpcs\x02id\x07\x05error\x13\x04\0\x0djsonrpc-error\x03\
-\0\x14\x01r\x04\x04types\x04texts\x0bannotations\x09\x04meta\x09\x04\0\x0ctext-c\
-ontent\x03\0\x16\x01r\x05\x04types\x04datas\x09mime-type\x08\x0bannotations\x09\x04\
-meta\x09\x04\0\x0dimage-content\x03\0\x18\x01r\x05\x04types\x04datas\x09mime-typ\
-e\x08\x0bannotations\x09\x04meta\x09\x04\0\x0daudio-content\x03\0\x1a\x01kw\x01r\
-\x05\x03uris\x09mime-type\x08\x04size\x1c\x0bannotations\x09\x04meta\x09\x04\0\x10\
-resource-content\x03\0\x1d\x01r\x06\x04names\x0bdescription\x08\x0cinput-schema\x01\
-\x0doutput-schema\x09\x0bannotations\x09\x04meta\x09\x04\0\x04tool\x03\0\x1f\x01\
-r\x02\x04names\x09arguments\x01\x04\0\x10tool-call-params\x03\0!\x01r\x06\x04nam\
-es\x0bdescription\x08\x03uris\x09mime-type\x08\x0bannotations\x09\x04meta\x09\x04\
-\0\x0cmcp-resource\x03\0#\x01k\x17\x01k\x19\x01k\x1b\x01k$\x01q\x05\x04text\x01%\
-\0\x05image\x01&\0\x05audio\x01'\0\x08resource\x01\x08\0\x11embedded-resource\x01\
-(\0\x04\0\x0ccontent-item\x03\0)\x01p*\x01k\x7f\x01r\x04\x07content+\x12structur\
-ed-content\x09\x08is-error,\x04meta\x09\x04\0\x10tool-call-result\x03\0-\x01r\x04\
-\x03uris\x09mime-type\x08\x07content*\x04meta\x09\x04\0\x15mcp-resource-contents\
-\x03\0/\x01k\x05\x01r\x02\x0bnext-cursor1\x04meta\x09\x04\0\x10paginated-result\x03\
-\02\x01p\x20\x01r\x03\x05tools4\x0bnext-cursor1\x04meta\x09\x04\0\x11list-tools-\
-result\x03\05\x01p$\x01r\x03\x09resources7\x0bnext-cursor1\x04meta\x09\x04\0\x15\
-list-resources-result\x03\08\x01r\x02\x06cursor1\x04meta\x09\x04\0\x11paginated-\
-request\x03\0:\x03\0\x1dcolinrozzi:mcp-protocol/types\x05\0\x02\x03\0\0\x04tool\x02\
+pub static __WIT_BINDGEN_COMPONENT_TYPE: [u8; 5456] = *b"\
+\0asm\x0d\0\x01\0\0\x19\x16wit-component-encoding\x04\0\x07\xd2)\x01A\x02\x01A#\x01\
+B<\x01p}\x04\0\x09json-data\x03\0\0\x01s\x04\0\x0eprogress-token\x03\0\x02\x01s\x04\
+\0\x06cursor\x03\0\x04\x01s\x04\0\x0arequest-id\x03\0\x06\x01ks\x01k\x01\x01r\x03\
+\x04names\x0bdescription\x08\x04meta\x09\x04\0\x0dbase-metadata\x03\0\x0a\x01r\x04\
+\x07jsonrpcs\x06methods\x06params\x09\x02id\x07\x04\0\x0fjsonrpc-request\x03\0\x0c\
+\x01r\x03\x07jsonrpcs\x02id\x07\x06result\x01\x04\0\x10jsonrpc-response\x03\0\x0e\
+\x01r\x03\x07jsonrpcs\x06methods\x06params\x09\x04\0\x14jsonrpc-notification\x03\
+\0\x10\x01r\x03\x04codez\x07messages\x04data\x09\x04\0\x0cerror-object\x03\0\x12\
+\x01r\x03\x07jsonrpcs\x02id\x07\x05error\x13\x04\0\x0djsonrpc-error\x03\0\x14\x01\
+r\x04\x04types\x04texts\x0bannotations\x09\x04meta\x09\x04\0\x0ctext-content\x03\
+\0\x16\x01r\x05\x04types\x04datas\x09mime-type\x08\x0bannotations\x09\x04meta\x09\
+\x04\0\x0dimage-content\x03\0\x18\x01r\x05\x04types\x04datas\x09mime-type\x08\x0b\
+annotations\x09\x04meta\x09\x04\0\x0daudio-content\x03\0\x1a\x01kw\x01r\x05\x03u\
+ris\x09mime-type\x08\x04size\x1c\x0bannotations\x09\x04meta\x09\x04\0\x10resourc\
+e-content\x03\0\x1d\x01r\x06\x04names\x0bdescription\x08\x0cinput-schema\x01\x0d\
+output-schema\x09\x0bannotations\x09\x04meta\x09\x04\0\x04tool\x03\0\x1f\x01r\x02\
+\x04names\x09arguments\x01\x04\0\x10tool-call-params\x03\0!\x01r\x06\x04names\x0b\
+description\x08\x03uris\x09mime-type\x08\x0bannotations\x09\x04meta\x09\x04\0\x0c\
+mcp-resource\x03\0#\x01k\x17\x01k\x19\x01k\x1b\x01k$\x01q\x05\x04text\x01%\0\x05\
+image\x01&\0\x05audio\x01'\0\x08resource\x01\x08\0\x11embedded-resource\x01(\0\x04\
+\0\x0ccontent-item\x03\0)\x01p*\x01k\x7f\x01r\x04\x07content+\x12structured-cont\
+ent\x09\x08is-error,\x04meta\x09\x04\0\x10tool-call-result\x03\0-\x01r\x04\x03ur\
+is\x09mime-type\x08\x07content*\x04meta\x09\x04\0\x15mcp-resource-contents\x03\0\
+/\x01k\x05\x01r\x02\x0bnext-cursor1\x04meta\x09\x04\0\x10paginated-result\x03\02\
+\x01p\x20\x01r\x03\x05tools4\x0bnext-cursor1\x04meta\x09\x04\0\x11list-tools-res\
+ult\x03\05\x01p$\x01r\x03\x09resources7\x0bnext-cursor1\x04meta\x09\x04\0\x15lis\
+t-resources-result\x03\08\x01r\x02\x06cursor1\x04meta\x09\x04\0\x11paginated-req\
+uest\x03\0:\x03\0\x1dcolinrozzi:mcp-protocol/types\x05\0\x02\x03\0\0\x04tool\x02\
 \x03\0\0\x09json-data\x01B*\x02\x03\x02\x01\x01\x04\0\x04tool\x03\0\0\x02\x03\x02\
 \x01\x02\x04\0\x09json-data\x03\0\x02\x01q\x03\x04user\0\0\x09assistant\0\0\x06s\
 ystem\0\0\x04\0\x0cmessage-role\x03\0\x04\x01r\x03\x02ids\x04names\x05input\x03\x04\
@@ -3485,19 +6462,46 @@ routes-county\x10middleware-county\x11websocket-enabled\x7f\x04\0\x0bserver-info
 \x03\0\x06\x0dhttp-response\x01B\x07\x02\x03\x02\x01\x0b\x04\0\x0chttp-request\x03\
 \0\0\x02\x03\x02\x01\x0c\x04\0\x0dhttp-response\x03\0\x02\x01j\x01\x03\x01s\x01@\
 \x01\x03req\x01\0\x04\x04\0\x09send-http\x01\x05\x03\0\x1atheater:simple/http-cl\
-ient\x05\x0d\x02\x03\0\x02\x05event\x02\x03\0\x02\x0achannel-id\x02\x03\0\x02\x0e\
-channel-accept\x01B\x1d\x02\x03\x02\x01\x0e\x04\0\x05event\x03\0\0\x02\x03\x02\x01\
-\x0f\x04\0\x0achannel-id\x03\0\x02\x02\x03\x02\x01\x10\x04\0\x0echannel-accept\x03\
-\0\x04\x01p}\x01k\x06\x01o\x01\x06\x01o\x01\x07\x01j\x01\x09\x01s\x01@\x02\x05st\
-ate\x07\x06params\x08\0\x0a\x04\0\x0bhandle-send\x01\x0b\x01o\x02s\x06\x01o\x02\x07\
-\x09\x01j\x01\x0d\x01s\x01@\x02\x05state\x07\x06params\x0c\0\x0e\x04\0\x0ehandle\
--request\x01\x0f\x01o\x01\x05\x01o\x02\x07\x10\x01j\x01\x11\x01s\x01@\x02\x05sta\
-te\x07\x06params\x0c\0\x12\x04\0\x13handle-channel-open\x01\x13\x01o\x02\x03\x06\
+ient\x05\x0d\x02\x03\0\x02\x0achannel-id\x01B\x19\x02\x03\x02\x01\x06\x04\0\x08a\
+ctor-id\x03\0\0\x02\x03\x02\x01\x0e\x04\0\x0achannel-id\x03\0\x02\x01p}\x01j\0\x01\
+s\x01@\x02\x08actor-id\x01\x03msg\x04\0\x05\x04\0\x04send\x01\x06\x01j\x01\x04\x01\
+s\x01@\x02\x08actor-id\x01\x03msg\x04\0\x07\x04\0\x07request\x01\x08\x01j\x01\x03\
+\x01s\x01@\x02\x08actor-id\x01\x0binitial-msg\x04\0\x09\x04\0\x0copen-channel\x01\
+\x0a\x01@\x02\x0achannel-id\x03\x03msg\x04\0\x05\x04\0\x0fsend-on-channel\x01\x0b\
+\x01@\x01\x0achannel-id\x03\0\x05\x04\0\x0dclose-channel\x01\x0c\x01ps\x01@\0\0\x0d\
+\x04\0\x19list-outstanding-requests\x01\x0e\x01@\x02\x0arequest-ids\x08response\x04\
+\0\x05\x04\0\x12respond-to-request\x01\x0f\x01@\x01\x0arequest-ids\0\x05\x04\0\x0e\
+cancel-request\x01\x10\x03\0\"theater:simple/message-server-host\x05\x0f\x01B(\x01\
+r\x01\x04hashs\x04\0\x0bcontent-ref\x03\0\0\x01j\x01s\x01s\x01@\0\0\x02\x04\0\x03\
+new\x01\x03\x01p}\x01j\x01\x01\x01s\x01@\x02\x08store-ids\x07content\x04\0\x05\x04\
+\0\x05store\x01\x06\x01j\x01\x04\x01s\x01@\x02\x08store-ids\x0bcontent-ref\x01\0\
+\x07\x04\0\x03get\x01\x08\x01j\x01\x7f\x01s\x01@\x02\x08store-ids\x0bcontent-ref\
+\x01\0\x09\x04\0\x06exists\x01\x0a\x01j\0\x01s\x01@\x03\x08store-ids\x05labels\x0b\
+content-ref\x01\0\x0b\x04\0\x05label\x01\x0c\x01k\x01\x01j\x01\x0d\x01s\x01@\x02\
+\x08store-ids\x05labels\0\x0e\x04\0\x0cget-by-label\x01\x0f\x01@\x02\x08store-id\
+s\x05labels\0\x0b\x04\0\x0cremove-label\x01\x10\x04\0\x11remove-from-label\x01\x0c\
+\x01@\x03\x08store-ids\x05labels\x07content\x04\0\x05\x04\0\x0estore-at-label\x01\
+\x11\x04\0\x18replace-content-at-label\x01\x11\x04\0\x10replace-at-label\x01\x0c\
+\x01ps\x01j\x01\x12\x01s\x01@\x01\x08store-ids\0\x13\x04\0\x0blist-labels\x01\x14\
+\x01p\x01\x01j\x01\x15\x01s\x01@\x01\x08store-ids\0\x16\x04\0\x10list-all-conten\
+t\x01\x17\x01j\x01w\x01s\x01@\x01\x08store-ids\0\x18\x04\0\x14calculate-total-si\
+ze\x01\x19\x03\0\x14theater:simple/store\x05\x10\x01B\x0d\x01p}\x01j\x01\0\x01s\x01\
+@\x01\x06lengthy\0\x01\x04\0\x0crandom-bytes\x01\x02\x01j\x01w\x01s\x01@\x02\x03\
+minw\x03maxw\0\x03\x04\0\x0crandom-range\x01\x04\x01j\x01u\x01s\x01@\0\0\x05\x04\
+\0\x0crandom-float\x01\x06\x01j\x01s\x01s\x01@\0\0\x07\x04\0\x0dgenerate-uuid\x01\
+\x08\x03\0\x15theater:simple/random\x05\x11\x02\x03\0\x02\x05event\x02\x03\0\x02\
+\x0echannel-accept\x01B\x1d\x02\x03\x02\x01\x12\x04\0\x05event\x03\0\0\x02\x03\x02\
+\x01\x0e\x04\0\x0achannel-id\x03\0\x02\x02\x03\x02\x01\x13\x04\0\x0echannel-acce\
+pt\x03\0\x04\x01p}\x01k\x06\x01o\x01\x06\x01o\x01\x07\x01j\x01\x09\x01s\x01@\x02\
+\x05state\x07\x06params\x08\0\x0a\x04\0\x0bhandle-send\x01\x0b\x01o\x02s\x06\x01\
+o\x02\x07\x09\x01j\x01\x0d\x01s\x01@\x02\x05state\x07\x06params\x0c\0\x0e\x04\0\x0e\
+handle-request\x01\x0f\x01o\x01\x05\x01o\x02\x07\x10\x01j\x01\x11\x01s\x01@\x02\x05\
+state\x07\x06params\x0c\0\x12\x04\0\x13handle-channel-open\x01\x13\x01o\x02\x03\x06\
 \x01@\x02\x05state\x07\x06params\x14\0\x0a\x04\0\x16handle-channel-message\x01\x15\
 \x01o\x01\x03\x01@\x02\x05state\x07\x06params\x16\0\x0a\x04\0\x14handle-channel-\
-close\x01\x17\x04\0$theater:simple/message-server-client\x05\x11\x01B\x07\x01p}\x01\
+close\x01\x17\x04\0$theater:simple/message-server-client\x05\x14\x01B\x07\x01p}\x01\
 k\0\x01o\x01s\x01o\x01\x01\x01j\x01\x03\x01s\x01@\x02\x05state\x01\x06params\x02\
-\0\x04\x04\0\x04init\x01\x05\x04\0\x14theater:simple/actor\x05\x12\x04\0!colinro\
+\0\x04\x04\0\x04init\x01\x05\x04\0\x14theater:simple/actor\x05\x15\x04\0!colinro\
 zzi:moonshot-proxy/default\x04\0\x0b\x0d\x01\0\x07default\x03\0\0\0G\x09producer\
 s\x01\x0cprocessed-by\x02\x0dwit-component\x070.227.1\x10wit-bindgen-rust\x060.4\
 1.0";
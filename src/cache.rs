@@ -0,0 +1,168 @@
+// Deterministic-response cache backed by the theater store
+//
+// Responses are stored under labels derived from a hash of the normalized
+// provider request, together with the request itself so that a hash collision
+// is a miss rather than another request's response. The LRU order lives in
+// actor state (`State::cache_index`) so entries beyond `Config::max_cache_size`
+// can be evicted from the store.
+
+use crate::api::ProviderClient;
+use crate::bindings::theater::simple::store;
+use crate::hash::fnv1a_64;
+use crate::logging;
+use crate::types::api::OpenAICompletionRequest;
+use crate::types::response::OpenAICompletionResponse;
+use crate::types::state::State;
+use serde::{Deserialize, Serialize};
+
+/// Prefix for every cache label in the actor's store
+const LABEL_PREFIX: &str = "moonshot-proxy/cache/";
+
+/// Where a request's response is cached
+pub struct CacheKey {
+    /// Store label, from a hash of `request`
+    label: String,
+    /// The normalized request, compared on lookup
+    request: serde_json::Value,
+}
+
+/// A cached response and the normalized request it answered
+#[derive(Serialize, Deserialize)]
+struct CacheEntry {
+    request: serde_json::Value,
+    response: OpenAICompletionResponse,
+}
+
+/// Compute the cache key for a request, or `None` if it shouldn't be cached
+///
+/// Only deterministic requests (temperature 0) are cached unless
+/// `cache.cache_nondeterministic` is set.
+pub fn cache_key(
+    state: &State,
+    client: &ProviderClient,
+    request: &OpenAICompletionRequest,
+) -> Option<CacheKey> {
+    let config = &state.config.cache;
+    if !config.enabled || state.store_id.is_none() {
+        return None;
+    }
+
+    let deterministic = request.temperature == Some(0.0);
+    if !deterministic && !config.cache_nondeterministic {
+        return None;
+    }
+
+    // serde_json sorts object keys, so the provider's request body is already normalized
+    let normalized = serde_json::json!({
+        "base_url": state.config.base_url,
+        "provider": client.provider().name(),
+        "request": client.request_body(request),
+    });
+    let bytes = serde_json::to_vec(&normalized).ok()?;
+
+    Some(CacheKey {
+        label: format!("{}{:016x}", LABEL_PREFIX, fnv1a_64(&bytes)),
+        request: normalized,
+    })
+}
+
+/// Look up a cached response, marking it as most recently used
+pub fn lookup(state: &mut State, key: &CacheKey) -> Option<OpenAICompletionResponse> {
+    let store_id = state.store_id.clone()?;
+    let label = key.label.as_str();
+
+    let content_ref = match store::get_by_label(&store_id, label) {
+        Ok(Some(content_ref)) => content_ref,
+        Ok(None) => {
+            state.cache_index.retain(|entry| entry != label);
+            return None;
+        }
+        Err(e) => {
+            logging::info(&format!("Cache lookup failed for {}: {}", label, e));
+            return None;
+        }
+    };
+
+    let entry = store::get(&store_id, &content_ref)
+        .map_err(|e| logging::error(&format!("Failed to read cached response {}: {}", label, e)))
+        .ok()
+        .and_then(|bytes| serde_json::from_slice::<CacheEntry>(&bytes).ok());
+
+    match entry {
+        // Another request with the same hash; it is replaced when this one is stored
+        Some(entry) if entry.request != key.request => {
+            logging::info(&format!("Cache entry {} is for a different request, ignoring it", label));
+            None
+        }
+        Some(entry) => {
+            touch(state, label);
+            logging::info(&format!("Cache hit for {}", label));
+            Some(entry.response)
+        }
+        None => {
+            logging::info(&format!("Discarding unreadable cache entry {}", label));
+            remove(state, &store_id, label);
+            None
+        }
+    }
+}
+
+/// Store a response and evict the least recently used entries beyond `max_cache_size`
+pub fn insert(state: &mut State, key: &CacheKey, response: &OpenAICompletionResponse) {
+    let label = key.label.as_str();
+    let store_id = match state.store_id.clone() {
+        Some(store_id) => store_id,
+        None => return,
+    };
+    let max_entries = state.config.max_cache_size.unwrap_or(0);
+    if max_entries == 0 {
+        return;
+    }
+
+    let entry = CacheEntry {
+        request: key.request.clone(),
+        response: response.clone(),
+    };
+    let bytes = match serde_json::to_vec(&entry) {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            logging::error(&format!("Failed to serialize response for cache: {}", e));
+            return;
+        }
+    };
+
+    // Drop any stale content so the label points at exactly one response
+    let _ = store::remove_label(&store_id, label);
+    if let Err(e) = store::store_at_label(&store_id, label, &bytes) {
+        logging::error(&format!("Failed to cache response under {}: {}", label, e));
+        return;
+    }
+    touch(state, label);
+
+    while state.cache_index.len() > max_entries {
+        let evicted = state.cache_index.remove(0);
+        logging::debug(&format!("Evicting cache entry {}", evicted));
+        if let Err(e) = store::remove_label(&store_id, &evicted) {
+            logging::error(&format!("Failed to evict cache entry {}: {}", evicted, e));
+        }
+    }
+
+    logging::debug(&format!(
+        "Cached response under {} ({}/{} entries)",
+        label,
+        state.cache_index.len(),
+        max_entries
+    ));
+}
+
+fn touch(state: &mut State, label: &str) {
+    state.cache_index.retain(|entry| entry != label);
+    state.cache_index.push(label.to_string());
+}
+
+fn remove(state: &mut State, store_id: &str, label: &str) {
+    state.cache_index.retain(|entry| entry != label);
+    if let Err(e) = store::remove_label(store_id, label) {
+        logging::error(&format!("Failed to remove cache entry {}: {}", label, e));
+    }
+}
@@ -0,0 +1,33 @@
+use crate::bindings::theater::simple::timing;
+use crate::types::admin::{AdminRequest, AdminResponse, UpstreamStatus};
+use crate::types::state::State;
+
+/// Answer a proxy-specific request from actor state
+pub fn handle_admin_request(state: &mut State, request: AdminRequest) -> AdminResponse {
+    match request {
+        AdminRequest::CircuitStatus => {
+            let now = timing::now();
+            let config = &state.config.circuit_breaker;
+            let statuses = state
+                .config
+                .upstreams()
+                .into_iter()
+                .map(|upstream| {
+                    let breaker = state
+                        .circuit_breakers
+                        .get(&upstream.name)
+                        .cloned()
+                        .unwrap_or_default();
+                    UpstreamStatus {
+                        retry_in_ms: breaker.retry_in_ms(config, now),
+                        name: upstream.name,
+                        provider: upstream.provider,
+                        breaker,
+                    }
+                })
+                .collect();
+            AdminResponse::CircuitStatus(statuses)
+        }
+        AdminRequest::Usage => AdminResponse::Usage(Box::new(state.usage.clone())),
+    }
+}
@@ -0,0 +1,352 @@
+use crate::bindings::colinrozzi::genai_types::types::{
+    CompletionRequest, CompletionResponse, ProxyRequest, ProxyResponse,
+};
+use crate::bindings::theater::simple::message_server_host::send_on_channel;
+use crate::bindings::theater::simple::types::ChannelAccept;
+use crate::cache;
+use crate::api::ProviderClient;
+use crate::handlers::message::{
+    check_limits, complete_with_failover, create_client, log_warnings, prepare_request, record_usage,
+};
+use crate::logging;
+use crate::types::conversion::ToolCallWarning;
+use crate::types::response::{to_vec_with_warnings, OpenAIError};
+use crate::types::state::State;
+use crate::types::stream::{completion_deltas, StreamEvent};
+use crate::types::tool_names::ToolNames;
+use crate::types::tool_schema::remove_strict_nulls;
+use crate::types::OpenAICompletionRequest;
+use serde::Deserialize;
+
+/// Streaming happens over message-server channels:
+///
+/// 1. The caller opens a channel with a `GenerateCompletion` request as the
+///    initial message. The request is validated and parked in the state.
+/// 2. The caller sends any message on the channel to start the stream. A
+///    `GenerateCompletion` request sent on an open channel is streamed directly.
+/// 3. The actor replies with `StreamEvent` deltas followed by a final
+///    `StreamEvent::Completion` (or `StreamEvent::Error`).
+///
+/// Channel IDs don't say who the caller is, so requests may be wrapped as
+/// `{"request_id": "planner:7", "GenerateCompletion": {...}}` to be counted
+/// towards the caller that request ID names, as on the request path.
+pub fn handle_channel_open(
+    state_bytes: Vec<u8>,
+    channel_id: String,
+    data: Vec<u8>,
+) -> Result<(Option<Vec<u8>>, (ChannelAccept,)), String> {
+    let mut state: State = match serde_json::from_slice(&state_bytes) {
+        Ok(s) => s,
+        Err(e) => {
+            logging::error(&format!("Error parsing state: {}", e));
+            return Err(format!("Failed to parse state: {}", e));
+        }
+    };
+    logging::configure(&state.config.logging);
+
+    // An empty initial message opens the channel for later requests
+    if data.is_empty() {
+        logging::info(&format!("Channel {} opened without a request", channel_id));
+        return Ok((Some(state_bytes), (accept(),)));
+    }
+
+    let (request_id, request) = match parse_channel_request(&data) {
+        Ok(ChannelRequest { request_id, request: ProxyRequest::GenerateCompletion(request) }) => (request_id, request),
+        Ok(ChannelRequest { request: ProxyRequest::ListModels, .. }) => {
+            let error =
+                OpenAIError::InvalidRequest("Only GenerateCompletion requests can be streamed".to_string());
+            return Ok((Some(state_bytes), (reject(&state, &error),)));
+        }
+        Err(e) => {
+            logging::error(&format!("Error parsing channel request: {}", e));
+            let error = OpenAIError::InvalidRequest(format!("Invalid request format: {}", e));
+            return Ok((Some(state_bytes), (reject(&state, &error),)));
+        }
+    };
+
+    // Validate up front so a bad request is rejected before the stream starts
+    let caller = channel_caller(&state, &channel_id, request_id.as_deref());
+    let resolved = check_limits(&state, &caller)
+        .and_then(|_| create_client(&state))
+        .and_then(|client| prepare_request(&mut state, &client, request.clone()));
+    let request = match resolved {
+        Ok((model, _)) => CompletionRequest { model, ..request },
+        Err(e) => {
+            let accept = reject(&state, &e);
+            let state_bytes = serde_json::to_vec(&state)
+                .map_err(|e| format!("Failed to serialize state: {}", e))?;
+            return Ok((Some(state_bytes), (accept,)));
+        }
+    };
+
+    logging::info(&format!(
+        "Channel {} opened for streaming with model: {}",
+        channel_id, request.model
+    ));
+    state.channel_callers.insert(channel_id.clone(), caller);
+    state.pending_streams.insert(channel_id, request);
+
+    let state_bytes =
+        serde_json::to_vec(&state).map_err(|e| format!("Failed to serialize state: {}", e))?;
+    Ok((Some(state_bytes), (accept(),)))
+}
+
+pub fn handle_channel_message(
+    state_bytes: Vec<u8>,
+    channel_id: String,
+    data: Vec<u8>,
+) -> Result<(Option<Vec<u8>>,), String> {
+    let mut state: State = match serde_json::from_slice(&state_bytes) {
+        Ok(s) => s,
+        Err(e) => {
+            logging::error(&format!("Error parsing state: {}", e));
+            return Err(format!("Failed to parse state: {}", e));
+        }
+    };
+    logging::configure(&state.config.logging);
+
+    // A request on the channel takes precedence over the one parked at open
+    let mut request_id = None;
+    let request = match parse_channel_request(&data) {
+        Ok(ChannelRequest { request_id: id, request: ProxyRequest::GenerateCompletion(request) }) => {
+            state.pending_streams.remove(&channel_id);
+            request_id = id;
+            Ok(request)
+        }
+        _ => state.pending_streams.remove(&channel_id).ok_or_else(|| {
+            logging::error(&format!("No pending completion for channel {}", channel_id));
+            OpenAIError::InvalidRequest("No completion request pending on this channel".to_string())
+        }),
+    };
+
+    let caller = channel_caller(&state, &channel_id, request_id.as_deref());
+    state.channel_callers.insert(channel_id.clone(), caller.clone());
+    let prepared = request.and_then(|request| {
+        check_limits(&state, &caller)?;
+        let client = create_client(&state)?;
+        let (model, openai_request) = prepare_request(&mut state, &client, request)?;
+        Ok((client, model, openai_request))
+    });
+
+    match prepared {
+        Ok((client, model, openai_request)) => {
+            stream_completion(&mut state, &channel_id, &caller, &client, model, openai_request)
+        }
+        Err(e) => send_event(
+            &channel_id,
+            &StreamEvent::Error(e.render(state.config.error_format, None)),
+        ),
+    }
+
+    let state_bytes =
+        serde_json::to_vec(&state).map_err(|e| format!("Failed to serialize state: {}", e))?;
+    Ok((Some(state_bytes),))
+}
+
+pub fn handle_channel_close(
+    state_bytes: Vec<u8>,
+    channel_id: String,
+) -> Result<(Option<Vec<u8>>,), String> {
+    let mut state: State = match serde_json::from_slice(&state_bytes) {
+        Ok(s) => s,
+        Err(e) => {
+            logging::error(&format!("Error parsing state: {}", e));
+            return Err(format!("Failed to parse state: {}", e));
+        }
+    };
+    logging::configure(&state.config.logging);
+
+    state.channel_callers.remove(&channel_id);
+    if state.pending_streams.remove(&channel_id).is_some() {
+        logging::info(&format!("Dropped pending completion for channel {}", channel_id));
+    }
+
+    let state_bytes =
+        serde_json::to_vec(&state).map_err(|e| format!("Failed to serialize state: {}", e))?;
+    Ok((Some(state_bytes),))
+}
+
+/// A request sent on a channel, optionally with the request ID naming its caller
+#[derive(Deserialize)]
+struct ChannelRequest {
+    #[serde(default)]
+    request_id: Option<String>,
+    #[serde(flatten)]
+    request: ProxyRequest,
+}
+
+fn parse_channel_request(data: &[u8]) -> serde_json::Result<ChannelRequest> {
+    serde_json::from_slice::<ProxyRequest>(data)
+        .map(|request| ChannelRequest { request_id: None, request })
+        .or_else(|e| serde_json::from_slice::<ChannelRequest>(data).map_err(|_| e))
+}
+
+/// The caller a channel's usage counts towards
+///
+/// A request's own `request_id` wins, then the caller recorded when the
+/// channel was first used, then the channel ID itself.
+fn channel_caller(state: &State, channel_id: &str, request_id: Option<&str>) -> String {
+    let usage = &state.config.usage;
+    match (request_id, state.channel_callers.get(channel_id)) {
+        (Some(request_id), _) => usage.caller(request_id),
+        (None, Some(caller)) => caller.clone(),
+        (None, None) => usage.caller(channel_id),
+    }
+}
+
+/// Run a streaming completion and forward every event on the channel
+fn stream_completion(
+    state: &mut State,
+    channel_id: &str,
+    caller: &str,
+    client: &ProviderClient,
+    model: String,
+    openai_request: OpenAICompletionRequest,
+) {
+    logging::info(&format!(
+        "Streaming completion with model {} on channel {}",
+        model, channel_id
+    ));
+
+    let cache_key = cache::cache_key(state, client, &openai_request);
+    let cached = cache_key
+        .as_ref()
+        .and_then(|key| cache::lookup(state, key));
+
+    let retry_config = state.config.retry_config.clone();
+    let tool_validation = state.config.tool_validation.clone();
+    let result = match cached {
+        Some(completion) => {
+            for event in completion_deltas(&completion) {
+                send_event(channel_id, &original_tool_name(event, &openai_request.tool_names));
+            }
+            Ok(completion)
+        }
+        // Events are only emitted once an upstream has answered, so failing over
+        // never sends a caller deltas from two upstreams. Deltas can't be taken
+        // back, so tool calls are checked without retrying.
+        None => complete_with_failover(state, client, &openai_request, &mut |client, request| {
+            client
+                .stream_completion(request, &retry_config, &mut |event| {
+                    send_event(channel_id, &original_tool_name(event, &request.tool_names))
+                })
+                .and_then(|mut completion| {
+                    remove_strict_nulls(request, &mut completion);
+                    tool_validation.check(request, completion)
+                })
+        })
+        .inspect(|completion| {
+            record_usage(state, caller, completion);
+            if let Some(key) = &cache_key {
+                cache::insert(state, key, completion);
+            }
+        })
+        .inspect_err(|e| {
+            if let Some(completion) = e.billed_completion() {
+                record_usage(state, caller, completion);
+            }
+        }),
+    };
+
+    let mut warnings = Vec::new();
+    let final_event = match result {
+        Ok(completion) => {
+            warnings = completion.tool_call_warnings(&openai_request.tool_names);
+            warnings.extend(tool_validation.warnings(&openai_request, &completion));
+            log_warnings(&warnings);
+            StreamEvent::Completion(CompletionResponse {
+                model,
+                ..completion.into_completion(&openai_request.tool_names)
+            })
+        }
+        Err(e) => {
+            logging::error(&format!("Error streaming completion: {}", e));
+            StreamEvent::Error(e.render(state.config.error_format, Some("Failed to generate completion")))
+        }
+    };
+    send_event_with_warnings(channel_id, &final_event, &warnings);
+}
+
+/// Report a tool call delta under the caller's name for the tool
+fn original_tool_name(event: StreamEvent, tool_names: &ToolNames) -> StreamEvent {
+    match event {
+        StreamEvent::ToolCallDelta { index, id, name, arguments } => StreamEvent::ToolCallDelta {
+            index,
+            id,
+            name: name.map(|name| tool_names.original_name(&name)),
+            arguments,
+        },
+        event => event,
+    }
+}
+
+fn send_event(channel_id: &str, event: &StreamEvent) {
+    send_event_with_warnings(channel_id, event, &[]);
+}
+
+fn send_event_with_warnings(channel_id: &str, event: &StreamEvent, warnings: &[ToolCallWarning]) {
+    let bytes = match to_vec_with_warnings(event, warnings) {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            logging::error(&format!("Error serializing stream event: {}", e));
+            return;
+        }
+    };
+
+    if let Err(e) = send_on_channel(channel_id, &bytes) {
+        logging::error(&format!("Error sending on channel {}: {}", channel_id, e));
+    }
+}
+
+fn accept() -> ChannelAccept {
+    ChannelAccept {
+        accepted: true,
+        message: None,
+    }
+}
+
+fn reject(state: &State, error: &OpenAIError) -> ChannelAccept {
+    logging::error(&format!("Rejecting channel: {}", error));
+    ChannelAccept {
+        accepted: false,
+        message: serde_json::to_vec(&ProxyResponse::Error(error.render(state.config.error_format, None))).ok(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_channel_caller() {
+        let mut state = State::new("proxy".to_string(), "MOONSHOT_API_KEY".to_string(), "", None, None);
+        let completion = serde_json::json!({
+            "model": "kimi-k2-0711-preview",
+            "messages": [],
+            "max_tokens": 100,
+            "temperature": null,
+            "system": null,
+            "tools": null,
+            "tool_choice": null,
+            "disable_parallel_tool_use": null,
+        });
+
+        // A channel opened with a request that names its caller
+        let opened = serde_json::json!({ "request_id": "planner:7", "GenerateCompletion": completion });
+        let request = parse_channel_request(opened.to_string().as_bytes()).unwrap();
+        assert!(matches!(request.request, ProxyRequest::GenerateCompletion(_)));
+        let caller = channel_caller(&state, "c0ffee", request.request_id.as_deref());
+        assert_eq!(caller, "planner");
+
+        // Later requests on the channel keep that caller
+        state.channel_callers.insert("c0ffee".to_string(), caller);
+        let plain = serde_json::json!({ "GenerateCompletion": completion });
+        let request = parse_channel_request(plain.to_string().as_bytes()).unwrap();
+        assert_eq!(request.request_id, None);
+        assert_eq!(channel_caller(&state, "c0ffee", None), "planner");
+
+        // Channels that never name a caller are anonymous
+        assert_eq!(channel_caller(&state, "d00d", None), "anonymous");
+        assert!(parse_channel_request(b"\"ListModels\"").is_ok());
+    }
+}
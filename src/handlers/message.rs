@@ -1,40 +1,419 @@
-use crate::api::OpenAIClient;
+use crate::api::{provider_for, ProviderClient};
+use crate::cache;
+use crate::handlers::admin::handle_admin_request;
+use crate::logging;
 use crate::types::OpenAICompletionRequest;
-use crate::types::response::OpenAIModelInfo;
-use crate::bindings::colinrozzi::genai_types::types::{ProxyRequest, ProxyResponse};
-use crate::bindings::theater::simple::runtime::log;
-use crate::types::state::State;
+use crate::types::response::{to_vec_with_warnings, OpenAICompletionResponse, OpenAIError, OpenAIModelInfo};
+use crate::types::conversion::{ToolCallWarning, ToolCallWarningKind};
+use crate::bindings::colinrozzi::genai_types::types::{
+    CompletionRequest, CompletionResponse, ProxyRequest, ProxyResponse,
+};
+use crate::bindings::theater::simple::timing;
+use crate::types::model_policy::ModelValidationMode;
+use crate::types::pricing::completion_cost;
+use crate::types::admin::AdminRequest;
+use crate::types::circuit_breaker::CircuitState;
+use crate::types::state::{CatalogSource, ModelCatalog, RetryConfig, State, UpstreamConfig};
+use crate::types::tool_schema::{configure_tools, remove_strict_nulls};
+use crate::types::tool_validation::{
+    find_problems, retry_request, ToolCallProblem, ToolValidationConfig, ToolValidationMode,
+};
 //use genai_types::{ProxyRequest, ProxyResponse};
 
+/// Create a client for the primary upstream
+///
+/// The API key is read from the environment on every call so it never lands in state.
+pub fn create_client(state: &State) -> Result<ProviderClient, OpenAIError> {
+    upstream_client(state, &state.config.primary_upstream())
+}
+
+/// Create a client for an upstream's provider, base URL and key, with the configured timeout
+pub fn upstream_client(state: &State, upstream: &UpstreamConfig) -> Result<ProviderClient, OpenAIError> {
+    let api_key = state.api_key_from(upstream.api_key_env())?;
+    let client = ProviderClient::new(provider_for(upstream), api_key);
+    let client = match &upstream.base_url {
+        Some(base_url) => client.with_base_url(base_url.clone()),
+        None => client,
+    };
+    Ok(client.with_timeout(state.config.timeout_ms))
+}
+
+/// Run a completion against each upstream in turn until one succeeds
+///
+/// `primary` is the client for the primary upstream. Upstreams whose circuit
+/// breaker is open are skipped without a request. Errors another upstream
+/// could avoid move on to the next fallback; the rest are returned straight
+/// away. When fallbacks are configured, the response ID is prefixed with the
+/// name of the upstream that served it.
+pub fn complete_with_failover(
+    state: &mut State,
+    primary: &ProviderClient,
+    request: &OpenAICompletionRequest,
+    complete: &mut dyn FnMut(&ProviderClient, &OpenAICompletionRequest) -> Result<OpenAICompletionResponse, OpenAIError>,
+) -> Result<OpenAICompletionResponse, OpenAIError> {
+    let upstreams = state.config.upstreams();
+    let breaker_config = state.config.circuit_breaker.clone();
+    let mut last_error = None;
+
+    for (index, upstream) in upstreams.iter().enumerate() {
+        let breaker = state.circuit_breakers.entry(upstream.name.clone()).or_default();
+        let now = timing::now();
+        if !breaker.allow_request(&breaker_config, now) {
+            let retry_in_ms = breaker.retry_in_ms(&breaker_config, now).unwrap_or_default();
+            logging::info(&format!(
+                "Skipping upstream {}: circuit open for another {} ms",
+                upstream.name, retry_in_ms
+            ));
+            last_error = Some(OpenAIError::CircuitOpen {
+                upstream: upstream.name.clone(),
+                retry_in_ms,
+            });
+            continue;
+        }
+
+        let fallback_client;
+        let client = if index == 0 {
+            primary
+        } else {
+            match upstream_client(state, upstream) {
+                Ok(client) => {
+                    fallback_client = client;
+                    &fallback_client
+                }
+                Err(e) => {
+                    logging::error(&format!("Skipping upstream {}: {}", upstream.name, e));
+                    last_error = Some(e);
+                    continue;
+                }
+            }
+        };
+
+        let request = OpenAICompletionRequest {
+            model: upstream.model_for(&request.model),
+            ..request.clone()
+        };
+
+        let result = complete(client, &request);
+        let breaker = state.circuit_breakers.entry(upstream.name.clone()).or_default();
+        match result {
+            Ok(mut completion) => {
+                breaker.record_success();
+                logging::info(&format!(
+                    "Completion served by upstream {} ({})",
+                    upstream.name,
+                    client.provider().name()
+                ));
+                if !state.config.fallbacks.is_empty() {
+                    completion.id = format!("{}:{}", upstream.name, completion.id);
+                }
+                return Ok(completion);
+            }
+            Err(e) if e.should_fail_over() => {
+                breaker.record_failure(&breaker_config, timing::now(), &e.to_string());
+                if breaker.state == CircuitState::Open {
+                    logging::error(&format!(
+                        "Circuit opened for upstream {} after {} consecutive failures",
+                        upstream.name, breaker.consecutive_failures
+                    ));
+                }
+                if index + 1 == upstreams.len() {
+                    return Err(e);
+                }
+                logging::error(&format!(
+                    "Upstream {} failed, failing over: {}",
+                    upstream.name, e
+                ));
+                last_error = Some(e);
+            }
+            Err(e) => {
+                // The upstream answered, so it is healthy even though the request failed
+                breaker.record_success();
+                return Err(e);
+            }
+        }
+    }
+
+    Err(last_error.unwrap_or_else(|| OpenAIError::HttpError("No upstream available".to_string())))
+}
+
+/// Generate a completion and check its tool calls as `tool_validation` says
+///
+/// In `retry` mode the model is shown its invalid tool calls and asked again,
+/// up to `max_retries` times. The returned usage covers every attempt, and so
+/// does the usage of the completion an `InvalidToolCall` error carries; if a
+/// retry fails, the previous attempt's error is returned so that it is counted.
+pub fn generate_validated_completion(
+    client: &ProviderClient,
+    request: &OpenAICompletionRequest,
+    retry_config: &RetryConfig,
+    validation: &ToolValidationConfig,
+) -> Result<OpenAICompletionResponse, OpenAIError> {
+    let mut request = request.clone();
+    let mut rejected: Option<(OpenAICompletionResponse, Vec<ToolCallProblem>)> = None;
+    let mut retries = 0;
+
+    loop {
+        let mut completion = match client.generate_completion(&request, retry_config) {
+            Ok(completion) => completion,
+            Err(e) => match rejected {
+                None => return Err(e),
+                Some((previous, problems)) => {
+                    logging::error(&format!("Tool call retry failed: {}", e));
+                    return validation.resolve(previous, problems);
+                }
+            },
+        };
+        if let Some((previous, _)) = &rejected {
+            completion.usage.add(&previous.usage);
+        }
+        remove_strict_nulls(&request, &mut completion);
+        if validation.mode == ToolValidationMode::Off {
+            return Ok(completion);
+        }
+
+        let problems = find_problems(&request, &completion);
+        if problems.is_empty() {
+            return Ok(completion);
+        }
+        for problem in &problems {
+            logging::error(&format!("Invalid tool call {}", problem));
+        }
+
+        if validation.mode != ToolValidationMode::Retry || retries == validation.max_retries {
+            return validation.resolve(completion, problems);
+        }
+        retries += 1;
+        logging::info(&format!(
+            "Asking the model to correct {} tool calls (retry {} of {})",
+            problems.len(),
+            retries,
+            validation.max_retries
+        ));
+        request = retry_request(&request, &completion, &problems);
+        rejected = Some((completion, problems));
+    }
+}
+
+/// Reject a request if the caller has used up one of its quotas or the budget is spent
+pub fn check_limits(state: &State, caller: &str) -> Result<(), OpenAIError> {
+    let now = timing::now();
+    state.usage.check_quota(&state.config.usage, caller, now)?;
+    match &state.config.pricing.budget {
+        Some(budget) => state.usage.check_budget(budget, now),
+        None => Ok(()),
+    }
+}
+
+/// Count a completion served by an upstream, and its estimated cost, towards its model and caller
+pub fn record_usage(state: &mut State, caller: &str, completion: &OpenAICompletionResponse) {
+    let cost = match state.config.pricing.price_for(&completion.model) {
+        Some(pricing) => {
+            let cost = completion_cost(&pricing, &completion.usage);
+            logging::info(&format!(
+                "Completion {} cost ${:.6} ({} input, {} output tokens)",
+                completion.id, cost, completion.usage.prompt_tokens, completion.usage.completion_tokens
+            ));
+            cost
+        }
+        None => {
+            logging::debug(&format!("No price for model {}, cost not counted", completion.model));
+            0.0
+        }
+    };
+    state
+        .usage
+        .record(caller, &completion.model, &completion.usage, cost, timing::now());
+}
+
+/// Log the warnings sent back with a completion
+pub fn log_warnings(warnings: &[ToolCallWarning]) {
+    for warning in warnings {
+        match warning.kind {
+            ToolCallWarningKind::ArgumentsRepaired => logging::info(&warning.to_string()),
+            _ => logging::error(&warning.to_string()),
+        }
+    }
+}
+
+/// How long the static fallback list is used before the provider is asked again
+const FALLBACK_CATALOG_TTL_MS: u64 = 60_000;
+
+/// Get the models the provider serves, refreshing the cached catalog when it expires
+///
+/// Falls back to the provider's built-in model list when the `/models` endpoint is unavailable.
+pub fn model_catalog(state: &mut State, client: &ProviderClient) -> Vec<OpenAIModelInfo> {
+    let now = timing::now();
+
+    if let Some(catalog) = &state.model_catalog {
+        if catalog.is_fresh(now) {
+            return catalog.models.clone();
+        }
+    }
+
+    let catalog = match client.list_models() {
+        Ok(models) => ModelCatalog {
+            models,
+            source: CatalogSource::Remote,
+            expires_at: now + state.config.model_cache_ttl_ms,
+        },
+        Err(e) => {
+            logging::error(&format!("Failed to fetch models, using static list: {}", e));
+            ModelCatalog {
+                models: client.provider().static_models(),
+                source: CatalogSource::Static,
+                expires_at: now + FALLBACK_CATALOG_TTL_MS,
+            }
+        }
+    };
+
+    logging::debug(&format!(
+        "Model catalog refreshed with {} models ({:?})",
+        catalog.models.len(),
+        catalog.source
+    ));
+
+    let models = catalog.models.clone();
+    state.model_catalog = Some(catalog);
+    models
+}
+
+/// Resolve a requested model through the configured policy
+///
+/// Returns the model ID to send to the provider.
+pub fn resolve_model(state: &mut State, client: &ProviderClient, requested: &str) -> Result<String, OpenAIError> {
+    let policy = state.config.model_policy.clone();
+
+    let named = state.config.model_or_default(requested);
+    if named != requested {
+        logging::debug(&format!("Using default model '{}' for requested model '{}'", named, requested));
+    }
+
+    let model = policy.resolve_alias(&named);
+    if model != named {
+        logging::debug(&format!("Resolved model alias '{}' to '{}'", named, model));
+    }
+
+    if let Err(error_msg) = policy.check_access(&model) {
+        logging::error(&format!("Model policy rejected request: {}", error_msg));
+        return Err(OpenAIError::InvalidRequest(error_msg));
+    }
+
+    match policy.mode {
+        ModelValidationMode::Strict => {
+            let models = model_catalog(state, client);
+            validate_model(&models, &model)?;
+        }
+        ModelValidationMode::Warn => {
+            let models = model_catalog(state, client);
+            if let Err(e) = validate_model(&models, &model) {
+                logging::info(&format!("Forwarding unknown model anyway: {}", e));
+            }
+        }
+        ModelValidationMode::Passthrough => {}
+    }
+
+    Ok(model)
+}
+
+/// Resolve the model and translate a request for the provider
+///
+/// Returns the resolved model ID alongside the provider request.
+pub fn prepare_request(
+    state: &mut State,
+    client: &ProviderClient,
+    request: CompletionRequest,
+) -> Result<(String, OpenAICompletionRequest), OpenAIError> {
+    let model = resolve_model(state, client, &request.model)?;
+    let mut openai_request =
+        OpenAICompletionRequest::try_from(CompletionRequest { model: model.clone(), ..request })
+            .map_err(OpenAIError::InvalidRequest)?;
+    if let Some(tools) = &mut openai_request.tools {
+        configure_tools(tools, &state.config.tool_schema);
+    }
+
+    // Providers that send placeholders instead of images work with any model
+    if openai_request.has_images()
+        && client.provider().sends_images()
+        && state.config.model_policy.mode != ModelValidationMode::Passthrough
+    {
+        let models = model_catalog(state, client);
+        if !OpenAIModelInfo::supports_vision(&models, &model) {
+            let vision_models: Vec<&str> = models
+                .iter()
+                .filter(|m| m.supports_vision)
+                .map(|m| m.id.as_str())
+                .collect();
+            let error_msg = if vision_models.is_empty() {
+                format!("Model '{}' does not accept image input", model)
+            } else {
+                format!(
+                    "Model '{}' does not accept image input. Vision models: {}",
+                    model,
+                    vision_models.join(", ")
+                )
+            };
+            logging::error(&error_msg);
+            return Err(OpenAIError::InvalidRequest(error_msg));
+        }
+    }
+
+    Ok((model, openai_request))
+}
+
+/// Check that a model is supported, suggesting similar names if not
+pub fn validate_model(models: &[OpenAIModelInfo], model: &str) -> Result<(), OpenAIError> {
+    if OpenAIModelInfo::is_model_supported(models, model) {
+        return Ok(());
+    }
+
+    let error = OpenAIError::UnsupportedModel {
+        requested: model.to_string(),
+        suggestions: OpenAIModelInfo::get_model_suggestions(models, model),
+    };
+    logging::error(&format!("Model validation failed: {}", error));
+    Err(error)
+}
+
 pub fn handle_request(
+    request_id: String,
     data: Vec<u8>,
     state_bytes: Vec<u8>,
 ) -> Result<(Option<Vec<u8>>, (Option<Vec<u8>>,)), String> {
-    log("Handling request in moonshot-proxy actor");
+    logging::debug("Handling request in moonshot-proxy actor");
 
     // Parse the state
-    let state: State = match serde_json::from_slice(&state_bytes) {
+    let mut state: State = match serde_json::from_slice(&state_bytes) {
         Ok(s) => s,
         Err(e) => {
-            log(&format!("Error parsing state: {}", e));
+            logging::error(&format!("Error parsing state: {}", e));
             return Err(format!("Failed to parse state: {}", e));
         }
     };
+    logging::configure(&state.config.logging);
 
     // Debug log the incoming request
-    log(&format!(
-        "Received request data: {}",
-        String::from_utf8_lossy(&data)
-    ));
+    logging::debug(&format!("Received request data: {}", logging::body(&data)));
+
+    // Proxy-specific requests are answered from state, without a provider client
+    if let Ok(admin_request) = serde_json::from_slice::<AdminRequest>(&data) {
+        logging::info(&format!("Handling admin request: {:?}", admin_request));
+        let response = handle_admin_request(&mut state, admin_request);
+        let response_bytes = serde_json::to_vec(&response)
+            .map_err(|e| format!("Failed to serialize response: {}", e))?;
+        let state_bytes = serde_json::to_vec(&state)
+            .map_err(|e| format!("Failed to serialize state: {}", e))?;
+        return Ok((Some(state_bytes), (Some(response_bytes),)));
+    }
 
     // Parse the request using the shared ProxyRequest type
     let request: ProxyRequest = match serde_json::from_slice(&data) {
         Ok(req) => req,
         Err(e) => {
-            log(&format!("Error parsing request: {}", e));
+            logging::error(&format!("Error parsing request: {}", e));
 
             // Try to respond with a properly formatted error
-            let error_response = ProxyResponse::Error(format!("Invalid request format: {}", e));
+            let error = OpenAIError::InvalidRequest(format!("Invalid request format: {}", e));
+            let error_response = ProxyResponse::Error(error.render(state.config.error_format, None));
 
             match serde_json::to_vec(&error_response) {
                 Ok(bytes) => return Ok((Some(state_bytes), (Some(bytes),))),
@@ -44,77 +423,107 @@ pub fn handle_request(
     };
 
     // Create OpenAI client with configurable base URL
-    let client = match &state.config.base_url {
-        Some(base_url) => OpenAIClient::new_with_base_url(state.api_key.clone(), base_url.clone()),
-        None => OpenAIClient::new(state.api_key.clone()),
+    let client = match create_client(&state) {
+        Ok(client) => client,
+        Err(e) => {
+            logging::error(&format!("Error creating client: {}", e));
+            let response_bytes =
+                serde_json::to_vec(&ProxyResponse::Error(e.render(state.config.error_format, None)))
+                .map_err(|e| format!("Failed to serialize response: {}", e))?;
+            return Ok((Some(state_bytes), (Some(response_bytes),)));
+        }
     };
 
     // Process based on operation type
+    let mut warnings = Vec::new();
     let response = match request {
         ProxyRequest::GenerateCompletion(request) => {
-            log(&format!(
+            logging::info(&format!(
                 "Generating completion with model: {}",
                 request.model
             ));
 
-            // Validate that the model is supported
-            if !OpenAIModelInfo::is_model_supported(&request.model) {
-                let suggestions = OpenAIModelInfo::get_model_suggestions(&request.model);
-                let error_msg = if !suggestions.is_empty() {
-                    format!(
-                        "Unsupported model '{}'. Did you mean one of: {}? Available models can be listed using the ListModels request.",
-                        request.model,
-                        suggestions.join(", ")
-                    )
-                } else {
-                    format!(
-                        "Unsupported model '{}'. Please check the available models using the ListModels request.",
-                        request.model
-                    )
-                };
-                
-                log(&format!("Model validation failed: {}", error_msg));
-                return Ok((Some(state_bytes), (Some(serde_json::to_vec(&ProxyResponse::Error(error_msg)).unwrap()),)));
-            }
+            // Check the caller's quota and the budget, then resolve aliases, validate the model
+            // against the policy and translate the request
+            let caller = state.config.usage.caller(&request_id);
+            let prepared = check_limits(&state, &caller)
+                .and_then(|_| prepare_request(&mut state, &client, request));
+            match prepared {
+                Err(e) => ProxyResponse::Error(e.render(state.config.error_format, None)),
+                Ok((model, openai_request)) => {
+                    let cache_key = cache::cache_key(&state, &client, &openai_request);
+                    let cached = cache_key
+                        .as_ref()
+                        .and_then(|key| cache::lookup(&mut state, key));
 
-            let openai_request: OpenAICompletionRequest = request.into();
-            match client.generate_completion(
-                &openai_request,
-                &state.config.retry_config,
-                &state.config.content_format,
-            ) {
-                Ok(completion) => ProxyResponse::Completion(completion.into()),
-                Err(e) => {
-                    log(&format!("Error generating completion: {}", e));
-                    ProxyResponse::Error(format!("Failed to generate completion: {}", e))
+                    let retry_config = state.config.retry_config.clone();
+                    let tool_validation = state.config.tool_validation.clone();
+                    let result = match cached {
+                        Some(completion) => Ok(completion),
+                        None => complete_with_failover(&mut state, &client, &openai_request, &mut |client, request| {
+                            generate_validated_completion(client, request, &retry_config, &tool_validation)
+                        })
+                        .inspect(|completion| {
+                            record_usage(&mut state, &caller, completion);
+                            if let Some(key) = &cache_key {
+                                cache::insert(&mut state, key, completion);
+                            }
+                        })
+                        .inspect_err(|e| {
+                            if let Some(completion) = e.billed_completion() {
+                                record_usage(&mut state, &caller, completion);
+                            }
+                        }),
+                    };
+
+                    match result {
+                        Ok(completion) => {
+                            warnings = completion.tool_call_warnings(&openai_request.tool_names);
+                            warnings.extend(tool_validation.warnings(&openai_request, &completion));
+                            log_warnings(&warnings);
+                            // Report the model the proxy chose, not the name the caller sent
+                            ProxyResponse::Completion(CompletionResponse {
+                                model,
+                                ..completion.into_completion(&openai_request.tool_names)
+                            })
+                        }
+                        Err(e) => {
+                            logging::error(&format!("Error generating completion: {}", e));
+                            ProxyResponse::Error(
+                                e.render(state.config.error_format, Some("Failed to generate completion")),
+                            )
+                        }
+                    }
                 }
             }
         }
 
         ProxyRequest::ListModels => {
-            log("Listing available models");
+            logging::info("Listing available models");
 
-            match client.list_models() {
-                Ok(models) => {
-                    ProxyResponse::ListModels(models.into_iter().map(|m| m.into()).collect())
-                }
-                Err(e) => {
-                    log(&format!("Error listing models: {}", e));
-                    ProxyResponse::Error(format!("Failed to list models: {}", e))
-                }
-            }
+            let mut models = model_catalog(&mut state, &client);
+            state.config.pricing.apply(&mut models);
+            ProxyResponse::ListModels(models.into_iter().map(|m| m.into()).collect())
         }
     };
 
     // Serialize the response
-    let response_bytes = match serde_json::to_vec(&response) {
+    let response_bytes = match to_vec_with_warnings(&response, &warnings) {
         Ok(bytes) => bytes,
         Err(e) => {
-            log(&format!("Error serializing response: {}", e));
+            logging::error(&format!("Error serializing response: {}", e));
             return Err(format!("Failed to serialize response: {}", e));
         }
     };
 
+    let state_bytes = match serde_json::to_vec(&state) {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            logging::error(&format!("Error serializing state: {}", e));
+            return Err(format!("Failed to serialize state: {}", e));
+        }
+    };
+
     // Return the updated state and response
     Ok((Some(state_bytes), (Some(response_bytes),)))
 }
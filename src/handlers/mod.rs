@@ -1,3 +1,3 @@
+pub mod admin;
+pub mod channel;
 pub mod message;
-
-
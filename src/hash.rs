@@ -0,0 +1,12 @@
+/// FNV-1a, 64-bit
+///
+/// Used where we need a hash that is stable across builds and runs (cache
+/// keys persisted in the store), which `std::hash::DefaultHasher` doesn't promise.
+pub fn fnv1a_64(bytes: &[u8]) -> u64 {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0000_0100_0000_01b3;
+
+    bytes.iter().fold(OFFSET_BASIS, |hash, byte| {
+        (hash ^ *byte as u64).wrapping_mul(PRIME)
+    })
+}
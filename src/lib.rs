@@ -1,16 +1,20 @@
 mod api;
 mod bindings;
+mod cache;
+mod date;
 mod handlers;
+mod hash;
+mod logging;
 pub mod types;
 
 // Tests removed - will be rewritten for new architecture
 
 use crate::bindings::exports::theater::simple::actor::Guest;
 use crate::bindings::exports::theater::simple::message_server_client::Guest as MessageServerClient;
-use crate::bindings::theater::simple::runtime::log;
-use crate::types::state::{Config, State};
+use crate::types::state::{Config, ProviderKind, State};
 
 use bindings::theater::simple::environment;
+use bindings::theater::simple::store;
 use bindings::theater::simple::types::ChannelAccept;
 use serde::{Deserialize, Serialize};
 
@@ -24,9 +28,9 @@ struct Component;
 
 impl Guest for Component {
     fn init(data: Option<Vec<u8>>, params: (String,)) -> Result<(Option<Vec<u8>>,), String> {
-        log("Initializing moonshot-proxy actor");
+        logging::info("Initializing moonshot-proxy actor");
         let (id,) = params;
-        log(&format!("Actor ID: {}", id));
+        logging::info(&format!("Actor ID: {}", id));
 
         // Parse initialization data
         let init_data: InitData = match data {
@@ -41,24 +45,29 @@ impl Guest for Component {
             }
         };
 
-        log("Init data parsed successfully");
+        if let Some(config) = &init_data.config {
+            logging::configure(&config.logging);
+        }
+        logging::debug("Init data parsed successfully");
 
         // Determine which environment variable to use for the API key
-        let api_key_env_name = init_data
-            .config
-            .as_ref()
-            .and_then(|config| config.api_key_env.as_ref())
-            .map(|s| s.as_str())
-            .unwrap_or("OPENAI_API_KEY");
+        let api_key_env_name = match &init_data.config {
+            Some(config) => config
+                .api_key_env
+                .as_deref()
+                .unwrap_or(config.provider.default_api_key_env()),
+            None => ProviderKind::default().default_api_key_env(),
+        };
 
-        log(&format!(
+        logging::debug(&format!(
             "Looking for API key in environment variable: {}",
             api_key_env_name
         ));
 
+        // Fail fast if the key is missing; only its fingerprint is kept in state
         let api_key = match environment::get_var(api_key_env_name) {
             Some(key) => {
-                log(&format!(
+                logging::info(&format!(
                     "API key found in environment variable: {}",
                     api_key_env_name
                 ));
@@ -72,15 +81,54 @@ impl Guest for Component {
             }
         };
 
+        // Fallback keys are read when a fallback is needed; a missing one only skips that upstream
+        if let Some(config) = &init_data.config {
+            for upstream in &config.fallbacks {
+                if environment::get_var(upstream.api_key_env()).is_none() {
+                    logging::info(&format!(
+                        "API key for fallback upstream {} not found in environment variable: {}",
+                        upstream.name,
+                        upstream.api_key_env()
+                    ));
+                }
+            }
+        }
+
+        // The response cache lives in the runtime store, so make sure we have one
+        let cache_enabled = init_data
+            .config
+            .as_ref()
+            .map(|config| config.cache.enabled)
+            .unwrap_or(false);
+        let store_id = match init_data.store_id {
+            Some(store_id) => Some(store_id),
+            None if cache_enabled => match store::new() {
+                Ok(store_id) => {
+                    logging::info(&format!("Created store {} for the response cache", store_id));
+                    Some(store_id)
+                }
+                Err(e) => {
+                    return Err(format!("Failed to create store for response cache: {}", e));
+                }
+            },
+            None => None,
+        };
+
         // Initialize state
-        let state = State::new(id, api_key, init_data.store_id, init_data.config);
+        let state = State::new(
+            id,
+            api_key_env_name.to_string(),
+            &api_key,
+            store_id,
+            init_data.config,
+        );
 
-        log("State initialized");
+        logging::debug("State initialized");
 
         // Serialize and return the state
         match serde_json::to_vec(&state) {
             Ok(state_bytes) => {
-                log("Actor initialized successfully");
+                logging::info("Actor initialized successfully");
                 Ok((Some(state_bytes),))
             }
             Err(e) => Err(format!("Failed to serialize state: {}", e)),
@@ -93,7 +141,7 @@ impl MessageServerClient for Component {
         state: Option<Vec<u8>>,
         _params: (Vec<u8>,),
     ) -> Result<(Option<Vec<u8>>,), String> {
-        log("Handling send message in moonshot-proxy");
+        logging::debug("Handling send message in moonshot-proxy");
 
         // Nothing to return for a send
         Ok((state,))
@@ -103,27 +151,22 @@ impl MessageServerClient for Component {
         state: Option<Vec<u8>>,
         params: (String, Vec<u8>),
     ) -> Result<(Option<Vec<u8>>, (Option<Vec<u8>>,)), String> {
-        log("Handling request message in moonshot-proxy");
+        logging::debug("Handling request message in moonshot-proxy");
         let (request_id, data) = params;
-        log(&format!("Request ID: {}", request_id));
+        logging::debug(&format!("Request ID: {}", request_id));
 
         // Use our message handler
-        handlers::message::handle_request(data, state.unwrap())
+        handlers::message::handle_request(request_id, data, state.unwrap())
     }
 
     fn handle_channel_open(
         state: Option<Vec<u8>>,
-        _params: (String, Vec<u8>),
+        params: (String, Vec<u8>),
     ) -> Result<(Option<Vec<u8>>, (ChannelAccept,)), String> {
-        log("Channel open request received");
-
-        Ok((
-            state,
-            (ChannelAccept {
-                accepted: true,
-                message: None,
-            },),
-        ))
+        logging::debug("Channel open request received");
+        let (channel_id, data) = params;
+
+        handlers::channel::handle_channel_open(state.unwrap(), channel_id, data)
     }
 
     fn handle_channel_close(
@@ -131,19 +174,19 @@ impl MessageServerClient for Component {
         params: (String,),
     ) -> Result<(Option<Vec<u8>>,), String> {
         let (channel_id,) = params;
-        log(&format!("Channel {} closed", channel_id));
+        logging::info(&format!("Channel {} closed", channel_id));
 
-        Ok((state,))
+        handlers::channel::handle_channel_close(state.unwrap(), channel_id)
     }
 
     fn handle_channel_message(
         state: Option<Vec<u8>>,
         params: (String, Vec<u8>),
     ) -> Result<(Option<Vec<u8>>,), String> {
-        let (channel_id, _message) = params;
-        log(&format!("Received message on channel {}", channel_id));
+        let (channel_id, message) = params;
+        logging::debug(&format!("Received message on channel {}", channel_id));
 
-        Ok((state,))
+        handlers::channel::handle_channel_message(state.unwrap(), channel_id, message)
     }
 }
 
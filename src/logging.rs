@@ -0,0 +1,156 @@
+// Leveled, redacting logging on top of the theater runtime log
+//
+// Every handler call configures the logger from the actor's `LoggingConfig`.
+// Below `trace`, request/response bodies are logged with message content and
+// credentials replaced by placeholders, so prompts and completions never end
+// up in theater logs in production.
+
+use crate::bindings::theater::simple::runtime::log;
+use crate::types::state::{LogLevel, LoggingConfig};
+use std::cell::RefCell;
+
+thread_local! {
+    static CONFIG: RefCell<LoggingConfig> = RefCell::new(LoggingConfig::default());
+}
+
+/// JSON keys whose string values carry prompt or completion content
+const CONTENT_KEYS: &[&str] = &[
+    "content",
+    "text",
+    "system",
+    "arguments",
+    "input",
+    "data",
+    "url",
+    "transcript",
+    "refusal",
+];
+
+/// Header names whose values are credentials
+const SECRET_HEADERS: &[&str] = &["authorization", "x-api-key", "api-key", "proxy-authorization"];
+
+/// Apply the logging configuration for the current handler call
+pub fn configure(config: &LoggingConfig) {
+    CONFIG.with(|current| *current.borrow_mut() = config.clone());
+}
+
+fn enabled(level: LogLevel) -> bool {
+    CONFIG.with(|config| level != LogLevel::Off && level <= config.borrow().level)
+}
+
+fn emit(level: LogLevel, prefix: &str, msg: &str) {
+    if enabled(level) {
+        log(&format!("[{}] {}", prefix, msg));
+    }
+}
+
+pub fn error(msg: &str) {
+    emit(LogLevel::Error, "error", msg);
+}
+
+pub fn info(msg: &str) {
+    emit(LogLevel::Info, "info", msg);
+}
+
+pub fn debug(msg: &str) {
+    emit(LogLevel::Debug, "debug", msg);
+}
+
+pub fn trace(msg: &str) {
+    emit(LogLevel::Trace, "trace", msg);
+}
+
+/// Render a JSON body for logging
+///
+/// Content fields are redacted unless the level is `trace`; the result is
+/// truncated to `max_body_chars` either way.
+pub fn body(bytes: &[u8]) -> String {
+    let rendered = if enabled(LogLevel::Trace) {
+        String::from_utf8_lossy(bytes).to_string()
+    } else {
+        match serde_json::from_slice::<serde_json::Value>(bytes) {
+            Ok(mut value) => {
+                redact_value(&mut value);
+                value.to_string()
+            }
+            Err(_) => format!("[redacted {} bytes]", bytes.len()),
+        }
+    };
+    truncate(rendered)
+}
+
+/// Render free-form content (a prompt, completion or tool result) for logging
+pub fn content(text: &str) -> String {
+    if enabled(LogLevel::Trace) {
+        truncate(text.to_string())
+    } else {
+        format!("[redacted {} chars]", text.chars().count())
+    }
+}
+
+/// Render headers for logging with credentials masked
+pub fn headers(headers: &[(String, String)]) -> String {
+    headers
+        .iter()
+        .map(|(name, value)| {
+            if SECRET_HEADERS.contains(&name.to_ascii_lowercase().as_str()) {
+                format!("{}: [redacted]", name)
+            } else {
+                format!("{}: {}", name, value)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn redact_value(value: &mut serde_json::Value) {
+    match value {
+        serde_json::Value::Object(map) => {
+            for (key, field) in map.iter_mut() {
+                match field {
+                    serde_json::Value::String(text) if CONTENT_KEYS.contains(&key.as_str()) => {
+                        *field = serde_json::Value::String(format!(
+                            "[redacted {} chars]",
+                            text.chars().count()
+                        ));
+                    }
+                    _ => redact_value(field),
+                }
+            }
+        }
+        serde_json::Value::Array(items) => items.iter_mut().for_each(redact_value),
+        _ => {}
+    }
+}
+
+fn truncate(text: String) -> String {
+    let max_chars = CONFIG.with(|config| config.borrow().max_body_chars);
+    if max_chars == 0 || text.chars().count() <= max_chars {
+        return text;
+    }
+
+    let truncated: String = text.chars().take(max_chars).collect();
+    format!("{}... [truncated, {} chars total]", truncated, text.chars().count())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_body_redaction() {
+        configure(&LoggingConfig::default());
+        let request = br#"{"model":"kimi-k2","messages":[{"role":"user","content":"secret prompt"}]}"#;
+
+        let logged = body(request);
+        assert!(!logged.contains("secret prompt"));
+        assert!(logged.contains("[redacted 13 chars]"));
+        assert!(logged.contains("kimi-k2"));
+
+        let logged = headers(&[("Authorization".to_string(), "Bearer sk-123".to_string())]);
+        assert_eq!(logged, "Authorization: [redacted]");
+
+        configure(&LoggingConfig { level: LogLevel::Trace, max_body_chars: 10 });
+        assert!(body(request).starts_with("{\"model\":\"... [truncated"));
+    }
+}
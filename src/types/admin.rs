@@ -0,0 +1,35 @@
+// Proxy-specific requests that aren't part of the genai-types interface
+//
+// `ProxyRequest` is defined by the shared WIT package, so operational requests
+// get their own enum. `handle_request` tries these first; their JSON names
+// never collide with a `ProxyRequest` variant.
+
+use crate::types::circuit_breaker::CircuitBreaker;
+use crate::types::state::ProviderKind;
+use crate::types::usage::UsageLedger;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub enum AdminRequest {
+    /// Report the circuit breaker of every configured upstream
+    CircuitStatus,
+    /// Report token and request counters per model and caller
+    Usage,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub enum AdminResponse {
+    CircuitStatus(Vec<UpstreamStatus>),
+    Usage(Box<UsageLedger>),
+}
+
+/// One upstream's circuit breaker, in failover order
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct UpstreamStatus {
+    pub name: String,
+    pub provider: ProviderKind,
+    #[serde(flatten)]
+    pub breaker: CircuitBreaker,
+    /// Time left before an open breaker lets a probe through
+    pub retry_in_ms: Option<u64>,
+}
@@ -0,0 +1,546 @@
+// Anthropic Messages API wire format
+//
+// The proxy converts genai requests to the OpenAI shape first, so the
+// Anthropic backend translates from `OpenAICompletionRequest` and back into
+// `OpenAICompletionResponse`. That keeps caching, streaming and the genai
+// conversion identical for every provider.
+
+use crate::types::api::{
+    OpenAICompletionRequest, OpenAIToolChoice, OpenAIToolChoiceMode, OpenAIUsage,
+};
+use crate::types::conversion::{
+    extract_text_from_content_items, OpenAIContent, OpenAIContentItem, OpenAIFunctionCall,
+    OpenAIImageUrl, OpenAIMessage, OpenAIToolCall,
+};
+use crate::types::response::{OpenAIChoice, OpenAICompletionResponse};
+use crate::types::stream::{
+    OpenAIFunctionCallDelta, OpenAIStreamChoice, OpenAIStreamChunk, OpenAIStreamDelta,
+    OpenAIToolCallDelta,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Version sent in the `anthropic-version` header
+pub const ANTHROPIC_VERSION: &str = "2023-06-01";
+
+/// The Messages API requires `max_tokens`; used when the request leaves it unset
+const DEFAULT_MAX_TOKENS: u32 = 4096;
+
+/// Build a `/v1/messages` request body
+pub fn anthropic_request_body(request: &OpenAICompletionRequest) -> serde_json::Value {
+    let mut system = Vec::new();
+    let mut messages: Vec<(String, Vec<serde_json::Value>)> = Vec::new();
+
+    for message in &request.messages {
+        let (role, blocks) = match message.role.as_str() {
+            "system" => {
+                if let Some(content) = &message.content {
+                    system.extend(text_parts(content));
+                }
+                continue;
+            }
+            "tool" => ("user", tool_result_blocks(message)),
+            "assistant" => ("assistant", assistant_blocks(message)),
+            _ => ("user", user_blocks(message)),
+        };
+
+        if blocks.is_empty() {
+            continue;
+        }
+
+        // Anthropic requires alternating roles, so merge consecutive turns
+        match messages.last_mut() {
+            Some((last_role, last_blocks)) if last_role == role => last_blocks.extend(blocks),
+            _ => messages.push((role.to_string(), blocks)),
+        }
+    }
+
+    let mut body = serde_json::Map::new();
+    body.insert("model".to_string(), serde_json::json!(request.model));
+    body.insert(
+        "max_tokens".to_string(),
+        serde_json::json!(request.max_tokens.unwrap_or(DEFAULT_MAX_TOKENS)),
+    );
+    body.insert(
+        "messages".to_string(),
+        messages
+            .into_iter()
+            .map(|(role, content)| serde_json::json!({ "role": role, "content": content }))
+            .collect(),
+    );
+
+    if !system.is_empty() {
+        body.insert("system".to_string(), serde_json::json!(system.join("\n\n")));
+    }
+    if let Some(temperature) = request.temperature {
+        body.insert("temperature".to_string(), serde_json::json!(temperature));
+    }
+    if let Some(top_p) = request.top_p {
+        body.insert("top_p".to_string(), serde_json::json!(top_p));
+    }
+    if let Some(stop) = &request.stop {
+        body.insert("stop_sequences".to_string(), serde_json::json!(stop));
+    }
+    if let Some(stream) = request.stream {
+        body.insert("stream".to_string(), serde_json::json!(stream));
+    }
+
+    if let Some(tools) = &request.tools {
+        let tools: Vec<serde_json::Value> = tools
+            .iter()
+            .map(|tool| {
+                let mut value = serde_json::json!({
+                    "name": tool.function.name,
+                    "input_schema": tool.function.parameters,
+                });
+                if let Some(description) = &tool.function.description {
+                    value["description"] = serde_json::json!(description);
+                }
+                value
+            })
+            .collect();
+        body.insert("tools".to_string(), serde_json::json!(tools));
+
+        let mut tool_choice = match &request.tool_choice {
+            Some(OpenAIToolChoice::Mode(OpenAIToolChoiceMode::Auto)) | None => {
+                serde_json::json!({ "type": "auto" })
+            }
+            Some(OpenAIToolChoice::Mode(OpenAIToolChoiceMode::Required)) => {
+                serde_json::json!({ "type": "any" })
+            }
+            Some(OpenAIToolChoice::Mode(OpenAIToolChoiceMode::None)) => {
+                serde_json::json!({ "type": "none" })
+            }
+            Some(OpenAIToolChoice::Function { function, .. }) => {
+                serde_json::json!({ "type": "tool", "name": function.name })
+            }
+        };
+        if request.parallel_tool_calls == Some(false) {
+            tool_choice["disable_parallel_tool_use"] = serde_json::json!(true);
+        }
+        if request.tool_choice.is_some() || request.parallel_tool_calls == Some(false) {
+            body.insert("tool_choice".to_string(), tool_choice);
+        }
+    }
+
+    serde_json::Value::Object(body)
+}
+
+fn text_parts(content: &OpenAIContent) -> Vec<String> {
+    content
+        .items()
+        .iter()
+        .filter_map(|item| match item {
+            OpenAIContentItem::Text { text } => Some(text.clone()),
+            _ => None,
+        })
+        .collect()
+}
+
+fn user_blocks(message: &OpenAIMessage) -> Vec<serde_json::Value> {
+    message
+        .content
+        .iter()
+        .flat_map(|content| content.items())
+        .filter_map(|item| match item {
+            OpenAIContentItem::Text { text } => Some(text_block(text)),
+            OpenAIContentItem::ImageUrl { image_url } => Some(image_block(image_url)),
+            _ => None,
+        })
+        .collect()
+}
+
+fn assistant_blocks(message: &OpenAIMessage) -> Vec<serde_json::Value> {
+    let mut blocks: Vec<serde_json::Value> = message
+        .content
+        .iter()
+        .flat_map(text_parts)
+        .filter(|text| !text.is_empty())
+        .map(|text| text_block(&text))
+        .collect();
+
+    for call in message.tool_calls.iter().flatten() {
+        blocks.push(serde_json::json!({
+            "type": "tool_use",
+            "id": call.id,
+            "name": call.function.name,
+            "input": call.function.decode_arguments().into_value().unwrap_or_else(|| serde_json::json!({})),
+        }));
+    }
+
+    blocks
+}
+
+fn tool_result_blocks(message: &OpenAIMessage) -> Vec<serde_json::Value> {
+    message
+        .content
+        .iter()
+        .flat_map(|content| content.items())
+        .filter_map(|item| match item {
+            OpenAIContentItem::ToolResult {
+                tool_use_id,
+                content,
+                is_error,
+            } => Some(serde_json::json!({
+                "type": "tool_result",
+                "tool_use_id": tool_use_id,
+                "content": extract_text_from_content_items(content),
+                "is_error": is_error.unwrap_or(false),
+            })),
+            _ => None,
+        })
+        .collect()
+}
+
+fn text_block(text: &str) -> serde_json::Value {
+    serde_json::json!({ "type": "text", "text": text })
+}
+
+fn image_block(image_url: &OpenAIImageUrl) -> serde_json::Value {
+    let base64 = image_url
+        .url
+        .strip_prefix("data:")
+        .and_then(|rest| rest.split_once(";base64,"));
+
+    match base64 {
+        Some((media_type, data)) => serde_json::json!({
+            "type": "image",
+            "source": { "type": "base64", "media_type": media_type, "data": data },
+        }),
+        None => serde_json::json!({
+            "type": "image",
+            "source": { "type": "url", "url": image_url.url },
+        }),
+    }
+}
+
+/// Response from `/v1/messages`
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct AnthropicResponse {
+    pub id: String,
+    pub model: String,
+    #[serde(default)]
+    pub content: Vec<AnthropicContentBlock>,
+    #[serde(default)]
+    pub stop_reason: Option<String>,
+    #[serde(default)]
+    pub usage: AnthropicUsage,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AnthropicContentBlock {
+    Text {
+        text: String,
+    },
+    ToolUse {
+        id: String,
+        name: String,
+        input: serde_json::Value,
+    },
+    /// Thinking and other blocks the proxy doesn't surface
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct AnthropicUsage {
+    #[serde(default)]
+    pub input_tokens: u32,
+    #[serde(default)]
+    pub output_tokens: u32,
+}
+
+impl From<AnthropicUsage> for OpenAIUsage {
+    fn from(usage: AnthropicUsage) -> Self {
+        Self {
+            prompt_tokens: usage.input_tokens,
+            completion_tokens: usage.output_tokens,
+            total_tokens: usage.input_tokens + usage.output_tokens,
+        }
+    }
+}
+
+/// Map an Anthropic stop reason to the OpenAI finish reason
+pub fn finish_reason(stop_reason: Option<&str>) -> String {
+    match stop_reason {
+        Some("max_tokens") => "length",
+        Some("tool_use") => "tool_calls",
+        Some("refusal") => "content_filter",
+        _ => "stop", // end_turn, stop_sequence, pause_turn
+    }
+    .to_string()
+}
+
+impl From<AnthropicResponse> for OpenAICompletionResponse {
+    fn from(response: AnthropicResponse) -> Self {
+        let mut text = String::new();
+        let mut tool_calls = Vec::new();
+
+        for block in response.content {
+            match block {
+                AnthropicContentBlock::Text { text: part } => text.push_str(&part),
+                AnthropicContentBlock::ToolUse { id, name, input } => tool_calls.push(OpenAIToolCall {
+                    id,
+                    tool_type: "function".to_string(),
+                    function: OpenAIFunctionCall::new(name, &input),
+                }),
+                AnthropicContentBlock::Other => {}
+            }
+        }
+
+        Self {
+            id: response.id,
+            object: "chat.completion".to_string(),
+            created: 0, // Anthropic doesn't report a creation time
+            model: response.model,
+            choices: vec![OpenAIChoice {
+                index: 0,
+                message: OpenAIMessage {
+                    role: "assistant".to_string(),
+                    content: if text.is_empty() {
+                        None
+                    } else {
+                        Some(OpenAIContent::from_text(text))
+                    },
+                    tool_calls: if tool_calls.is_empty() {
+                        None
+                    } else {
+                        Some(tool_calls)
+                    },
+                    tool_call_id: None,
+                    name: None,
+                    audio: None,
+                    refusal: None,
+                },
+                logprobs: None,
+                finish_reason: finish_reason(response.stop_reason.as_deref()),
+            }],
+            usage: response.usage.into(),
+            service_tier: None,
+            system_fingerprint: None,
+        }
+    }
+}
+
+/// Translates Anthropic stream events into OpenAI stream chunks
+///
+/// Anthropic numbers content blocks across text and tool use, while OpenAI
+/// numbers tool calls on their own, so block indices are remapped.
+#[derive(Debug, Default)]
+pub struct AnthropicStreamTranslator {
+    id: Option<String>,
+    model: Option<String>,
+    input_tokens: u32,
+    tool_indices: HashMap<u32, u32>,
+}
+
+impl AnthropicStreamTranslator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Translate one event, returning the chunk to feed the stream accumulator
+    ///
+    /// Returns an error message for `error` events.
+    pub fn push_event(&mut self, event: serde_json::Value) -> Result<Option<OpenAIStreamChunk>, String> {
+        let index = event["index"].as_u64().unwrap_or_default() as u32;
+
+        let (delta, finish_reason, usage) = match event["type"].as_str().unwrap_or_default() {
+            "message_start" => {
+                let message = &event["message"];
+                self.id = message["id"].as_str().map(str::to_string);
+                self.model = message["model"].as_str().map(str::to_string);
+                self.input_tokens = message["usage"]["input_tokens"].as_u64().unwrap_or_default() as u32;
+                (OpenAIStreamDelta::default(), None, None)
+            }
+            "content_block_start" if event["content_block"]["type"] == "tool_use" => {
+                let tool_index = self.tool_indices.len() as u32;
+                self.tool_indices.insert(index, tool_index);
+                let block = &event["content_block"];
+                let delta = tool_call_delta(
+                    tool_index,
+                    block["id"].as_str().map(str::to_string),
+                    block["name"].as_str().map(str::to_string),
+                    String::new(),
+                );
+                (delta, None, None)
+            }
+            "content_block_delta" => {
+                let delta = &event["delta"];
+                match delta["type"].as_str().unwrap_or_default() {
+                    "text_delta" => (
+                        OpenAIStreamDelta {
+                            content: delta["text"].as_str().map(str::to_string),
+                            ..Default::default()
+                        },
+                        None,
+                        None,
+                    ),
+                    "input_json_delta" => match self.tool_indices.get(&index) {
+                        Some(&tool_index) => (
+                            tool_call_delta(
+                                tool_index,
+                                None,
+                                None,
+                                delta["partial_json"].as_str().unwrap_or_default().to_string(),
+                            ),
+                            None,
+                            None,
+                        ),
+                        None => return Ok(None),
+                    },
+                    _ => return Ok(None),
+                }
+            }
+            "message_delta" => {
+                let output_tokens = event["usage"]["output_tokens"].as_u64().unwrap_or_default() as u32;
+                let usage = AnthropicUsage {
+                    input_tokens: self.input_tokens,
+                    output_tokens,
+                };
+                (
+                    OpenAIStreamDelta::default(),
+                    Some(finish_reason(event["delta"]["stop_reason"].as_str())),
+                    Some(usage.into()),
+                )
+            }
+            "error" => {
+                return Err(event["error"]["message"]
+                    .as_str()
+                    .unwrap_or("Unknown stream error")
+                    .to_string());
+            }
+            // ping, message_stop, content_block_stop, text block starts
+            _ => return Ok(None),
+        };
+
+        Ok(Some(OpenAIStreamChunk {
+            id: self.id.clone(),
+            created: None,
+            model: self.model.clone(),
+            choices: vec![OpenAIStreamChoice {
+                index: 0,
+                delta,
+                finish_reason,
+                usage: None,
+            }],
+            usage,
+        }))
+    }
+}
+
+fn tool_call_delta(index: u32, id: Option<String>, name: Option<String>, arguments: String) -> OpenAIStreamDelta {
+    OpenAIStreamDelta {
+        tool_calls: Some(vec![OpenAIToolCallDelta {
+            index,
+            id,
+            tool_type: Some("function".to_string()),
+            function: Some(OpenAIFunctionCallDelta {
+                name,
+                arguments: Some(arguments),
+            }),
+        }]),
+        ..Default::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bindings::colinrozzi::genai_types::types::{
+        CompletionRequest, Message, MessageContent, MessageRole, ToolResult, ToolUse,
+    };
+    use crate::types::conversion::DecodedArguments;
+    use crate::types::stream::{parse_sse_data, StreamAccumulator};
+
+    #[test]
+    fn test_request_translation() {
+        let request = OpenAICompletionRequest::try_from(CompletionRequest {
+            model: "claude-3-5-sonnet-20241022".to_string(),
+            messages: vec![
+                Message {
+                    role: MessageRole::User,
+                    content: vec![MessageContent::Text("Weather in Paris?".to_string())],
+                },
+                Message {
+                    role: MessageRole::Assistant,
+                    content: vec![MessageContent::ToolUse(ToolUse {
+                        id: "toolu_1".to_string(),
+                        name: "get_weather".to_string(),
+                        input: br#"{"city":"Paris"}"#.to_vec(),
+                    })],
+                },
+                Message {
+                    role: MessageRole::User,
+                    content: vec![
+                        MessageContent::ToolResult(ToolResult {
+                            tool_use_id: "toolu_1".to_string(),
+                            content: b"18C".to_vec(),
+                            is_error: false,
+                        }),
+                        MessageContent::Text("Thanks".to_string()),
+                    ],
+                },
+            ],
+            max_tokens: 512,
+            temperature: None,
+            system: Some("Be brief.".to_string()),
+            tools: None,
+            tool_choice: None,
+            disable_parallel_tool_use: None,
+        })
+        .unwrap();
+
+        let body = anthropic_request_body(&request);
+        assert_eq!(body["system"], "Be brief.");
+        assert_eq!(body["messages"].as_array().unwrap().len(), 3);
+        assert_eq!(
+            body["messages"][1]["content"][0],
+            serde_json::json!({ "type": "tool_use", "id": "toolu_1", "name": "get_weather", "input": { "city": "Paris" } })
+        );
+        assert_eq!(
+            body["messages"][2]["content"],
+            serde_json::json!([
+                { "type": "tool_result", "tool_use_id": "toolu_1", "content": "18C", "is_error": false },
+                { "type": "text", "text": "Thanks" }
+            ])
+        );
+    }
+
+    #[test]
+    fn test_stream_translation() {
+        let body = concat!(
+            "event: message_start\n",
+            "data: {\"type\":\"message_start\",\"message\":{\"id\":\"msg_1\",\"model\":\"claude-3-5-sonnet-20241022\",\"usage\":{\"input_tokens\":12}}}\n\n",
+            "event: content_block_delta\n",
+            "data: {\"type\":\"content_block_delta\",\"index\":0,\"delta\":{\"type\":\"text_delta\",\"text\":\"Checking\"}}\n\n",
+            "event: content_block_start\n",
+            "data: {\"type\":\"content_block_start\",\"index\":1,\"content_block\":{\"type\":\"tool_use\",\"id\":\"toolu_1\",\"name\":\"get_weather\",\"input\":{}}}\n\n",
+            "event: content_block_delta\n",
+            "data: {\"type\":\"content_block_delta\",\"index\":1,\"delta\":{\"type\":\"input_json_delta\",\"partial_json\":\"{\\\"city\\\":\\\"Paris\\\"}\"}}\n\n",
+            "event: message_delta\n",
+            "data: {\"type\":\"message_delta\",\"delta\":{\"stop_reason\":\"tool_use\"},\"usage\":{\"output_tokens\":9}}\n\n",
+        );
+
+        let mut translator = AnthropicStreamTranslator::new();
+        let mut accumulator = StreamAccumulator::new();
+        for data in parse_sse_data(body) {
+            let event: serde_json::Value = serde_json::from_str(&data).unwrap();
+            if let Some(chunk) = translator.push_event(event).unwrap() {
+                accumulator.push_chunk(chunk).unwrap();
+            }
+        }
+
+        let response = accumulator.into_response();
+        assert_eq!(response.id, "msg_1");
+        assert_eq!(response.usage.total_tokens, 21);
+        let choice = &response.choices[0];
+        assert_eq!(choice.finish_reason, "tool_calls");
+        let call = &choice.message.tool_calls.as_ref().unwrap()[0];
+        assert_eq!(
+            call.function.decode_arguments(),
+            DecodedArguments::Valid(serde_json::json!({ "city": "Paris" }))
+        );
+    }
+}
@@ -2,7 +2,8 @@
 use crate::bindings::colinrozzi::genai_types::types::{CompletionRequest, Tool, ToolChoice};
 use crate::types::conversion::{MessageConverter, OpenAIMessage};
 use crate::types::state::ContentFormat;
-use crate::bindings::theater::simple::runtime::log;
+use crate::types::tool_names::ToolNames;
+use crate::types::tool_schema::check_schema;
 use serde::{Deserialize, Serialize};
 
 /// OpenAI-compatible tool structure for the API
@@ -19,6 +20,12 @@ pub struct OpenAIFunction {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub description: Option<String>,
     pub parameters: serde_json::Value,
+    /// Ask OpenAI to constrain arguments to `parameters` exactly
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub strict: Option<bool>,
+    /// The tool's `output_schema`, kept for describing the tool to the model
+    #[serde(skip)]
+    pub output_schema: Option<serde_json::Value>,
 }
 
 /// OpenAI completion request structure
@@ -41,46 +48,171 @@ pub struct OpenAICompletionRequest {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub stream: Option<bool>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    pub stream_options: Option<OpenAIStreamOptions>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub tools: Option<Vec<OpenAITool>>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub tool_choice: Option<ToolChoice>,
+    pub tool_choice: Option<OpenAIToolChoice>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parallel_tool_calls: Option<bool>,
+    /// How the caller's tool names map to the names in `tools`
+    #[serde(skip)]
+    pub tool_names: ToolNames,
 }
 
-/// Convert MCP Protocol Tools to OpenAI format
-fn convert_tools_to_openai_format(tools: &[Tool]) -> Vec<OpenAITool> {
-    tools.iter().map(|tool| {
-        // Parse the input_schema bytes to JSON
-        let parameters = match serde_json::from_slice::<serde_json::Value>(&tool.input_schema) {
-            Ok(schema) => schema,
-            Err(e) => {
-                log(&format!("Failed to parse tool input_schema for {}: {}", tool.name, e));
-                serde_json::json!({
-                    "type": "object",
-                    "properties": {},
-                    "required": []
+/// Tool choice in OpenAI's wire format
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum OpenAIToolChoice {
+    /// `"auto"`, `"required"` or `"none"`
+    Mode(OpenAIToolChoiceMode),
+    /// `{"type": "function", "function": {"name": ...}}`
+    Function {
+        #[serde(rename = "type")]
+        tool_type: String,
+        function: OpenAIToolChoiceFunction,
+    },
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum OpenAIToolChoiceMode {
+    Auto,
+    Required,
+    None,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct OpenAIToolChoiceFunction {
+    pub name: String,
+}
+
+impl OpenAIToolChoice {
+    /// Translate a genai tool choice, checking that a specific tool was supplied
+    pub fn from_genai(choice: ToolChoice, tools: Option<&[Tool]>) -> Result<Self, String> {
+        match choice {
+            ToolChoice::Auto => Ok(Self::Mode(OpenAIToolChoiceMode::Auto)),
+            ToolChoice::Any => Ok(Self::Mode(OpenAIToolChoiceMode::Required)),
+            ToolChoice::None => Ok(Self::Mode(OpenAIToolChoiceMode::None)),
+            ToolChoice::Specific(name) => {
+                let tools = tools.unwrap_or_default();
+                if !tools.iter().any(|tool| tool.name == name) {
+                    let available: Vec<&str> = tools.iter().map(|tool| tool.name.as_str()).collect();
+                    return Err(format!(
+                        "tool_choice names tool '{}', which is not in the request's tools ({})",
+                        name,
+                        if available.is_empty() { "none supplied".to_string() } else { available.join(", ") }
+                    ));
+                }
+                Ok(Self::Function {
+                    tool_type: "function".to_string(),
+                    function: OpenAIToolChoiceFunction { name },
                 })
             }
+        }
+    }
+}
+
+/// Options that only apply when `stream` is set
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct OpenAIStreamOptions {
+    /// Ask the provider to send token usage on the final chunk
+    pub include_usage: bool,
+}
+
+/// Convert MCP Protocol Tools to OpenAI format
+///
+/// Tools are renamed as `tool_names` says. Fails when a tool's schema isn't
+/// JSON or can't describe tool arguments.
+fn convert_tools_to_openai_format(tools: &[Tool], tool_names: &ToolNames) -> Result<Vec<OpenAITool>, String> {
+    tools.iter().map(|tool| {
+        // An empty schema means the tool takes no arguments
+        let parameters = if tool.input_schema.iter().all(u8::is_ascii_whitespace) {
+            serde_json::json!({ "type": "object", "properties": {} })
+        } else {
+            serde_json::from_slice::<serde_json::Value>(&tool.input_schema).map_err(|e| e.to_string())
+                .and_then(|schema| check_schema(&schema).map(|_| schema))
+                .map_err(|e| format!("Tool '{}' has an invalid input_schema: {}", tool.name, e))?
         };
-        
-        OpenAITool {
+
+        let output_schema = tool
+            .output_schema
+            .as_ref()
+            .map(|schema| serde_json::from_slice::<serde_json::Value>(schema))
+            .transpose()
+            .map_err(|e| format!("Tool '{}' has an invalid output_schema: {}", tool.name, e))?;
+
+        let annotations = tool
+            .annotations
+            .as_ref()
+            .and_then(|annotations| serde_json::from_slice::<serde_json::Value>(annotations).ok())
+            .unwrap_or_default();
+
+        Ok(OpenAITool {
             tool_type: "function".to_string(),
             function: OpenAIFunction {
-                name: tool.name.clone(),
-                description: tool.description.clone(),
+                name: tool_names.provider_name(&tool.name),
+                description: describe_tool(tool.description.clone(), &annotations),
                 parameters,
+                strict: None,
+                output_schema,
             },
-        }
+        })
     }).collect()
 }
 
-impl From<CompletionRequest> for OpenAICompletionRequest {
-    fn from(request: CompletionRequest) -> Self {
+/// A tool's description, with the MCP annotations the model should know about
+///
+/// Providers have no field for annotations, so `title` stands in for a missing
+/// description and the behaviour hints are appended to it.
+fn describe_tool(description: Option<String>, annotations: &serde_json::Value) -> Option<String> {
+    let title = annotations.get("title").and_then(|title| title.as_str()).map(str::to_string);
+    let hints: Vec<&str> = [
+        ("readOnlyHint", true, "This tool does not modify anything."),
+        ("destructiveHint", true, "This tool may make destructive changes."),
+        ("idempotentHint", true, "Calling this tool repeatedly with the same arguments has no additional effect."),
+    ]
+    .into_iter()
+    .filter(|(hint, expected, _)| annotations.get(*hint).and_then(|value| value.as_bool()) == Some(*expected))
+    .map(|(_, _, text)| text)
+    .collect();
+
+    let description = description.or(title);
+    if hints.is_empty() {
+        return description;
+    }
+    Some(match description {
+        Some(description) => format!("{}\n\n{}", description, hints.join(" ")),
+        None => hints.join(" "),
+    })
+}
+
+impl TryFrom<CompletionRequest> for OpenAICompletionRequest {
+    type Error = String;
+
+    /// Fails with a user-facing message when the request is inconsistent
+    fn try_from(request: CompletionRequest) -> Result<Self, String> {
+        let tool_names = ToolNames::new(request.tools.iter().flatten().map(|tool| tool.name.as_str()));
+
+        let mut tool_choice = request
+            .tool_choice
+            .map(|choice| OpenAIToolChoice::from_genai(choice, request.tools.as_deref()))
+            .transpose()?;
+        if let Some(OpenAIToolChoice::Function { function, .. }) = &mut tool_choice {
+            function.name = tool_names.provider_name(&function.name);
+        }
+
         let mut messages: Vec<OpenAIMessage> = request
             .messages
             .into_iter()
-            .map(MessageConverter::to_openai_message)
+            .flat_map(MessageConverter::to_openai_message)
             .collect();
 
+        // Earlier tool calls must use the names the tools are sent under
+        for call in messages.iter_mut().flat_map(|message| message.tool_calls.iter_mut().flatten()) {
+            call.function.name = tool_names.provider_name(&call.function.name);
+        }
+
         // Add system message if provided
         if let Some(system_content) = request.system {
             let system_message = OpenAIMessage {
@@ -95,7 +227,7 @@ impl From<CompletionRequest> for OpenAICompletionRequest {
             messages.insert(0, system_message);
         }
 
-        Self {
+        Ok(Self {
             model: request.model,
             messages,
             max_tokens: Some(request.max_tokens),
@@ -104,14 +236,29 @@ impl From<CompletionRequest> for OpenAICompletionRequest {
             frequency_penalty: None,
             presence_penalty: None,
             stop: None,
-            stream: Some(false), // We don't support streaming yet
-            tools: request.tools.as_ref().map(|tools| convert_tools_to_openai_format(tools)),
-            tool_choice: request.tool_choice,
-        }
+            stream: Some(false), // Set by Provider::request_body(.., stream) for ProviderClient::stream_completion
+            stream_options: None,
+            tools: request
+                .tools
+                .as_deref()
+                .map(|tools| convert_tools_to_openai_format(tools, &tool_names))
+                .transpose()?,
+            tool_choice,
+            parallel_tool_calls: request.disable_parallel_tool_use.map(|disable| !disable),
+            tool_names,
+        })
     }
 }
 
 impl OpenAICompletionRequest {
+    /// Whether any message carries image parts
+    pub fn has_images(&self) -> bool {
+        self.messages
+            .iter()
+            .filter_map(|message| message.content.as_ref())
+            .any(|content| content.has_images())
+    }
+
     /// Serialize for specific provider format
     pub fn serialize_for_provider(&self, format: &ContentFormat) -> serde_json::Value {
         let mut request = serde_json::Map::new();
@@ -162,6 +309,10 @@ impl OpenAICompletionRequest {
             request.insert("stream".to_string(), serde_json::Value::Bool(stream));
         }
         
+        if let Some(stream_options) = &self.stream_options {
+            request.insert("stream_options".to_string(), serde_json::to_value(stream_options).unwrap_or(serde_json::Value::Null));
+        }
+        
         if let Some(tools) = &self.tools {
             request.insert("tools".to_string(), serde_json::to_value(tools).unwrap_or(serde_json::Value::Null));
         }
@@ -170,18 +321,35 @@ impl OpenAICompletionRequest {
             request.insert("tool_choice".to_string(), serde_json::to_value(tool_choice).unwrap_or(serde_json::Value::Null));
         }
         
+        // parallel_tool_calls only means anything alongside tools
+        if let (Some(parallel_tool_calls), Some(_)) = (self.parallel_tool_calls, &self.tools) {
+            request.insert("parallel_tool_calls".to_string(), serde_json::Value::Bool(parallel_tool_calls));
+        }
+        
         serde_json::Value::Object(request)
     }
 }
 
 /// Usage statistics from the API
-#[derive(Serialize, Deserialize, Debug, Clone)]
+///
+/// Some compatible servers leave out fields, or the whole object.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(default)]
 pub struct OpenAIUsage {
     pub prompt_tokens: u32,
     pub completion_tokens: u32,
     pub total_tokens: u32,
 }
 
+impl OpenAIUsage {
+    /// Add the usage of another request, such as an earlier attempt
+    pub fn add(&mut self, other: &OpenAIUsage) {
+        self.prompt_tokens = self.prompt_tokens.saturating_add(other.prompt_tokens);
+        self.completion_tokens = self.completion_tokens.saturating_add(other.completion_tokens);
+        self.total_tokens = self.total_tokens.saturating_add(other.total_tokens);
+    }
+}
+
 /// Input audio structure for audio-capable models
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct OpenAIInputAudio {
@@ -210,3 +378,46 @@ impl From<OpenAIUsage> for Usage {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(tool_choice: ToolChoice) -> CompletionRequest {
+        CompletionRequest {
+            model: "kimi-k2-0711-preview".to_string(),
+            messages: Vec::new(),
+            max_tokens: 256,
+            temperature: None,
+            system: None,
+            tools: Some(vec![Tool {
+                name: "get_weather".to_string(),
+                description: None,
+                input_schema: br#"{"type":"object"}"#.to_vec(),
+                output_schema: None,
+                annotations: None,
+                meta: None,
+            }]),
+            tool_choice: Some(tool_choice),
+            disable_parallel_tool_use: Some(true),
+        }
+    }
+
+    #[test]
+    fn test_tool_choice_translation() {
+        let openai_request =
+            OpenAICompletionRequest::try_from(request(ToolChoice::Specific("get_weather".to_string()))).unwrap();
+        let body = openai_request.serialize_for_provider(&ContentFormat::Array);
+        assert_eq!(
+            body["tool_choice"],
+            serde_json::json!({ "type": "function", "function": { "name": "get_weather" } })
+        );
+        assert_eq!(body["parallel_tool_calls"], serde_json::json!(false));
+
+        let openai_request = OpenAICompletionRequest::try_from(request(ToolChoice::Any)).unwrap();
+        let body = openai_request.serialize_for_provider(&ContentFormat::Array);
+        assert_eq!(body["tool_choice"], serde_json::json!("required"));
+
+        assert!(OpenAICompletionRequest::try_from(request(ToolChoice::Specific("search".to_string()))).is_err());
+    }
+}
@@ -0,0 +1,159 @@
+use serde::{Deserialize, Serialize};
+
+/// When an upstream's breaker trips and how long it stays open
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CircuitBreakerConfig {
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+
+    /// Consecutive failed requests that open the breaker
+    #[serde(default = "default_failure_threshold")]
+    pub failure_threshold: u32,
+
+    /// How long an open breaker fast-fails before letting a probe through, in milliseconds
+    #[serde(default = "default_cooldown_ms")]
+    pub cooldown_ms: u64,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+fn default_failure_threshold() -> u32 {
+    5
+}
+
+fn default_cooldown_ms() -> u64 {
+    30_000
+}
+
+impl Default for CircuitBreakerConfig {
+    fn default() -> Self {
+        Self {
+            enabled: default_enabled(),
+            failure_threshold: default_failure_threshold(),
+            cooldown_ms: default_cooldown_ms(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum CircuitState {
+    /// Requests go through normally
+    #[default]
+    Closed,
+    /// Requests fail immediately until the cooldown has passed
+    Open,
+    /// One probe request is in flight; its outcome closes or reopens the breaker
+    HalfOpen,
+}
+
+/// Health of one upstream, kept in actor state between requests
+///
+/// Only failures another upstream might avoid (see `OpenAIError::should_fail_over`)
+/// count; any other outcome shows the upstream is answering.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct CircuitBreaker {
+    pub state: CircuitState,
+    pub consecutive_failures: u32,
+    pub total_failures: u64,
+    /// Timestamp (ms since epoch) of the most recent failure
+    pub last_failure_at: Option<u64>,
+    /// Timestamp (ms since epoch) the breaker last opened
+    pub opened_at: Option<u64>,
+    pub last_error: Option<String>,
+}
+
+impl CircuitBreaker {
+    /// Whether a request may be sent at `now`
+    ///
+    /// An open breaker whose cooldown has passed moves to half-open and lets
+    /// this request through as the probe.
+    pub fn allow_request(&mut self, config: &CircuitBreakerConfig, now: u64) -> bool {
+        if !config.enabled {
+            return true;
+        }
+
+        match self.state {
+            CircuitState::Closed | CircuitState::HalfOpen => true,
+            CircuitState::Open => {
+                if self.retry_in_ms(config, now).is_none() {
+                    self.state = CircuitState::HalfOpen;
+                    true
+                } else {
+                    false
+                }
+            }
+        }
+    }
+
+    pub fn record_success(&mut self) {
+        self.state = CircuitState::Closed;
+        self.consecutive_failures = 0;
+        self.opened_at = None;
+    }
+
+    pub fn record_failure(&mut self, config: &CircuitBreakerConfig, now: u64, error: &str) {
+        self.consecutive_failures += 1;
+        self.total_failures += 1;
+        self.last_failure_at = Some(now);
+        self.last_error = Some(error.to_string());
+
+        let tripped = self.state == CircuitState::HalfOpen
+            || self.consecutive_failures >= config.failure_threshold;
+        if config.enabled && tripped {
+            self.state = CircuitState::Open;
+            self.opened_at = Some(now);
+        }
+    }
+
+    /// Time left before an open breaker lets a probe through
+    pub fn retry_in_ms(&self, config: &CircuitBreakerConfig, now: u64) -> Option<u64> {
+        match (self.state, self.opened_at) {
+            (CircuitState::Open, Some(opened_at)) => {
+                let reopen_at = opened_at + config.cooldown_ms;
+                (now < reopen_at).then(|| reopen_at - now)
+            }
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_breaker_lifecycle() {
+        let config = CircuitBreakerConfig {
+            enabled: true,
+            failure_threshold: 2,
+            cooldown_ms: 1000,
+        };
+        let mut breaker = CircuitBreaker::default();
+
+        breaker.record_failure(&config, 100, "API error 503");
+        assert_eq!(breaker.state, CircuitState::Closed);
+        breaker.record_failure(&config, 200, "API error 503");
+        assert_eq!(breaker.state, CircuitState::Open);
+
+        // Fast-fail during the cooldown
+        assert!(!breaker.allow_request(&config, 500));
+        assert_eq!(breaker.retry_in_ms(&config, 500), Some(700));
+
+        // A failed probe reopens the breaker straight away
+        assert!(breaker.allow_request(&config, 1200));
+        assert_eq!(breaker.state, CircuitState::HalfOpen);
+        breaker.record_failure(&config, 1300, "Request timed out");
+        assert_eq!(breaker.state, CircuitState::Open);
+        assert!(!breaker.allow_request(&config, 1400));
+
+        // A successful probe closes it
+        assert!(breaker.allow_request(&config, 2300));
+        breaker.record_success();
+        assert_eq!(breaker.state, CircuitState::Closed);
+        assert_eq!(breaker.consecutive_failures, 0);
+        assert_eq!(breaker.total_failures, 3);
+    }
+}
@@ -8,18 +8,31 @@
 use crate::bindings::colinrozzi::genai_types::types::{
     Message, MessageContent, MessageRole, ToolResult, ToolUse,
 };
-use crate::bindings::colinrozzi::mcp_protocol::types::ContentItem;
-use crate::bindings::theater::simple::runtime::log;
+use crate::bindings::colinrozzi::mcp_protocol::types::{ContentItem, ImageContent};
+use crate::logging;
+use crate::types::json_repair::repair_json;
 use crate::types::state::ContentFormat;
-use serde::{Deserialize, Serialize};
+use crate::types::tool_names::ToolNames;
+use serde::{Deserialize, Deserializer, Serialize};
 
 // === CLEANER CONTENT FORMAT HANDLING ===
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Debug, Clone)]
 pub struct OpenAIContent {
     items: Vec<OpenAIContentItem>,
 }
 
+/// Accepts message content in any shape a provider sends, whatever the
+/// configured `ContentFormat`: a string, an array of parts (or of bare
+/// strings), a single part, or the `{"items": [...]}` shape this type
+/// serializes to (as stored in the response cache). `null` is handled by the
+/// `Option` around `OpenAIMessage.content`.
+impl<'de> Deserialize<'de> for OpenAIContent {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(Self::from_value(serde_json::Value::deserialize(deserializer)?))
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(tag = "type")]
 pub enum OpenAIContentItem {
@@ -52,6 +65,43 @@ pub struct OpenAIImageUrl {
     pub detail: Option<String>,
 }
 
+/// Mime type assumed when MCP image content doesn't name one
+const DEFAULT_IMAGE_MIME_TYPE: &str = "image/png";
+
+impl OpenAIImageUrl {
+    /// Build an image part from MCP image content
+    ///
+    /// Base64 data becomes a `data:` URL; data that is already a URL is passed
+    /// through. `detail` is read from the content's `meta` (`{"detail": "low"}`).
+    pub fn from_image(image: &ImageContent) -> Self {
+        let url = if image.data.starts_with("data:")
+            || image.data.starts_with("https://")
+            || image.data.starts_with("http://")
+        {
+            image.data.clone()
+        } else {
+            format!(
+                "data:{};base64,{}",
+                image.mime_type.as_deref().unwrap_or(DEFAULT_IMAGE_MIME_TYPE),
+                image.data
+            )
+        };
+
+        let detail = image
+            .meta
+            .as_ref()
+            .and_then(|meta| serde_json::from_slice::<serde_json::Value>(meta).ok())
+            .and_then(|meta| meta.get("detail")?.as_str().map(str::to_string));
+
+        Self { url, detail }
+    }
+
+    /// Mime type of a `data:` URL, if this is one
+    pub fn mime_type(&self) -> Option<&str> {
+        self.url.strip_prefix("data:")?.split(';').next()
+    }
+}
+
 impl OpenAIContent {
     pub fn new() -> Self {
         Self { items: Vec::new() }
@@ -63,6 +113,29 @@ impl OpenAIContent {
         }
     }
 
+    fn from_value(value: serde_json::Value) -> Self {
+        use serde_json::Value;
+
+        let parts = match value {
+            Value::Null => Vec::new(),
+            Value::String(text) => vec![Value::String(text)],
+            Value::Array(parts) => parts,
+            Value::Object(mut object) => match object.remove("items") {
+                Some(Value::Array(items)) if !object.contains_key("type") => items,
+                Some(items) => {
+                    object.insert("items".to_string(), items);
+                    vec![Value::Object(object)]
+                }
+                None => vec![Value::Object(object)],
+            },
+            other => vec![Value::String(other.to_string())],
+        };
+
+        Self {
+            items: parts.into_iter().filter_map(content_part).collect(),
+        }
+    }
+
     pub fn add_item(&mut self, item: OpenAIContentItem) {
         self.items.push(item);
     }
@@ -71,6 +144,16 @@ impl OpenAIContent {
         self.items.is_empty()
     }
 
+    pub fn has_images(&self) -> bool {
+        self.items
+            .iter()
+            .any(|item| matches!(item, OpenAIContentItem::ImageUrl { .. }))
+    }
+
+    pub fn items(&self) -> &[OpenAIContentItem] {
+        &self.items
+    }
+
     /// Serialize for different provider formats
     pub fn serialize_for_format(&self, format: &ContentFormat) -> serde_json::Value {
         match format {
@@ -85,18 +168,13 @@ impl OpenAIContent {
             .iter()
             .filter_map(|item| match item {
                 OpenAIContentItem::Text { text } => Some(text.clone()),
-                OpenAIContentItem::ToolResult {
-                    content, is_error, ..
-                } => {
-                    let result_text = extract_text_from_content_items(content);
-                    if *is_error == Some(true) {
-                        Some(format!("Error: {}", result_text))
-                    } else {
-                        Some(result_text)
-                    }
+                OpenAIContentItem::ToolResult { .. } => Self::tool_result_text(item),
+                // Images can't be sent as a string, so leave a marker in their place
+                OpenAIContentItem::ImageUrl { image_url } => {
+                    Some(image_placeholder(image_url.mime_type().unwrap_or("image")))
                 }
-                // Skip tool_use and other complex types in string format
-                _ => None,
+                // Tool calls travel in `tool_calls`
+                OpenAIContentItem::ToolUse { .. } => None,
             })
             .collect();
 
@@ -108,8 +186,40 @@ impl OpenAIContent {
         })
     }
 
+    /// Only `text` and `image_url` parts are valid here; tool calls travel in
+    /// `tool_calls` and tool results in `role: "tool"` messages
     fn serialize_as_array(&self) -> serde_json::Value {
-        serde_json::to_value(&self.items).unwrap_or(serde_json::Value::Null)
+        let parts: Vec<serde_json::Value> = self
+            .items
+            .iter()
+            .filter_map(|item| match item {
+                OpenAIContentItem::Text { .. } | OpenAIContentItem::ImageUrl { .. } => {
+                    serde_json::to_value(item).ok()
+                }
+                OpenAIContentItem::ToolResult { .. } => {
+                    let text = Self::tool_result_text(item)?;
+                    Some(serde_json::json!({ "type": "text", "text": text }))
+                }
+                OpenAIContentItem::ToolUse { .. } => None,
+            })
+            .collect();
+        serde_json::Value::Array(parts)
+    }
+
+    fn tool_result_text(item: &OpenAIContentItem) -> Option<String> {
+        match item {
+            OpenAIContentItem::ToolResult {
+                content, is_error, ..
+            } => {
+                let result_text = extract_text_from_content_items(content);
+                if *is_error == Some(true) {
+                    Some(format!("Error: {}", result_text))
+                } else {
+                    Some(result_text)
+                }
+            }
+            _ => None,
+        }
     }
 }
 
@@ -122,33 +232,33 @@ impl ToolResultParser {
     pub fn parse_content(raw_content: &[u8]) -> Vec<ContentItem> {
         // Strategy 1: Direct deserialization
         if let Ok(items) = serde_json::from_slice::<Vec<ContentItem>>(raw_content) {
-            log("Successfully parsed tool result as ContentItem array");
+            logging::debug("Successfully parsed tool result as ContentItem array");
             return items;
         }
 
         // Strategy 2: Parse as UTF-8 string first, then JSON
         if let Ok(content_str) = String::from_utf8(raw_content.to_vec()) {
             if let Ok(items) = serde_json::from_str::<Vec<ContentItem>>(&content_str) {
-                log("Successfully parsed tool result from UTF-8 string");
+                logging::debug("Successfully parsed tool result from UTF-8 string");
                 return items;
             }
 
             // Strategy 3: Try parsing as single ContentItem
             if let Ok(item) = serde_json::from_str::<ContentItem>(&content_str) {
-                log("Successfully parsed tool result as single ContentItem");
+                logging::debug("Successfully parsed tool result as single ContentItem");
                 return vec![item];
             }
 
             // Strategy 4: Treat as plain text
-            log("Treating tool result as plain text");
+            logging::debug("Treating tool result as plain text");
             return vec![Self::create_text_content_item(content_str)];
         }
 
         // Strategy 5: Final fallback - binary content as text
         let content_str = String::from_utf8_lossy(raw_content);
-        log(&format!(
+        logging::debug(&format!(
             "Using lossy UTF-8 conversion for tool result: {}",
-            content_str
+            logging::content(&content_str)
         ));
         vec![Self::create_text_content_item(content_str.to_string())]
     }
@@ -170,12 +280,16 @@ impl ToolResultParser {
 pub struct MessageConverter;
 
 impl MessageConverter {
-    /// Convert from genai Message to OpenAI message format
-    pub fn to_openai_message(message: Message) -> OpenAIMessage {
+    /// Convert from genai Message to OpenAI messages
+    ///
+    /// OpenAI expects one `role: "tool"` message per tool result, so a message
+    /// carrying several results is split. Tool messages come first, since they
+    /// must directly follow the assistant message that made the calls; any text
+    /// alongside the results is sent as a separate message after them.
+    pub fn to_openai_message(message: Message) -> Vec<OpenAIMessage> {
         let mut content = OpenAIContent::new();
         let mut tool_calls = Vec::new();
-        let mut tool_call_id = None;
-        let mut message_role = Self::map_role(&message.role);
+        let mut tool_messages = Vec::new();
 
         // Process each content item
         for content_item in message.content {
@@ -187,17 +301,14 @@ impl MessageConverter {
                 MessageContent::ToolUse(ToolUse { id, name, input }) => {
                     let input_value: serde_json::Value = serde_json::from_slice(&input)
                         .unwrap_or_else(|_| {
-                            log("Failed to parse tool use input, using empty object");
+                            logging::error("Failed to parse tool use input, using empty object");
                             serde_json::json!({})
                         });
 
                     tool_calls.push(OpenAIToolCall {
-                        id: id.clone(),
+                        id,
                         tool_type: "function".to_string(),
-                        function: OpenAIFunctionCall {
-                            name,
-                            arguments: input_value,
-                        },
+                        function: OpenAIFunctionCall::new(name, &input_value),
                     });
                 }
 
@@ -206,22 +317,47 @@ impl MessageConverter {
                     content: raw_content,
                     is_error,
                 }) => {
-                    // Override role for tool results
-                    message_role = "tool".to_string();
-                    tool_call_id = Some(tool_use_id.clone());
-
                     let parsed_content = ToolResultParser::parse_content(&raw_content);
-                    content.add_item(OpenAIContentItem::ToolResult {
-                        tool_use_id,
+
+                    // Tool messages only take text, so images follow in a user message
+                    let images: Vec<OpenAIContentItem> = parsed_content
+                        .iter()
+                        .filter_map(image_content_item)
+                        .collect();
+                    if !images.is_empty() {
+                        content.add_item(OpenAIContentItem::Text {
+                            text: format!("Images returned by tool call {}:", tool_use_id),
+                        });
+                        images.into_iter().for_each(|image| content.add_item(image));
+                    }
+
+                    let mut result = OpenAIContent::new();
+                    result.add_item(OpenAIContentItem::ToolResult {
+                        tool_use_id: tool_use_id.clone(),
                         content: parsed_content,
                         is_error: Some(is_error),
                     });
+
+                    tool_messages.push(OpenAIMessage {
+                        role: "tool".to_string(),
+                        content: Some(result),
+                        tool_calls: None,
+                        tool_call_id: Some(tool_use_id),
+                        name: None,
+                        audio: None,
+                        refusal: None,
+                    });
                 }
             }
         }
 
-        OpenAIMessage {
-            role: message_role,
+        if !tool_messages.is_empty() && content.is_empty() && tool_calls.is_empty() {
+            return tool_messages;
+        }
+
+        let mut messages = tool_messages;
+        messages.push(OpenAIMessage {
+            role: Self::map_role(&message.role),
             content: if content.is_empty() {
                 None
             } else {
@@ -232,15 +368,18 @@ impl MessageConverter {
             } else {
                 Some(tool_calls)
             },
-            tool_call_id,
+            tool_call_id: None,
             name: None,
             audio: None,
             refusal: None,
-        }
+        });
+        messages
     }
 
     /// Convert from OpenAI message back to genai Message
-    pub fn from_openai_message(openai_msg: OpenAIMessage) -> Message {
+    ///
+    /// Tool calls are given the caller's names for the tools (see `ToolNames`).
+    pub fn from_openai_message(openai_msg: OpenAIMessage, tool_names: &ToolNames) -> Message {
         let mut content = Vec::new();
 
         // Process content items
@@ -257,7 +396,7 @@ impl MessageConverter {
                         is_error,
                     } => {
                         let serialized_content = serde_json::to_vec(&items).unwrap_or_else(|_| {
-                            log("Failed to serialize tool result content");
+                            logging::error("Failed to serialize tool result content");
                             Vec::new()
                         });
 
@@ -270,19 +409,19 @@ impl MessageConverter {
 
                     OpenAIContentItem::ToolUse { id, name, input } => {
                         let serialized_input = serde_json::to_vec(&input).unwrap_or_else(|_| {
-                            log("Failed to serialize tool use input");
+                            logging::error("Failed to serialize tool use input");
                             serde_json::to_vec(&serde_json::json!({})).unwrap_or_default()
                         });
 
                         content.push(MessageContent::ToolUse(ToolUse {
                             id,
-                            name,
+                            name: tool_names.original_name(&name),
                             input: serialized_input,
                         }));
                     }
 
                     _ => {
-                        log("Unsupported OpenAI content item type");
+                        logging::info("Unsupported OpenAI content item type");
                         content.push(MessageContent::Text("[Unsupported content]".to_string()));
                     }
                 }
@@ -290,20 +429,22 @@ impl MessageConverter {
         }
 
         // Process tool calls
+        //
+        // Arguments that needed repair or couldn't be decoded are reported by
+        // `OpenAICompletionResponse::tool_call_warnings`, not here.
         if let Some(tool_calls) = openai_msg.tool_calls {
             for tool_call in tool_calls {
                 if tool_call.tool_type == "function" {
-                    let input: serde_json::Value =
-                        serde_json::from_value(tool_call.function.arguments).unwrap_or_else(|_| {
-                            log("Failed to parse tool call arguments");
-                            serde_json::json!({})
-                        });
-
+                    let input = tool_call
+                        .function
+                        .decode_arguments()
+                        .into_value()
+                        .unwrap_or_else(|| serde_json::json!({}));
                     let serialized_input = serde_json::to_vec(&input).unwrap_or_default();
 
                     content.push(MessageContent::ToolUse(ToolUse {
                         id: tool_call.id,
-                        name: tool_call.function.name,
+                        name: tool_names.original_name(&tool_call.function.name),
                         input: serialized_input,
                     }));
                 }
@@ -363,7 +504,13 @@ impl OpenAIMessage {
             serde_json::Value::String(self.role.clone()),
         );
 
+        // Tool messages take plain string content in every format
         if let Some(content) = &self.content {
+            let format = if self.role == "tool" {
+                &ContentFormat::String
+            } else {
+                format
+            };
             map.insert("content".to_string(), content.serialize_for_format(format));
         }
 
@@ -415,9 +562,148 @@ pub struct OpenAIToolCall {
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct OpenAIFunctionCall {
     pub name: String,
+    /// A JSON-encoded string on the wire; some providers send an object instead
     pub arguments: serde_json::Value,
 }
 
+impl OpenAIFunctionCall {
+    pub fn new(name: String, input: &serde_json::Value) -> Self {
+        Self {
+            name,
+            arguments: serde_json::Value::String(input.to_string()),
+        }
+    }
+
+    /// Decode the arguments into a JSON object, accepting either wire shape
+    ///
+    /// Strings that aren't valid JSON go through `repair_json`. Strings that
+    /// decode to another JSON-encoded string are decoded again, and count as
+    /// repaired. An empty string is taken to mean no arguments. Anything that
+    /// doesn't end up as an object is invalid, since tools take named arguments.
+    pub fn decode_arguments(&self) -> DecodedArguments {
+        let (mut value, mut repaired) = match &self.arguments {
+            serde_json::Value::String(arguments) if arguments.trim().is_empty() => {
+                return DecodedArguments::Valid(serde_json::json!({}));
+            }
+            serde_json::Value::String(arguments) => match parse_arguments(arguments) {
+                Ok(decoded) => decoded,
+                Err(error) => return DecodedArguments::Invalid(error),
+            },
+            other => (other.clone(), false),
+        };
+
+        // Some models encode the arguments more than once
+        for _ in 0..MAX_ARGUMENT_ENCODINGS {
+            let serde_json::Value::String(inner) = &value else {
+                break;
+            };
+            match parse_arguments(inner) {
+                Ok((inner, _)) => {
+                    value = inner;
+                    repaired = true;
+                }
+                Err(_) => break,
+            }
+        }
+
+        match value {
+            serde_json::Value::Object(_) if repaired => DecodedArguments::Repaired(value),
+            serde_json::Value::Object(_) => DecodedArguments::Valid(value),
+            serde_json::Value::Null => DecodedArguments::Invalid("arguments are null".to_string()),
+            serde_json::Value::Array(_) => DecodedArguments::Invalid("arguments must be a JSON object, not an array".to_string()),
+            _ => DecodedArguments::Invalid(format!("arguments must be a JSON object, not {}", value)),
+        }
+    }
+}
+
+/// How many extra layers of string encoding `decode_arguments` removes
+const MAX_ARGUMENT_ENCODINGS: usize = 3;
+
+/// Parse JSON text, falling back to `repair_json`; the flag says whether it was repaired
+fn parse_arguments(text: &str) -> Result<(serde_json::Value, bool), String> {
+    match serde_json::from_str(text) {
+        Ok(value) => Ok((value, false)),
+        Err(e) => repair_json(text).map(|value| (value, true)).ok_or_else(|| e.to_string()),
+    }
+}
+
+/// The outcome of decoding a tool call's arguments
+#[derive(Debug, Clone, PartialEq)]
+pub enum DecodedArguments {
+    /// A JSON object, as sent
+    Valid(serde_json::Value),
+    /// A JSON object after repairing malformed or multiply-encoded JSON
+    Repaired(serde_json::Value),
+    /// Not recoverable as an object; holds the reason
+    Invalid(String),
+}
+
+impl DecodedArguments {
+    pub fn into_value(self) -> Option<serde_json::Value> {
+        match self {
+            DecodedArguments::Valid(value) | DecodedArguments::Repaired(value) => Some(value),
+            DecodedArguments::Invalid(_) => None,
+        }
+    }
+
+    /// The warning for the caller when the arguments weren't valid as sent
+    pub fn warning(&self, id: &str, name: &str) -> Option<ToolCallWarning> {
+        let (kind, errors) = match self {
+            DecodedArguments::Valid(_) => return None,
+            DecodedArguments::Repaired(_) => (ToolCallWarningKind::ArgumentsRepaired, Vec::new()),
+            DecodedArguments::Invalid(error) => (ToolCallWarningKind::ArgumentsInvalid, vec![error.clone()]),
+        };
+        Some(ToolCallWarning {
+            id: id.to_string(),
+            name: name.to_string(),
+            kind,
+            errors,
+        })
+    }
+}
+
+/// What was wrong with a tool call the model returned
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ToolCallWarningKind {
+    /// The arguments were malformed JSON and have been repaired
+    ArgumentsRepaired,
+    /// The arguments could not be decoded and were sent as `{}`
+    ArgumentsInvalid,
+    /// The call doesn't match the request's tools (`tool_validation` `warn` mode)
+    InvalidToolCall,
+}
+
+/// A problem with one tool call in a completion
+///
+/// `CompletionResponse` has no field for these, and notes in the message
+/// content would be mistaken for model output, so they are reported in a
+/// separate `tool_call_warnings` field (see `to_vec_with_warnings`).
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ToolCallWarning {
+    pub id: String,
+    /// The caller's name for the tool
+    pub name: String,
+    pub kind: ToolCallWarningKind,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<String>,
+}
+
+impl std::fmt::Display for ToolCallWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let kind = match self.kind {
+            ToolCallWarningKind::ArgumentsRepaired => "arguments were malformed JSON and have been repaired",
+            ToolCallWarningKind::ArgumentsInvalid => "arguments could not be decoded and were sent as {}",
+            ToolCallWarningKind::InvalidToolCall => "invalid tool call",
+        };
+        write!(f, "Tool call {} ({}): {}", self.id, self.name, kind)?;
+        if !self.errors.is_empty() {
+            write!(f, ": {}", self.errors.join("; "))?;
+        }
+        Ok(())
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct OpenAIAudio {
     pub id: String,
@@ -428,12 +714,43 @@ pub struct OpenAIAudio {
 
 // === HELPER FUNCTIONS ===
 
-fn extract_text_from_content_items(items: &[ContentItem]) -> String {
+/// Read one content part, keeping the text of part types we don't model
+/// (e.g. `output_text`) and dropping empty strings and parts without text
+fn content_part(part: serde_json::Value) -> Option<OpenAIContentItem> {
+    match part {
+        serde_json::Value::String(text) if text.is_empty() => None,
+        serde_json::Value::String(text) => Some(OpenAIContentItem::Text { text }),
+        part => serde_json::from_value::<OpenAIContentItem>(part.clone())
+            .ok()
+            .or_else(|| {
+                let text = part.get("text")?.as_str()?.to_string();
+                Some(OpenAIContentItem::Text { text })
+            }),
+    }
+}
+
+fn image_content_item(item: &ContentItem) -> Option<OpenAIContentItem> {
+    match item {
+        ContentItem::Image(Some(image)) => Some(OpenAIContentItem::ImageUrl {
+            image_url: OpenAIImageUrl::from_image(image),
+        }),
+        _ => None,
+    }
+}
+
+fn image_placeholder(mime_type: &str) -> String {
+    format!("[Image: {}]", mime_type)
+}
+
+pub(crate) fn extract_text_from_content_items(items: &[ContentItem]) -> String {
     items
         .iter()
         .filter_map(|item| match item {
             ContentItem::Text(Some(text_content)) => Some(text_content.text.clone()),
             ContentItem::Text(None) => Some("[Empty text]".to_string()),
+            ContentItem::Image(Some(image)) => Some(image_placeholder(
+                image.mime_type.as_deref().unwrap_or(DEFAULT_IMAGE_MIME_TYPE),
+            )),
             _ => None,
         })
         .collect::<Vec<_>>()
@@ -475,11 +792,239 @@ mod tests {
             content: vec![MessageContent::Text("Hello".to_string())],
         };
 
-        let openai_msg = MessageConverter::to_openai_message(original.clone());
-        let converted_back = MessageConverter::from_openai_message(openai_msg);
+        let mut openai_msgs = MessageConverter::to_openai_message(original.clone());
+        assert_eq!(openai_msgs.len(), 1);
+        let converted_back = MessageConverter::from_openai_message(openai_msgs.remove(0), &ToolNames::default());
 
         // Check that essential data is preserved
-        assert_eq!(converted_back.role, original.role);
+        assert!(matches!(converted_back.role, MessageRole::User));
         assert_eq!(converted_back.content.len(), original.content.len());
     }
+
+    fn tool_conversation() -> Vec<Message> {
+        let tool_use = |id: &str, city: &str| {
+            MessageContent::ToolUse(ToolUse {
+                id: id.to_string(),
+                name: "get_weather".to_string(),
+                input: serde_json::to_vec(&serde_json::json!({ "city": city })).unwrap(),
+            })
+        };
+
+        vec![
+            Message {
+                role: MessageRole::User,
+                content: vec![MessageContent::Text(
+                    "What's the weather in Paris and Tokyo?".to_string(),
+                )],
+            },
+            Message {
+                role: MessageRole::Assistant,
+                content: vec![
+                    MessageContent::Text("Checking both cities.".to_string()),
+                    tool_use("call_1", "Paris"),
+                    tool_use("call_2", "Tokyo"),
+                ],
+            },
+            Message {
+                role: MessageRole::User,
+                content: vec![
+                    MessageContent::ToolResult(ToolResult {
+                        tool_use_id: "call_1".to_string(),
+                        content: r#"[{"Text": {"type_": "text", "text": "18°C, cloudy"}}]"#.as_bytes().to_vec(),
+                        is_error: false,
+                    }),
+                    MessageContent::ToolResult(ToolResult {
+                        tool_use_id: "call_2".to_string(),
+                        content: b"Service unavailable".to_vec(),
+                        is_error: true,
+                    }),
+                    MessageContent::Text("Summarize please.".to_string()),
+                ],
+            },
+        ]
+    }
+
+    fn serialize_conversation(format: &ContentFormat) -> serde_json::Value {
+        tool_conversation()
+            .into_iter()
+            .flat_map(MessageConverter::to_openai_message)
+            .map(|message| message.serialize_for_format(format))
+            .collect()
+    }
+
+    #[test]
+    fn test_tool_conversation_golden_array() {
+        let expected: serde_json::Value =
+            serde_json::from_str(include_str!("../../tests/fixtures/tool_conversation.array.json"))
+                .unwrap();
+        assert_eq!(serialize_conversation(&ContentFormat::Array), expected);
+    }
+
+    #[test]
+    fn test_tool_conversation_golden_string() {
+        let expected: serde_json::Value =
+            serde_json::from_str(include_str!("../../tests/fixtures/tool_conversation.string.json"))
+                .unwrap();
+        assert_eq!(serialize_conversation(&ContentFormat::String), expected);
+    }
+
+    #[test]
+    fn test_tool_call_message_roundtrip() {
+        let fixture = include_str!("../../tests/fixtures/tool_call_message.json");
+        let openai_msg: OpenAIMessage = serde_json::from_str(fixture).unwrap();
+
+        let message = MessageConverter::from_openai_message(openai_msg, &ToolNames::default());
+        let input = match &message.content[..] {
+            [MessageContent::ToolUse(tool_use)] => {
+                serde_json::from_slice::<serde_json::Value>(&tool_use.input).unwrap()
+            }
+            other => panic!("expected a single tool use, got {:?}", other),
+        };
+        assert_eq!(input, serde_json::json!({ "city": "Paris", "units": "celsius" }));
+
+        let mut expected: serde_json::Value = serde_json::from_str(fixture).unwrap();
+        expected.as_object_mut().unwrap().remove("content");
+        let messages = MessageConverter::to_openai_message(message);
+        assert_eq!(messages.len(), 1);
+        assert_eq!(messages[0].serialize_for_format(&ContentFormat::Array), expected);
+    }
+
+    #[test]
+    fn test_tool_result_images() {
+        let message = Message {
+            role: MessageRole::User,
+            content: vec![MessageContent::ToolResult(ToolResult {
+                tool_use_id: "call_1".to_string(),
+                content: serde_json::to_vec(&serde_json::json!([
+                    { "Text": { "type_": "text", "text": "Screenshot taken" } },
+                    { "Image": {
+                        "type_": "image",
+                        "data": "iVBORw0KGgo=",
+                        "mime_type": "image/jpeg",
+                        "meta": serde_json::to_vec(&serde_json::json!({ "detail": "low" })).unwrap()
+                    } }
+                ]))
+                .unwrap(),
+                is_error: false,
+            })],
+        };
+
+        let messages = MessageConverter::to_openai_message(message);
+        assert_eq!(messages.len(), 2);
+
+        let array: Vec<serde_json::Value> = messages
+            .iter()
+            .map(|m| m.serialize_for_format(&ContentFormat::Array))
+            .collect();
+        assert_eq!(array[0]["content"], "Screenshot taken\n[Image: image/jpeg]");
+        assert_eq!(array[1]["role"], "user");
+        assert_eq!(
+            array[1]["content"][1],
+            serde_json::json!({
+                "type": "image_url",
+                "image_url": { "url": "data:image/jpeg;base64,iVBORw0KGgo=", "detail": "low" }
+            })
+        );
+
+        let string = messages[1].serialize_for_format(&ContentFormat::String);
+        assert_eq!(
+            string["content"],
+            "Images returned by tool call call_1:\n[Image: image/jpeg]"
+        );
+    }
+
+    #[test]
+    fn test_tool_call_argument_decoding() {
+        crate::logging::configure(&crate::types::state::LoggingConfig {
+            level: crate::types::state::LogLevel::Off,
+            max_body_chars: 0,
+        });
+
+        let call = |id: &str, arguments: serde_json::Value| OpenAIToolCall {
+            id: id.to_string(),
+            tool_type: "function".to_string(),
+            function: OpenAIFunctionCall {
+                name: "search".to_string(),
+                arguments,
+            },
+        };
+        let message = OpenAIMessage {
+            role: "assistant".to_string(),
+            content: None,
+            tool_calls: Some(vec![
+                call("call_1", serde_json::json!(r#"{"q": "moon"}"#)),
+                call("call_2", serde_json::json!({ "q": "sun" })),
+                call("call_3", serde_json::json!("{'q': 'mars',}")),
+                call("call_4", serde_json::json!("search for venus")),
+            ]),
+            tool_call_id: None,
+            name: None,
+            audio: None,
+            refusal: None,
+        };
+
+        let message = MessageConverter::from_openai_message(message, &ToolNames::default());
+        let inputs: Vec<serde_json::Value> = message
+            .content
+            .iter()
+            .filter_map(|content| match content {
+                MessageContent::ToolUse(tool_use) => serde_json::from_slice(&tool_use.input).ok(),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            inputs,
+            [
+                serde_json::json!({ "q": "moon" }),
+                serde_json::json!({ "q": "sun" }),
+                serde_json::json!({ "q": "mars" }),
+                serde_json::json!({}),
+            ]
+        );
+
+        // The message only holds the model's output
+        assert!(message.content.iter().all(|content| matches!(content, MessageContent::ToolUse(_))));
+
+        // Arguments encoded twice are decoded, and count as repaired
+        let twice = call("call_5", serde_json::json!(r#""{\"q\": \"pluto\"}""#));
+        assert_eq!(
+            twice.function.decode_arguments(),
+            DecodedArguments::Repaired(serde_json::json!({ "q": "pluto" }))
+        );
+        let warnings: Vec<Option<ToolCallWarningKind>> = [
+            call("call_3", serde_json::json!("{'q': 'mars',}")),
+            call("call_4", serde_json::json!("search for venus")),
+            call("call_6", serde_json::json!(r#""search for venus""#)),
+            call("call_7", serde_json::json!("[1, 2]")),
+            call("call_8", serde_json::json!({ "q": "sun" })),
+        ]
+        .iter()
+        .map(|call| call.function.decode_arguments().warning(&call.id, "search").map(|warning| warning.kind))
+        .collect();
+        assert_eq!(
+            warnings,
+            [
+                Some(ToolCallWarningKind::ArgumentsRepaired),
+                Some(ToolCallWarningKind::ArgumentsInvalid),
+                Some(ToolCallWarningKind::ArgumentsInvalid),
+                Some(ToolCallWarningKind::ArgumentsInvalid),
+                None,
+            ]
+        );
+
+        // Warnings travel next to the completion, not in it
+        let warning = twice.function.decode_arguments().warning("call_5", "search").unwrap();
+        let bytes = crate::types::response::to_vec_with_warnings(
+            &serde_json::json!({ "Completion": { "id": "c1" } }),
+            &[warning],
+        )
+        .unwrap();
+        assert_eq!(
+            serde_json::from_slice::<serde_json::Value>(&bytes).unwrap(),
+            serde_json::json!({ "Completion": {
+                "id": "c1",
+                "tool_call_warnings": [{ "id": "call_5", "name": "search", "kind": "arguments_repaired" }]
+            } })
+        );
+    }
 }
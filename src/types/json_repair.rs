@@ -0,0 +1,125 @@
+// Best-effort repair of malformed JSON in tool-call arguments
+//
+// Models occasionally emit arguments that are almost JSON: wrapped in a
+// markdown code fence, with single-quoted strings, with trailing commas, or
+// cut off by `max_tokens` before the closing braces. `repair_json` fixes
+// those mistakes in one pass and leaves anything else to fail parsing.
+
+/// Parse `input` after fixing common mistakes, or `None` if it still isn't JSON
+pub fn repair_json(input: &str) -> Option<serde_json::Value> {
+    let text = strip_code_fence(input.trim());
+
+    let mut out = String::with_capacity(text.len() + 8);
+    let mut closers: Vec<char> = Vec::new();
+    // The quote character of the string being copied, if any
+    let mut in_string: Option<char> = None;
+    let mut chars = text.chars();
+
+    while let Some(c) = chars.next() {
+        if let Some(quote) = in_string {
+            match c {
+                '\\' => match chars.next() {
+                    Some('\'') => out.push('\''),
+                    Some(escaped) => {
+                        out.push('\\');
+                        out.push(escaped);
+                    }
+                    // A truncated escape is dropped
+                    None => {}
+                },
+                c if c == quote => {
+                    out.push('"');
+                    in_string = None;
+                }
+                // Only reachable inside a single-quoted string
+                '"' => out.push_str("\\\""),
+                '\n' => out.push_str("\\n"),
+                c => out.push(c),
+            }
+            continue;
+        }
+
+        match c {
+            '"' | '\'' => {
+                out.push('"');
+                in_string = Some(c);
+            }
+            '{' => {
+                out.push(c);
+                closers.push('}');
+            }
+            '[' => {
+                out.push(c);
+                closers.push(']');
+            }
+            '}' | ']' => {
+                trim_trailing_comma(&mut out);
+                if closers.last() == Some(&c) {
+                    closers.pop();
+                }
+                out.push(c);
+            }
+            c => out.push(c),
+        }
+    }
+
+    // Truncated output: finish the open string and value, then close every bracket
+    if in_string.is_some() {
+        out.push('"');
+    }
+    while let Some(closer) = closers.pop() {
+        trim_trailing_comma(&mut out);
+        if out.trim_end().ends_with(':') {
+            out.push_str(" null");
+        }
+        out.push(closer);
+    }
+
+    serde_json::from_str(&out).ok()
+}
+
+/// The body of a markdown code fence (```` ```json ... ``` ````), or the input unchanged
+fn strip_code_fence(text: &str) -> &str {
+    let Some(rest) = text.strip_prefix("```") else {
+        return text;
+    };
+    // Skip the language tag on the opening line
+    let body = rest.split_once('\n').map_or("", |(_, body)| body);
+    body.trim_end().strip_suffix("```").unwrap_or(body).trim()
+}
+
+fn trim_trailing_comma(out: &mut String) {
+    let trimmed = out.trim_end().len();
+    if out[..trimmed].ends_with(',') {
+        out.truncate(trimmed - 1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_repair_common_mistakes() {
+        assert_eq!(
+            repair_json("```json\n{\"city\": \"Paris\"}\n```"),
+            Some(json!({ "city": "Paris" }))
+        );
+        assert_eq!(
+            repair_json("{'city': 'Paris', 'note': 'say \"hi\"', 'it\\'s': true}"),
+            Some(json!({ "city": "Paris", "note": "say \"hi\"", "it's": true }))
+        );
+        assert_eq!(
+            repair_json(r#"{"cities": ["Paris", "Rome",], "limit": 3,}"#),
+            Some(json!({ "cities": ["Paris", "Rome"], "limit": 3 }))
+        );
+        // Cut off by max_tokens
+        assert_eq!(
+            repair_json(r#"{"query": {"text": "moon landing", "tags": ["nasa", "apol"#),
+            Some(json!({ "query": { "text": "moon landing", "tags": ["nasa", "apol"] } }))
+        );
+        assert_eq!(repair_json(r#"{"city": "Paris", "unit":"#), Some(json!({ "city": "Paris", "unit": null })));
+        assert_eq!(repair_json("get_weather(city=Paris)"), None);
+    }
+}
@@ -0,0 +1,312 @@
+// A subset of JSON Schema for checking tool-call arguments
+//
+// Covers the keywords tool schemas use in practice: `type`, `enum`, `const`,
+// `properties`, `required`, `additionalProperties`, `items`, length and range
+// limits, `allOf`/`anyOf`/`oneOf` and local `$ref`s. Other keywords (such as
+// `format` and `pattern`) are ignored, so a schema we only partly understand
+// never rejects arguments a full validator would accept.
+
+use serde_json::{Map, Value};
+
+/// How many `$ref`s may be followed in a row for one value
+const MAX_REF_DEPTH: usize = 32;
+
+/// Check `value` against `schema`, returning one message per problem
+///
+/// Messages start with the path of the offending value, e.g.
+/// `arguments.cities[1]: expected string, got number`.
+pub fn validate(schema: &Value, value: &Value) -> Vec<String> {
+    let mut errors = Vec::new();
+    check(schema, schema, value, "arguments", &[], &mut errors);
+    errors
+}
+
+/// `refs` are the `$ref`s followed for this value. Meeting one again means the
+/// schema is circular (`{"$ref": "#"}`), which would otherwise never end. The
+/// list starts empty for each nested value, so recursive schemas still
+/// validate nested data.
+fn check<'a>(
+    root: &'a Value,
+    schema: &'a Value,
+    value: &Value,
+    path: &str,
+    refs: &[&'a str],
+    errors: &mut Vec<String>,
+) {
+    let schema = match schema {
+        Value::Object(schema) => schema,
+        Value::Bool(false) => {
+            errors.push(format!("{}: no value is allowed here", path));
+            return;
+        }
+        _ => return,
+    };
+
+    if let Some(reference) = schema.get("$ref").and_then(Value::as_str) {
+        if refs.contains(&reference) || refs.len() >= MAX_REF_DEPTH {
+            errors.push(format!("{}: schema $ref '{}' is circular", path, reference));
+            return;
+        }
+        if let Some(target) = resolve_ref(root, reference) {
+            let refs = [refs, &[reference]].concat();
+            check(root, target, value, path, &refs, errors);
+        }
+    }
+
+    if let Some(types) = schema.get("type") {
+        let allowed: Vec<&str> = match types {
+            Value::String(name) => vec![name.as_str()],
+            Value::Array(names) => names.iter().filter_map(Value::as_str).collect(),
+            _ => Vec::new(),
+        };
+        if !allowed.is_empty() && !allowed.iter().any(|name| type_matches(name, value)) {
+            errors.push(format!(
+                "{}: expected {}, got {}",
+                path,
+                allowed.join(" or "),
+                type_name(value)
+            ));
+            // Everything else assumes the right type
+            return;
+        }
+    }
+
+    if let Some(Value::Array(options)) = schema.get("enum") {
+        if !options.contains(value) {
+            let options: Vec<String> = options.iter().map(Value::to_string).collect();
+            errors.push(format!("{}: expected one of {}, got {}", path, options.join(", "), value));
+        }
+    }
+    if let Some(expected) = schema.get("const") {
+        if expected != value {
+            errors.push(format!("{}: expected {}, got {}", path, expected, value));
+        }
+    }
+
+    match value {
+        Value::Object(object) => check_object(root, schema, object, path, errors),
+        Value::Array(items) => {
+            if let Some(item_schema) = schema.get("items") {
+                for (index, item) in items.iter().enumerate() {
+                    check(root, item_schema, item, &format!("{}[{}]", path, index), &[], errors);
+                }
+            }
+            check_count(schema, "minItems", "maxItems", items.len(), "items", path, errors);
+        }
+        Value::String(text) => {
+            check_count(schema, "minLength", "maxLength", text.chars().count(), "characters", path, errors);
+        }
+        Value::Number(number) => check_range(schema, number.as_f64().unwrap_or_default(), path, errors),
+        _ => {}
+    }
+
+    check_combinators(root, schema, value, path, refs, errors);
+}
+
+fn check_object(root: &Value, schema: &Map<String, Value>, object: &Map<String, Value>, path: &str, errors: &mut Vec<String>) {
+    for name in schema
+        .get("required")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(Value::as_str)
+    {
+        if !object.contains_key(name) {
+            errors.push(format!("{}: missing required property '{}'", path, name));
+        }
+    }
+
+    let properties = schema.get("properties").and_then(Value::as_object);
+    for (name, property) in object {
+        let property_path = format!("{}.{}", path, name);
+        match properties.and_then(|properties| properties.get(name)) {
+            Some(property_schema) => check(root, property_schema, property, &property_path, &[], errors),
+            None => match schema.get("additionalProperties") {
+                Some(Value::Bool(false)) => {
+                    errors.push(format!("{}: unexpected property '{}'", path, name));
+                }
+                Some(additional) => check(root, additional, property, &property_path, &[], errors),
+                None => {}
+            },
+        }
+    }
+}
+
+fn check_combinators<'a>(
+    root: &'a Value,
+    schema: &'a Map<String, Value>,
+    value: &Value,
+    path: &str,
+    refs: &[&'a str],
+    errors: &mut Vec<String>,
+) {
+    let branches = |keyword: &str| -> Vec<Vec<String>> {
+        schema
+            .get(keyword)
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .map(|branch| {
+                let mut branch_errors = Vec::new();
+                check(root, branch, value, path, refs, &mut branch_errors);
+                branch_errors
+            })
+            .collect()
+    };
+
+    for branch_errors in branches("allOf") {
+        errors.extend(branch_errors);
+    }
+
+    let any_of = branches("anyOf");
+    if !any_of.is_empty() && any_of.iter().all(|errors| !errors.is_empty()) {
+        errors.push(format!("{}: does not match any of the allowed schemas", path));
+    }
+
+    let one_of = branches("oneOf");
+    if !one_of.is_empty() {
+        match one_of.iter().filter(|errors| errors.is_empty()).count() {
+            1 => {}
+            0 => errors.push(format!("{}: does not match any of the allowed schemas", path)),
+            _ => errors.push(format!("{}: matches more than one of the allowed schemas", path)),
+        }
+    }
+}
+
+fn check_count(
+    schema: &Map<String, Value>,
+    min_keyword: &str,
+    max_keyword: &str,
+    count: usize,
+    unit: &str,
+    path: &str,
+    errors: &mut Vec<String>,
+) {
+    let limit = |keyword: &str| schema.get(keyword).and_then(Value::as_u64);
+    if let Some(min) = limit(min_keyword) {
+        if (count as u64) < min {
+            errors.push(format!("{}: expected at least {} {}, got {}", path, min, unit, count));
+        }
+    }
+    if let Some(max) = limit(max_keyword) {
+        if count as u64 > max {
+            errors.push(format!("{}: expected at most {} {}, got {}", path, max, unit, count));
+        }
+    }
+}
+
+fn check_range(schema: &Map<String, Value>, number: f64, path: &str, errors: &mut Vec<String>) {
+    let bounds = [
+        ("minimum", ">="),
+        ("maximum", "<="),
+        ("exclusiveMinimum", ">"),
+        ("exclusiveMaximum", "<"),
+    ];
+    for (keyword, comparison) in bounds {
+        // Draft 4 boolean `exclusiveMinimum`/`exclusiveMaximum` are skipped here
+        let Some(bound) = schema.get(keyword).and_then(Value::as_f64) else {
+            continue;
+        };
+        let in_range = match comparison {
+            ">=" => number >= bound,
+            "<=" => number <= bound,
+            ">" => number > bound,
+            _ => number < bound,
+        };
+        if !in_range {
+            errors.push(format!("{}: expected a number {} {}, got {}", path, comparison, bound, number));
+        }
+    }
+}
+
+/// Follow a `$ref` within the same schema (`#`, `#/$defs/...`, `#/definitions/...`)
+fn resolve_ref<'a>(root: &'a Value, reference: &str) -> Option<&'a Value> {
+    root.pointer(reference.strip_prefix('#')?)
+}
+
+fn type_matches(name: &str, value: &Value) -> bool {
+    match name {
+        "object" => value.is_object(),
+        "array" => value.is_array(),
+        "string" => value.is_string(),
+        "boolean" => value.is_boolean(),
+        "null" => value.is_null(),
+        "number" => value.is_number(),
+        "integer" => value.is_i64() || value.is_u64() || value.as_f64().is_some_and(|n| n.fract() == 0.0),
+        // A type we don't know about can't be checked
+        _ => true,
+    }
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_schema_subset() {
+        let schema = json!({
+            "type": "object",
+            "properties": {
+                "location": { "type": "string", "minLength": 2 },
+                "unit": { "enum": ["celsius", "fahrenheit"] },
+                "days": { "type": "integer", "minimum": 1, "maximum": 14 },
+                "stops": { "type": "array", "items": { "$ref": "#/$defs/stop" }, "maxItems": 2 },
+                "when": { "anyOf": [{ "type": "string" }, { "type": "null" }] }
+            },
+            "required": ["location"],
+            "additionalProperties": false,
+            "$defs": {
+                "stop": { "type": "object", "properties": { "city": { "type": "string" } }, "required": ["city"] }
+            }
+        });
+
+        let valid = json!({ "location": "Paris", "unit": "celsius", "days": 3.0, "stops": [{ "city": "Lyon" }], "when": null });
+        assert_eq!(validate(&schema, &valid), Vec::<String>::new());
+
+        let invalid = json!({ "unit": "kelvin", "days": 30, "stops": [{ "city": 4 }, {}, {}], "when": 5, "extra": true });
+        assert_eq!(
+            validate(&schema, &invalid),
+            [
+                "arguments: missing required property 'location'",
+                "arguments.days: expected a number <= 14, got 30",
+                "arguments: unexpected property 'extra'",
+                "arguments.stops[0].city: expected string, got number",
+                "arguments.stops[1]: missing required property 'city'",
+                "arguments.stops[2]: missing required property 'city'",
+                "arguments.stops: expected at most 2 items, got 3",
+                "arguments.unit: expected one of \"celsius\", \"fahrenheit\", got \"kelvin\"",
+                "arguments.when: does not match any of the allowed schemas",
+            ]
+        );
+
+        assert_eq!(validate(&json!({ "type": "object" }), &json!("Paris")), ["arguments: expected object, got string"]);
+
+        // Circular references fail instead of recursing forever
+        let circular = json!({ "$ref": "#/$defs/a", "$defs": { "a": { "allOf": [{ "$ref": "#/$defs/a" }] } } });
+        assert_eq!(
+            validate(&circular, &json!({})),
+            ["arguments: schema $ref '#/$defs/a' is circular"]
+        );
+        assert_eq!(validate(&json!({ "$ref": "#" }), &json!({})).len(), 1);
+
+        // Recursive schemas still validate nested data
+        let tree = json!({
+            "type": "object",
+            "properties": { "children": { "type": "array", "items": { "$ref": "#" } } }
+        });
+        let deep = (0..40).fold(json!({}), |child, _| json!({ "children": [child] }));
+        assert_eq!(validate(&tree, &deep), Vec::<String>::new());
+    }
+}
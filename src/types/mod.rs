@@ -1,7 +1,19 @@
+pub mod admin;
+pub mod anthropic;
 pub mod api;
+pub mod circuit_breaker;
 pub mod conversion;
+pub mod json_repair;
+pub mod json_schema;
+pub mod model_policy;
+pub mod pricing;
 pub mod response;
 pub mod state;
+pub mod stream;
+pub mod tool_names;
+pub mod tool_schema;
+pub mod tool_validation;
+pub mod usage;
 
 // Use the improved API types
 pub use api::*;
@@ -10,8 +22,16 @@ pub use api::*;
 pub use conversion::{
     MessageConverter, OpenAIContent, OpenAIContentItem, OpenAIMessage,
     OpenAIToolCall, OpenAIFunctionCall, OpenAIAudio, OpenAIImageUrl,
-    ToolResultParser
+    ToolResultParser, DecodedArguments, ToolCallWarning, ToolCallWarningKind
 };
 
+pub use circuit_breaker::*;
+pub use model_policy::*;
+pub use pricing::*;
 pub use response::*;
 pub use state::*;
+pub use stream::*;
+pub use tool_names::*;
+pub use tool_schema::*;
+pub use tool_validation::*;
+pub use usage::*;
@@ -0,0 +1,103 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// How strictly requested models are checked against the provider's catalog
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ModelValidationMode {
+    /// Reject models the provider doesn't list
+    #[default]
+    Strict,
+    /// Log unknown models but forward the request anyway
+    Warn,
+    /// Skip the catalog entirely (for servers whose models can't be enumerated)
+    Passthrough,
+}
+
+/// Per-deployment rules for which models may be requested
+///
+/// Allow and deny entries match exactly, or by prefix when they end in `*`
+/// (e.g. `kimi-*`). They are enforced in every mode; `mode` only controls the
+/// catalog check.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct ModelPolicy {
+    #[serde(default)]
+    pub mode: ModelValidationMode,
+
+    /// If non-empty, only these models may be used
+    #[serde(default)]
+    pub allow: Vec<String>,
+
+    /// Models that may never be used
+    #[serde(default)]
+    pub deny: Vec<String>,
+
+    /// Alternative names mapped to real model IDs (e.g. `kimi-latest`)
+    #[serde(default)]
+    pub aliases: HashMap<String, String>,
+}
+
+/// Upper bound on alias chains, to stop cycles in a misconfigured policy
+const MAX_ALIAS_DEPTH: usize = 8;
+
+impl ModelPolicy {
+    /// Follow alias mappings to the model ID that will be sent to the provider
+    pub fn resolve_alias(&self, model: &str) -> String {
+        let mut resolved = model.to_string();
+        for _ in 0..MAX_ALIAS_DEPTH {
+            match self.aliases.get(&resolved) {
+                Some(target) if *target != resolved => resolved = target.clone(),
+                _ => break,
+            }
+        }
+        resolved
+    }
+
+    /// Check a resolved model against the allow and deny lists
+    pub fn check_access(&self, model: &str) -> Result<(), String> {
+        if self.deny.iter().any(|pattern| matches_pattern(pattern, model)) {
+            return Err(format!("Model '{}' is not permitted by this proxy", model));
+        }
+
+        if !self.allow.is_empty() && !self.allow.iter().any(|pattern| matches_pattern(pattern, model)) {
+            return Err(format!(
+                "Model '{}' is not permitted by this proxy. Allowed models: {}",
+                model,
+                self.allow.join(", ")
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+pub(crate) fn matches_pattern(pattern: &str, model: &str) -> bool {
+    match pattern.strip_suffix('*') {
+        Some(prefix) => model.starts_with(prefix),
+        None => pattern == model,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_alias_resolution_and_access() {
+        let policy = ModelPolicy {
+            mode: ModelValidationMode::Strict,
+            allow: vec!["kimi-*".to_string()],
+            deny: vec!["kimi-k2-0707-preview".to_string()],
+            aliases: HashMap::from([
+                ("kimi-latest".to_string(), "kimi-k2-0711-preview".to_string()),
+                ("loop".to_string(), "loop".to_string()),
+            ]),
+        };
+
+        assert_eq!(policy.resolve_alias("kimi-latest"), "kimi-k2-0711-preview");
+        assert_eq!(policy.resolve_alias("loop"), "loop");
+        assert!(policy.check_access("kimi-k2-0711-preview").is_ok());
+        assert!(policy.check_access("kimi-k2-0707-preview").is_err());
+        assert!(policy.check_access("moonshot-v1-8k").is_err());
+    }
+}
@@ -0,0 +1,166 @@
+// Cost estimation
+//
+// Prices are USD per million tokens, the unit providers publish them in. The
+// cost of a completion is estimated from the usage the provider reports, so
+// it can differ slightly from the bill (e.g. cached input discounts).
+
+use crate::bindings::colinrozzi::genai_types::types::ModelPricing;
+use crate::types::api::OpenAIUsage;
+use crate::types::model_policy::matches_pattern;
+use crate::types::response::OpenAIModelInfo;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Which spend a budget is compared against
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum BudgetPeriod {
+    /// Everything spent since the actor started
+    #[default]
+    Total,
+    /// Spend in the current UTC day
+    Daily,
+    /// Spend in the current UTC month
+    Monthly,
+}
+
+impl BudgetPeriod {
+    pub fn describe(&self) -> &'static str {
+        match self {
+            BudgetPeriod::Total => "in total",
+            BudgetPeriod::Daily => "today",
+            BudgetPeriod::Monthly => "this month",
+        }
+    }
+}
+
+/// A hard spending limit across all callers
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Budget {
+    pub max_usd: f64,
+    #[serde(default)]
+    pub period: BudgetPeriod,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct PricingConfig {
+    /// Prices by model ID, or by prefix when the key ends in `*`
+    ///
+    /// These take precedence over the built-in prices of known models.
+    #[serde(default)]
+    pub prices: HashMap<String, ModelPricing>,
+
+    /// Completions are rejected once estimated spend reaches this budget
+    #[serde(default)]
+    pub budget: Option<Budget>,
+}
+
+impl PricingConfig {
+    /// The price of a model: configured first, then built-in
+    ///
+    /// Providers often answer with a dated model ID (`gpt-4-0613`), so
+    /// built-in prices also apply to a known ID followed by a date. Other
+    /// suffixes name different models (`gpt-4o` is not `gpt-4`) and get no
+    /// built-in price.
+    pub fn price_for(&self, model: &str) -> Option<ModelPricing> {
+        if let Some(pricing) = self.prices.get(model) {
+            return Some(*pricing);
+        }
+
+        let configured = self
+            .prices
+            .iter()
+            .filter(|(pattern, _)| pattern.ends_with('*') && matches_pattern(pattern, model))
+            .max_by_key(|(pattern, _)| pattern.len())
+            .map(|(_, pricing)| *pricing);
+
+        configured.or_else(|| {
+            OpenAIModelInfo::get_available_models()
+                .into_iter()
+                .filter(|known| {
+                    model
+                        .strip_prefix(known.id.as_str())
+                        .is_some_and(|suffix| suffix.is_empty() || is_date_suffix(suffix))
+                })
+                .max_by_key(|known| known.id.len())
+                .and_then(|known| known.pricing)
+        })
+    }
+
+    /// Fill in the price of every model in a catalog
+    pub fn apply(&self, models: &mut [OpenAIModelInfo]) {
+        for model in models {
+            model.pricing = self.price_for(&model.id);
+        }
+    }
+}
+
+/// Whether `suffix` is a model snapshot date: `-0613` or `-2024-04-09`
+fn is_date_suffix(suffix: &str) -> bool {
+    let digits = |part: &str, len: usize| part.len() == len && part.bytes().all(|b| b.is_ascii_digit());
+    let parts: Vec<&str> = suffix.split('-').collect();
+    match parts.as_slice() {
+        ["", short] => digits(short, 4) || digits(short, 8),
+        ["", year, month, day] => digits(year, 4) && digits(month, 2) && digits(day, 2),
+        _ => false,
+    }
+}
+
+/// Estimated cost of a completion in USD
+pub fn completion_cost(pricing: &ModelPricing, usage: &OpenAIUsage) -> f64 {
+    (usage.prompt_tokens as f64 * pricing.input_price
+        + usage.completion_tokens as f64 * pricing.output_price)
+        / 1_000_000.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::response::OpenAIError;
+    use crate::types::usage::UsageLedger;
+
+    #[test]
+    fn test_prices_and_budget() {
+        let config = PricingConfig {
+            prices: HashMap::from([
+                ("kimi-*".to_string(), ModelPricing { input_price: 1.0, output_price: 4.0 }),
+                ("my-llama".to_string(), ModelPricing { input_price: 0.0, output_price: 0.0 }),
+            ]),
+            budget: Some(Budget { max_usd: 0.01, period: BudgetPeriod::Total }),
+        };
+
+        let kimi = config.price_for("kimi-k2-0711-preview").unwrap();
+        assert_eq!((kimi.input_price, kimi.output_price), (1.0, 4.0));
+        assert_eq!(config.price_for("my-llama").unwrap().output_price, 0.0);
+        // Dated IDs use the longest matching built-in model
+        assert_eq!(config.price_for("gpt-4-turbo-2024-04-09").unwrap().input_price, 10.0);
+        assert_eq!(config.price_for("gpt-4-0613").unwrap().input_price, 30.0);
+        assert!(config.price_for("llama-3-70b").is_none());
+        // Longer names are different models with their own prices, not dated variants
+        let price = |model: &str| config.price_for(model).map(|p| (p.input_price, p.output_price));
+        assert_eq!(price("gpt-4o"), Some((2.5, 10.0)));
+        assert_eq!(price("gpt-4o-2024-08-06"), Some((2.5, 10.0)));
+        assert_eq!(price("gpt-4o-mini"), Some((0.15, 0.6)));
+        assert_eq!(price("gpt-4o-mini-2024-07-18"), Some((0.15, 0.6)));
+        assert_eq!(price("gpt-4.1"), Some((2.0, 8.0)));
+        assert!(config.price_for("gpt-4-turbo-preview").is_none());
+
+        let usage = OpenAIUsage {
+            prompt_tokens: 2000,
+            completion_tokens: 1000,
+            total_tokens: 3000,
+        };
+        let cost = completion_cost(&kimi, &usage);
+        assert!((cost - 0.006).abs() < 1e-12);
+
+        let budget = config.budget.as_ref().unwrap();
+        let mut ledger = UsageLedger::default();
+        ledger.record("planner", "kimi-k2-0711-preview", &usage, cost, 0);
+        assert!(ledger.check_budget(budget, 0).is_ok());
+        ledger.record("reviewer", "kimi-k2-0711-preview", &usage, cost, 0);
+        assert!(matches!(
+            ledger.check_budget(budget, 0),
+            Err(OpenAIError::BudgetExceeded { retry_after: None, .. })
+        ));
+    }
+}
@@ -3,15 +3,29 @@ use crate::types::conversion::*;
 use crate::bindings::colinrozzi::genai_types::types::{
     CompletionResponse, ModelInfo, ModelPricing, StopReason,
 };
-use serde::{Deserialize, Serialize};
+use crate::types::pricing::BudgetPeriod;
+use crate::types::state::ErrorFormat;
+use crate::types::tool_names::ToolNames;
+use serde::{Deserialize, Deserializer, Serialize};
+
+/// Treat `null` like a missing field
+fn null_as_default<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Default + Deserialize<'de>,
+{
+    Ok(Option::<T>::deserialize(deserializer)?.unwrap_or_default())
+}
 
 /// A single choice in the completion response
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct OpenAIChoice {
+    #[serde(default)]
     pub index: u32,
     pub message: OpenAIMessage,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub logprobs: Option<serde_json::Value>,
+    #[serde(default, deserialize_with = "null_as_default")]
     pub finish_reason: String, // "stop" | "length" | "tool_calls" | "content_filter" | "function_call"
 }
 
@@ -19,10 +33,13 @@ pub struct OpenAIChoice {
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct OpenAICompletionResponse {
     pub id: String,
+    #[serde(default)]
     pub object: String, // "chat.completion"
+    #[serde(default)]
     pub created: i64,
     pub model: String,
     pub choices: Vec<OpenAIChoice>,
+    #[serde(default, deserialize_with = "null_as_default")]
     pub usage: OpenAIUsage,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub service_tier: Option<String>,
@@ -33,6 +50,28 @@ pub struct OpenAICompletionResponse {
 // Implement conversion from OpenAI types to genai-types
 impl From<OpenAICompletionResponse> for CompletionResponse {
     fn from(response: OpenAICompletionResponse) -> Self {
+        response.into_completion(&ToolNames::default())
+    }
+}
+
+impl OpenAICompletionResponse {
+    /// Tool calls whose arguments were repaired or couldn't be decoded
+    pub fn tool_call_warnings(&self, tool_names: &ToolNames) -> Vec<ToolCallWarning> {
+        self.choices
+            .first()
+            .and_then(|choice| choice.message.tool_calls.as_ref())
+            .into_iter()
+            .flatten()
+            .filter_map(|call| {
+                let name = tool_names.original_name(&call.function.name);
+                call.function.decode_arguments().warning(&call.id, &name)
+            })
+            .collect()
+    }
+
+    /// Convert to a genai response, calling tools by the names in the request
+    pub fn into_completion(self, tool_names: &ToolNames) -> CompletionResponse {
+        let response = self;
         // Take the first choice (OpenAI can return multiple choices)
         let choice = response.choices.into_iter().next().unwrap_or(OpenAIChoice {
             index: 0,
@@ -49,15 +88,23 @@ impl From<OpenAICompletionResponse> for CompletionResponse {
             finish_reason: "error".to_string(),
         });
 
+        let has_tool_calls = choice
+            .message
+            .tool_calls
+            .as_ref()
+            .is_some_and(|calls| !calls.is_empty());
+
         // Convert the OpenAI message back to genai Message format
-        let genai_message = MessageConverter::from_openai_message(choice.message);
+        let genai_message = MessageConverter::from_openai_message(choice.message, tool_names);
 
         // Map finish reason to stop reason
         let stop_reason = match choice.finish_reason.as_str() {
             "stop" => StopReason::EndTurn,
-            "length" => StopReason::MaxTokens,
-            "tool_calls" => StopReason::ToolUse,
+            "length" | "max_tokens" => StopReason::MaxTokens,
+            "tool_calls" | "function_call" => StopReason::ToolUse,
             "content_filter" => StopReason::EndTurn, // Map to closest equivalent
+            // Missing or unknown reasons: go by whether the model called a tool
+            _ if has_tool_calls => StopReason::ToolUse,
             _ => StopReason::EndTurn,
         };
 
@@ -72,6 +119,79 @@ impl From<OpenAICompletionResponse> for CompletionResponse {
     }
 }
 
+/// Serialize a `Completion` response or stream event with its tool call warnings
+///
+/// `CompletionResponse` is a WIT record, so warnings go in an extra
+/// `tool_call_warnings` field of the serialized completion, which callers that
+/// don't read it ignore. Without warnings this is plain `serde_json::to_vec`.
+pub fn to_vec_with_warnings<T: Serialize>(message: &T, warnings: &[ToolCallWarning]) -> serde_json::Result<Vec<u8>> {
+    if warnings.is_empty() {
+        return serde_json::to_vec(message);
+    }
+    let mut value = serde_json::to_value(message)?;
+    if let Some(serde_json::Value::Object(completion)) = value.get_mut("Completion") {
+        completion.insert("tool_call_warnings".to_string(), serde_json::to_value(warnings)?);
+    }
+    serde_json::to_vec(&value)
+}
+
+/// An error response from the provider, with the fields callers can act on
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct ProviderError {
+    pub status: u16,
+    /// The raw response body
+    pub body: String,
+    /// `error.message` from the body
+    pub message: Option<String>,
+    /// `error.code` from the body
+    pub code: Option<String>,
+    /// `error.type` from the body (e.g. `engine_overloaded_error`)
+    pub error_type: Option<String>,
+    /// The provider's request ID header, for support requests
+    pub request_id: Option<String>,
+}
+
+/// Headers providers use to identify a request
+const REQUEST_ID_HEADERS: &[&str] = &["x-request-id", "request-id", "msh-request-id"];
+
+impl ProviderError {
+    /// Parse an error response
+    ///
+    /// OpenAI, Moonshot and Anthropic all nest the details under `error`;
+    /// bodies that aren't JSON only fill in `body`.
+    pub fn from_response(status: u16, headers: &[(String, String)], body: &[u8]) -> Self {
+        let json: serde_json::Value = serde_json::from_slice(body).unwrap_or_default();
+        let error = &json["error"];
+        let field = |key: &str| match &error[key] {
+            serde_json::Value::String(value) => Some(value.clone()),
+            serde_json::Value::Number(value) => Some(value.to_string()),
+            _ => None,
+        };
+
+        Self {
+            status,
+            body: String::from_utf8_lossy(body).to_string(),
+            message: field("message").or_else(|| json["message"].as_str().map(str::to_string)),
+            code: field("code"),
+            error_type: field("type"),
+            request_id: headers
+                .iter()
+                .find(|(name, _)| REQUEST_ID_HEADERS.contains(&name.to_ascii_lowercase().as_str()))
+                .map(|(_, value)| value.clone()),
+        }
+    }
+
+    /// An error reported outside an HTTP error response, such as in a stream
+    pub fn from_message(status: u16, message: String) -> Self {
+        Self {
+            status,
+            body: message.clone(),
+            message: Some(message),
+            ..Self::default()
+        }
+    }
+}
+
 /// Error types for OpenAI API interactions
 #[derive(Debug, Clone)]
 pub enum OpenAIError {
@@ -80,15 +200,44 @@ pub enum OpenAIError {
     /// JSON serialization/deserialization error
     SerializationError(String),
     /// OpenAI API returned an error
-    ApiError { status: u16, message: String },
+    ApiError(Box<ProviderError>),
     /// Authentication failed
-    AuthenticationError(String),
+    AuthenticationError(Box<ProviderError>),
     /// Rate limit exceeded
-    RateLimitExceeded { retry_after: Option<u64> },
+    RateLimitExceeded { retry_after: Option<u64>, error: Box<ProviderError> },
     /// Invalid response format
     InvalidResponse(String),
+    /// The request was rejected before reaching the provider
+    InvalidRequest(String),
     /// Unsupported model requested
     UnsupportedModel { requested: String, suggestions: Vec<String> },
+    /// A request attempt ran past its deadline
+    Timeout { timeout_ms: Option<u64>, elapsed_ms: u64 },
+    /// The API key environment variable is no longer set
+    MissingApiKey { env_var: String },
+    /// The upstream's circuit breaker is open, so the request was not sent
+    CircuitOpen { upstream: String, retry_in_ms: u64 },
+    /// The model's tool calls failed `tool_validation`
+    ///
+    /// `completion` is the rejected completion. It was still billed, and its
+    /// usage covers every attempt, so it is counted like a successful one.
+    InvalidToolCall { problems: Vec<String>, completion: Box<OpenAICompletionResponse> },
+    /// Estimated spend has reached the configured budget
+    BudgetExceeded {
+        period: BudgetPeriod,
+        spent_usd: f64,
+        max_usd: f64,
+        retry_after: Option<u64>,
+    },
+    /// The caller has used up a daily or monthly quota
+    QuotaExceeded {
+        caller: String,
+        period: String,
+        unit: String,
+        used: u64,
+        limit: u64,
+        retry_after: u64,
+    },
 }
 
 impl std::fmt::Display for OpenAIError {
@@ -96,11 +245,11 @@ impl std::fmt::Display for OpenAIError {
         match self {
             OpenAIError::HttpError(msg) => write!(f, "HTTP error: {}", msg),
             OpenAIError::SerializationError(msg) => write!(f, "Serialization error: {}", msg),
-            OpenAIError::ApiError { status, message } => {
-                write!(f, "API error {}: {}", status, message)
+            OpenAIError::ApiError(error) => {
+                write!(f, "API error {}: {}", error.status, error.body)
             }
-            OpenAIError::AuthenticationError(msg) => write!(f, "Authentication error: {}", msg),
-            OpenAIError::RateLimitExceeded { retry_after } => {
+            OpenAIError::AuthenticationError(error) => write!(f, "Authentication error: {}", error.body),
+            OpenAIError::RateLimitExceeded { retry_after, .. } => {
                 if let Some(seconds) = retry_after {
                     write!(f, "Rate limit exceeded, retry after {} seconds", seconds)
                 } else {
@@ -108,15 +257,207 @@ impl std::fmt::Display for OpenAIError {
                 }
             }
             OpenAIError::InvalidResponse(msg) => write!(f, "Invalid response: {}", msg),
+            OpenAIError::InvalidRequest(msg) => write!(f, "{}", msg),
             OpenAIError::UnsupportedModel { requested, suggestions } => {
                 write!(f, "Unsupported model '{}'. ", requested)?;
                 if !suggestions.is_empty() {
-                    write!(f, "Did you mean one of: {}?", suggestions.join(", "))?;
+                    write!(
+                        f,
+                        "Did you mean one of: {}? Available models can be listed using the ListModels request.",
+                        suggestions.join(", ")
+                    )
                 } else {
-                    write!(f, "Please check the available models list.")?;
+                    write!(f, "Please check the available models using the ListModels request.")
                 }
-                Ok(())
             }
+            OpenAIError::Timeout { timeout_ms, elapsed_ms } => match timeout_ms {
+                Some(timeout_ms) => write!(
+                    f,
+                    "Request timed out after {} ms (limit {} ms)",
+                    elapsed_ms, timeout_ms
+                ),
+                None => write!(f, "Request timed out after {} ms", elapsed_ms),
+            },
+            OpenAIError::MissingApiKey { env_var } => {
+                write!(f, "API key not found in environment variable: {}", env_var)
+            }
+            OpenAIError::CircuitOpen { upstream, retry_in_ms } => write!(
+                f,
+                "Upstream {} is unavailable (circuit open), next attempt in {} ms",
+                upstream, retry_in_ms
+            ),
+            OpenAIError::InvalidToolCall { problems, .. } => {
+                write!(f, "Model returned invalid tool calls: {}", problems.join(" | "))
+            }
+            OpenAIError::BudgetExceeded { period, spent_usd, max_usd, .. } => write!(
+                f,
+                "Spending budget of ${:.2} reached (${:.2} spent {})",
+                max_usd,
+                spent_usd,
+                period.describe()
+            ),
+            OpenAIError::QuotaExceeded { caller, period, unit, used, limit, retry_after } => write!(
+                f,
+                "Caller {} has used {} of its {} {} {} quota, resets in {} seconds",
+                caller, used, limit, period, unit, retry_after
+            ),
+        }
+    }
+}
+
+/// What went wrong, for callers deciding whether to retry, re-authenticate or give up
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorKind {
+    InvalidRequest,
+    UnsupportedModel,
+    Authentication,
+    RateLimited,
+    Timeout,
+    Network,
+    /// Every upstream's circuit breaker is open
+    Unavailable,
+    /// The caller has used up a quota configured in `usage`
+    QuotaExceeded,
+    /// Estimated spend has reached `pricing.budget`
+    BudgetExceeded,
+    /// The model's tool calls failed `tool_validation`
+    InvalidToolCall,
+    /// The provider answered with an error not covered above
+    ProviderError,
+    InvalidResponse,
+}
+
+/// Machine-readable form of an error, sent when `error_format` is `json`
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ErrorDetails {
+    pub kind: ErrorKind,
+    /// The same text sent when `error_format` is `text`
+    pub message: String,
+    /// HTTP status from the provider
+    pub status: Option<u16>,
+    pub provider_code: Option<String>,
+    pub provider_type: Option<String>,
+    pub provider_message: Option<String>,
+    /// Seconds to wait before retrying, when the provider said
+    pub retry_after: Option<u64>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub suggestions: Vec<String>,
+    /// One entry per tool call that failed validation
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tool_call_errors: Vec<String>,
+    /// The provider's request ID
+    pub request_id: Option<String>,
+}
+
+impl OpenAIError {
+    /// A completion the provider generated, and billed, before the request failed
+    pub fn billed_completion(&self) -> Option<&OpenAICompletionResponse> {
+        match self {
+            OpenAIError::InvalidToolCall { completion, .. } => Some(completion),
+            _ => None,
+        }
+    }
+
+    /// Whether a different upstream might succeed where this one failed
+    ///
+    /// Exhausted retries, server errors and credential problems are specific
+    /// to one endpoint; a malformed request would fail everywhere.
+    pub fn should_fail_over(&self) -> bool {
+        match self {
+            OpenAIError::HttpError(_)
+            | OpenAIError::AuthenticationError(_)
+            | OpenAIError::RateLimitExceeded { .. }
+            | OpenAIError::Timeout { .. }
+            | OpenAIError::MissingApiKey { .. }
+            | OpenAIError::CircuitOpen { .. } => true,
+            OpenAIError::ApiError(error) => error.status >= 500 || error.status == 403,
+            OpenAIError::SerializationError(_)
+            | OpenAIError::InvalidResponse(_)
+            | OpenAIError::InvalidRequest(_)
+            | OpenAIError::UnsupportedModel { .. }
+            | OpenAIError::QuotaExceeded { .. }
+            | OpenAIError::BudgetExceeded { .. }
+            | OpenAIError::InvalidToolCall { .. } => false,
+        }
+    }
+
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            OpenAIError::HttpError(_) => ErrorKind::Network,
+            OpenAIError::SerializationError(_) | OpenAIError::InvalidResponse(_) => {
+                ErrorKind::InvalidResponse
+            }
+            OpenAIError::ApiError(error) => match error.status {
+                400 | 404 | 413 | 422 => ErrorKind::InvalidRequest,
+                403 => ErrorKind::Authentication,
+                _ => ErrorKind::ProviderError,
+            },
+            OpenAIError::AuthenticationError(_) | OpenAIError::MissingApiKey { .. } => {
+                ErrorKind::Authentication
+            }
+            OpenAIError::RateLimitExceeded { .. } => ErrorKind::RateLimited,
+            OpenAIError::InvalidRequest(_) => ErrorKind::InvalidRequest,
+            OpenAIError::UnsupportedModel { .. } => ErrorKind::UnsupportedModel,
+            OpenAIError::Timeout { .. } => ErrorKind::Timeout,
+            OpenAIError::CircuitOpen { .. } => ErrorKind::Unavailable,
+            OpenAIError::QuotaExceeded { .. } => ErrorKind::QuotaExceeded,
+            OpenAIError::BudgetExceeded { .. } => ErrorKind::BudgetExceeded,
+            OpenAIError::InvalidToolCall { .. } => ErrorKind::InvalidToolCall,
+        }
+    }
+
+    /// Structured details, with `message` as the text callers see
+    pub fn details(&self, message: String) -> ErrorDetails {
+        let provider = match self {
+            OpenAIError::ApiError(error)
+            | OpenAIError::AuthenticationError(error)
+            | OpenAIError::RateLimitExceeded { error, .. } => Some(error),
+            _ => None,
+        };
+
+        ErrorDetails {
+            kind: self.kind(),
+            message,
+            status: provider.map(|error| error.status),
+            provider_code: provider.and_then(|error| error.code.clone()),
+            provider_type: provider.and_then(|error| error.error_type.clone()),
+            provider_message: provider.and_then(|error| error.message.clone()),
+            retry_after: match self {
+                OpenAIError::RateLimitExceeded { retry_after, .. } => *retry_after,
+                OpenAIError::CircuitOpen { retry_in_ms, .. } => Some(retry_in_ms.div_ceil(1000)),
+                OpenAIError::QuotaExceeded { retry_after, .. } => Some(*retry_after),
+                OpenAIError::BudgetExceeded { retry_after, .. } => *retry_after,
+                _ => None,
+            },
+            suggestions: match self {
+                OpenAIError::UnsupportedModel { suggestions, .. } => suggestions.clone(),
+                _ => Vec::new(),
+            },
+            tool_call_errors: match self {
+                OpenAIError::InvalidToolCall { problems, .. } => problems.clone(),
+                _ => Vec::new(),
+            },
+            request_id: provider.and_then(|error| error.request_id.clone()),
+        }
+    }
+
+    /// The error string sent to callers, prefixed with what was being attempted
+    ///
+    /// In `json` format the plain text sent in `text` format is followed by a
+    /// newline and a serialized `ErrorDetails`, which is always the last line,
+    /// so callers that only show or match the message keep working.
+    pub fn render(&self, format: ErrorFormat, context: Option<&str>) -> String {
+        let message = match context {
+            Some(context) => format!("{}: {}", context, self),
+            None => self.to_string(),
+        };
+        match format {
+            ErrorFormat::Text => message,
+            ErrorFormat::Json => match serde_json::to_string(&self.details(message.clone())) {
+                Ok(details) => format!("{}\n{}", message, details),
+                Err(_) => message,
+            },
         }
     }
 }
@@ -136,23 +477,94 @@ pub struct OpenAIModelInfo {
     pub owned_by: String,
     pub context_length: u32,
     pub pricing: Option<ModelPricing>,
+    /// Accepts image input
+    #[serde(default)]
+    pub supports_vision: bool,
+}
+
+/// Response from the `/models` endpoint
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct OpenAIModelList {
+    #[serde(default)]
+    pub data: Vec<OpenAIRemoteModel>,
+}
+
+/// A model as reported by the provider's `/models` endpoint
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct OpenAIRemoteModel {
+    pub id: String,
+    #[serde(default)]
+    pub created: Option<i64>,
+    #[serde(default)]
+    pub owned_by: Option<String>,
+    /// Reported by some compatible servers (e.g. Moonshot)
+    #[serde(default)]
+    pub context_length: Option<u32>,
+    /// Reported by vLLM
+    #[serde(default)]
+    pub max_model_len: Option<u32>,
+}
+
+/// Context length assumed for models we have no metadata for
+const DEFAULT_CONTEXT_LENGTH: u32 = 8192;
+
+/// Guess whether a model we have no metadata for accepts images
+fn infer_vision_support(model_id: &str) -> bool {
+    let id = model_id.to_lowercase();
+    id.contains("vision")
+        || id.contains("-vl")
+        || id.starts_with("gpt-4o")
+        || id.starts_with("gpt-4.1")
+        || id.starts_with("gpt-4-turbo")
 }
 
 impl OpenAIModelInfo {
-    /// Check if a model is supported
-    pub fn is_model_supported(model_id: &str) -> bool {
-        Self::get_available_models()
-            .iter()
-            .any(|model| model.id == model_id)
+    /// Check if a model is in the given catalog
+    pub fn is_model_supported(models: &[Self], model_id: &str) -> bool {
+        models.iter().any(|model| model.id == model_id)
+    }
+
+    /// Check if a model accepts images, falling back to its name when it isn't in the catalog
+    pub fn supports_vision(models: &[Self], model_id: &str) -> bool {
+        match models.iter().find(|model| model.id == model_id) {
+            Some(model) => model.supports_vision,
+            None => infer_vision_support(model_id),
+        }
+    }
+
+    /// Merge models reported by the provider with our curated metadata
+    pub fn merge_with_known(remote_models: Vec<OpenAIRemoteModel>) -> Vec<Self> {
+        let known_models = Self::get_available_models();
+
+        remote_models
+            .into_iter()
+            .map(|remote| match known_models.iter().find(|known| known.id == remote.id) {
+                Some(known) => Self {
+                    created: remote.created.or(known.created),
+                    ..known.clone()
+                },
+                None => Self {
+                    context_length: remote
+                        .context_length
+                        .or(remote.max_model_len)
+                        .unwrap_or(DEFAULT_CONTEXT_LENGTH),
+                    supports_vision: infer_vision_support(&remote.id),
+                    id: remote.id,
+                    object: "model".to_string(),
+                    created: remote.created,
+                    owned_by: remote.owned_by.unwrap_or_else(|| "unknown".to_string()),
+                    pricing: None,
+                },
+            })
+            .collect()
     }
 
     /// Get supported model suggestions for error messages
-    pub fn get_model_suggestions(requested_model: &str) -> Vec<String> {
-        let available_models = Self::get_available_models();
+    pub fn get_model_suggestions(available_models: &[Self], requested_model: &str) -> Vec<String> {
         let mut suggestions = Vec::new();
         
         // First, look for exact case-insensitive matches
-        for model in &available_models {
+        for model in available_models {
             if model.id.to_lowercase() == requested_model.to_lowercase() {
                 suggestions.push(model.id.clone());
             }
@@ -160,7 +572,7 @@ impl OpenAIModelInfo {
         
         // If no exact matches, look for partial matches
         if suggestions.is_empty() {
-            for model in &available_models {
+            for model in available_models {
                 if model.id.to_lowercase().contains(&requested_model.to_lowercase()) 
                    || requested_model.to_lowercase().contains(&model.id.to_lowercase()) {
                     suggestions.push(model.id.clone());
@@ -196,7 +608,9 @@ impl OpenAIModelInfo {
         suggestions.into_iter().take(3).collect()
     }
 
-    /// Get a list of available OpenAI and Moonshot models
+    /// Get a list of available Moonshot, Anthropic and OpenAI models
+    ///
+    /// Prices are list prices in USD per million tokens.
     pub fn get_available_models() -> Vec<Self> {
         vec![
             // Moonshot models
@@ -206,7 +620,8 @@ impl OpenAIModelInfo {
                 created: None,
                 owned_by: "moonshot".to_string(),
                 context_length: 8192,
-                pricing: None,
+                pricing: Some(ModelPricing { input_price: 0.2, output_price: 2.0 }),
+                supports_vision: false,
             },
             Self {
                 id: "moonshot-v1-32k".to_string(),
@@ -214,7 +629,8 @@ impl OpenAIModelInfo {
                 created: None,
                 owned_by: "moonshot".to_string(),
                 context_length: 32768,
-                pricing: None,
+                pricing: Some(ModelPricing { input_price: 1.0, output_price: 3.0 }),
+                supports_vision: false,
             },
             Self {
                 id: "moonshot-v1-128k".to_string(),
@@ -222,7 +638,8 @@ impl OpenAIModelInfo {
                 created: None,
                 owned_by: "moonshot".to_string(),
                 context_length: 131072,
-                pricing: None,
+                pricing: Some(ModelPricing { input_price: 2.0, output_price: 5.0 }),
+                supports_vision: false,
             },
             Self {
                 id: "moonshot-v1-8k-vision-preview".to_string(),
@@ -230,7 +647,8 @@ impl OpenAIModelInfo {
                 created: None,
                 owned_by: "moonshot".to_string(),
                 context_length: 8192,
-                pricing: None,
+                pricing: Some(ModelPricing { input_price: 0.2, output_price: 2.0 }),
+                supports_vision: true,
             },
             Self {
                 id: "kimi-k2-0711-preview".to_string(),
@@ -238,7 +656,8 @@ impl OpenAIModelInfo {
                 created: None,
                 owned_by: "moonshot".to_string(),
                 context_length: 128000,
-                pricing: None,
+                pricing: Some(ModelPricing { input_price: 0.6, output_price: 2.5 }),
+                supports_vision: false,
             },
             Self {
                 id: "kimi-k2-0707-preview".to_string(),
@@ -246,16 +665,82 @@ impl OpenAIModelInfo {
                 created: None,
                 owned_by: "moonshot".to_string(),
                 context_length: 128000,
-                pricing: None,
+                pricing: Some(ModelPricing { input_price: 0.6, output_price: 2.5 }),
+                supports_vision: false,
+            },
+            // Anthropic models (for the anthropic provider)
+            Self {
+                id: "claude-3-5-sonnet-20241022".to_string(),
+                object: "model".to_string(),
+                created: None,
+                owned_by: "anthropic".to_string(),
+                context_length: 200000,
+                pricing: Some(ModelPricing { input_price: 3.0, output_price: 15.0 }),
+                supports_vision: true,
+            },
+            Self {
+                id: "claude-3-5-haiku-20241022".to_string(),
+                object: "model".to_string(),
+                created: None,
+                owned_by: "anthropic".to_string(),
+                context_length: 200000,
+                pricing: Some(ModelPricing { input_price: 0.8, output_price: 4.0 }),
+                supports_vision: false,
+            },
+            Self {
+                id: "claude-3-sonnet-20240229".to_string(),
+                object: "model".to_string(),
+                created: None,
+                owned_by: "anthropic".to_string(),
+                context_length: 200000,
+                pricing: Some(ModelPricing { input_price: 3.0, output_price: 15.0 }),
+                supports_vision: true,
             },
             // OpenAI models (for compatibility)
+            Self {
+                id: "gpt-4o".to_string(),
+                object: "model".to_string(),
+                created: None,
+                owned_by: "openai".to_string(),
+                context_length: 128000,
+                pricing: Some(ModelPricing { input_price: 2.5, output_price: 10.0 }),
+                supports_vision: true,
+            },
+            Self {
+                id: "gpt-4o-mini".to_string(),
+                object: "model".to_string(),
+                created: None,
+                owned_by: "openai".to_string(),
+                context_length: 128000,
+                pricing: Some(ModelPricing { input_price: 0.15, output_price: 0.6 }),
+                supports_vision: true,
+            },
+            Self {
+                id: "gpt-4.1".to_string(),
+                object: "model".to_string(),
+                created: None,
+                owned_by: "openai".to_string(),
+                context_length: 1047576,
+                pricing: Some(ModelPricing { input_price: 2.0, output_price: 8.0 }),
+                supports_vision: true,
+            },
+            Self {
+                id: "gpt-4.1-mini".to_string(),
+                object: "model".to_string(),
+                created: None,
+                owned_by: "openai".to_string(),
+                context_length: 1047576,
+                pricing: Some(ModelPricing { input_price: 0.4, output_price: 1.6 }),
+                supports_vision: true,
+            },
             Self {
                 id: "gpt-4".to_string(),
                 object: "model".to_string(),
                 created: None,
                 owned_by: "openai".to_string(),
                 context_length: 8192,
-                pricing: None,
+                pricing: Some(ModelPricing { input_price: 30.0, output_price: 60.0 }),
+                supports_vision: false,
             },
             Self {
                 id: "gpt-4-turbo".to_string(),
@@ -263,7 +748,8 @@ impl OpenAIModelInfo {
                 created: None,
                 owned_by: "openai".to_string(),
                 context_length: 128000,
-                pricing: None,
+                pricing: Some(ModelPricing { input_price: 10.0, output_price: 30.0 }),
+                supports_vision: true,
             },
             Self {
                 id: "gpt-3.5-turbo".to_string(),
@@ -271,7 +757,8 @@ impl OpenAIModelInfo {
                 created: None,
                 owned_by: "openai".to_string(),
                 context_length: 4096,
-                pricing: None,
+                pricing: Some(ModelPricing { input_price: 0.5, output_price: 1.5 }),
+                supports_vision: false,
             },
         ]
     }
@@ -288,3 +775,41 @@ impl From<OpenAIModelInfo> for ModelInfo {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_structured_error() {
+        let body = br#"{"error":{"message":"The engine is currently overloaded, please try again later","type":"engine_overloaded_error"}}"#;
+        let headers = vec![("X-Request-Id".to_string(), "req_123".to_string())];
+        let error = OpenAIError::RateLimitExceeded {
+            retry_after: Some(2),
+            error: Box::new(ProviderError::from_response(429, &headers, body)),
+        };
+
+        let text = error.render(ErrorFormat::Text, Some("Failed to generate completion"));
+        assert_eq!(text, "Failed to generate completion: Rate limit exceeded, retry after 2 seconds");
+
+        let rendered = error.render(ErrorFormat::Json, Some("Failed to generate completion"));
+        let (message, details) = rendered.rsplit_once('\n').unwrap();
+        assert_eq!(message, text);
+        let json: serde_json::Value = serde_json::from_str(details).unwrap();
+        assert_eq!(json["kind"], "rate_limited");
+        assert_eq!(json["message"], text);
+        assert_eq!(json["status"], 429);
+        assert_eq!(json["provider_type"], "engine_overloaded_error");
+        assert_eq!(json["retry_after"], 2);
+        assert_eq!(json["request_id"], "req_123");
+
+        let error = OpenAIError::UnsupportedModel {
+            requested: "kimi-k3".to_string(),
+            suggestions: vec!["kimi-k2-0711-preview".to_string()],
+        };
+        let rendered = error.render(ErrorFormat::Json, None);
+        let json: serde_json::Value = serde_json::from_str(rendered.lines().last().unwrap()).unwrap();
+        assert_eq!(json["kind"], "unsupported_model");
+        assert_eq!(json["suggestions"], serde_json::json!(["kimi-k2-0711-preview"]));
+    }
+}
@@ -1,4 +1,15 @@
+use crate::bindings::colinrozzi::genai_types::types::CompletionRequest;
+use crate::bindings::theater::simple::environment;
+use crate::logging;
+use crate::types::circuit_breaker::{CircuitBreaker, CircuitBreakerConfig};
+use crate::types::model_policy::ModelPolicy;
+use crate::types::pricing::PricingConfig;
+use crate::types::tool_schema::ToolSchemaConfig;
+use crate::types::tool_validation::ToolValidationConfig;
+use crate::types::response::{OpenAIError, OpenAIModelInfo};
+use crate::types::usage::{UsageConfig, UsageLedger};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub enum ContentFormat {
@@ -14,6 +25,21 @@ impl Default for ContentFormat {
     }
 }
 
+/// How retry delays are randomized, so actors that fail together don't retry together
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum JitterStrategy {
+    /// Exactly the exponential backoff delay
+    #[default]
+    None,
+    /// Anywhere between zero and the backoff delay
+    Full,
+    /// Half the backoff delay, plus up to the other half
+    Equal,
+    /// Between `initial_delay_ms` and three times the previous delay
+    Decorrelated,
+}
+
 /// Retry configuration for API requests
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct RetryConfig {
@@ -31,6 +57,26 @@ pub struct RetryConfig {
     
     /// Maximum total time to spend on retries in milliseconds
     pub max_total_timeout_ms: u32,
+    
+    /// How backoff delays are randomized
+    #[serde(default)]
+    pub jitter: JitterStrategy,
+    
+    /// Statuses to retry, replacing the provider's defaults (429, 502, 503 and 504,
+    /// plus 529 for Anthropic)
+    #[serde(default)]
+    pub retryable_statuses: Option<Vec<u16>>,
+    
+    /// Error `type` or `code` values that make a response retryable whatever its status
+    #[serde(default = "default_retryable_error_types")]
+    pub retryable_error_types: Vec<String>,
+}
+
+fn default_retryable_error_types() -> Vec<String> {
+    vec![
+        "engine_overloaded_error".to_string(), // Moonshot
+        "overloaded_error".to_string(),        // Anthropic
+    ]
 }
 
 impl Default for RetryConfig {
@@ -41,6 +87,156 @@ impl Default for RetryConfig {
             max_delay_ms: 30000,       // Cap at 30 seconds
             backoff_multiplier: 2.0,   // Double the delay each time
             max_total_timeout_ms: 60000, // 1 minute total
+            jitter: JitterStrategy::default(),
+            retryable_statuses: None,
+            retryable_error_types: default_retryable_error_types(),
+        }
+    }
+}
+
+impl RetryConfig {
+    /// Apply the jitter strategy to a backoff delay
+    ///
+    /// `previous_ms` is the last delay actually slept and `random` a sample
+    /// from `[0, 1)`. The result never exceeds `max_delay_ms`.
+    pub fn jittered_delay(&self, backoff_ms: u64, previous_ms: u64, random: f64) -> u64 {
+        let random = random.clamp(0.0, 1.0);
+        let delay = match self.jitter {
+            JitterStrategy::None => backoff_ms,
+            JitterStrategy::Full => (backoff_ms as f64 * random) as u64,
+            JitterStrategy::Equal => backoff_ms / 2 + (backoff_ms as f64 / 2.0 * random) as u64,
+            JitterStrategy::Decorrelated => {
+                let low = self.initial_delay_ms as u64;
+                let high = (previous_ms * 3).max(low);
+                low + ((high - low) as f64 * random) as u64
+            }
+        };
+        delay.min(self.max_delay_ms as u64)
+    }
+}
+
+/// Which API the upstream speaks
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ProviderKind {
+    /// Any OpenAI-compatible server, using `content_format` for message content
+    #[default]
+    Compatible,
+    /// OpenAI Chat Completions
+    OpenAI,
+    /// Moonshot, which takes string content and rejects some OpenAI options
+    Moonshot,
+    /// Anthropic Messages API
+    Anthropic,
+}
+
+impl ProviderKind {
+    /// Environment variable the API key is read from when `api_key_env` is unset
+    pub fn default_api_key_env(&self) -> &'static str {
+        match self {
+            ProviderKind::Compatible | ProviderKind::OpenAI => "OPENAI_API_KEY",
+            ProviderKind::Moonshot => "MOONSHOT_API_KEY",
+            ProviderKind::Anthropic => "ANTHROPIC_API_KEY",
+        }
+    }
+}
+
+/// An upstream endpoint tried when the ones before it fail
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct UpstreamConfig {
+    /// Name recorded in logs and response IDs
+    pub name: String,
+
+    /// API the upstream speaks
+    #[serde(default)]
+    pub provider: ProviderKind,
+
+    /// Base URL for the API (defaults to the provider's public API)
+    pub base_url: Option<String>,
+
+    /// Environment variable name for the API key (defaults to the provider's)
+    pub api_key_env: Option<String>,
+
+    /// Content format to use for requests (defaults to the provider's)
+    #[serde(default)]
+    pub content_format: Option<ContentFormat>,
+
+    /// Model to request from this upstream in place of the resolved model
+    #[serde(default)]
+    pub models: HashMap<String, String>,
+}
+
+impl UpstreamConfig {
+    /// Environment variable the API key is read from
+    pub fn api_key_env(&self) -> &str {
+        self.api_key_env
+            .as_deref()
+            .unwrap_or(self.provider.default_api_key_env())
+    }
+
+    /// The model ID this upstream knows `model` by
+    pub fn model_for(&self, model: &str) -> String {
+        self.models.get(model).cloned().unwrap_or_else(|| model.to_string())
+    }
+}
+
+/// How errors are encoded in `ProxyResponse::Error` and `StreamEvent::Error`
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ErrorFormat {
+    /// A human-readable message
+    #[default]
+    Text,
+    /// The message, then a JSON-encoded `ErrorDetails` object on the last line
+    Json,
+}
+
+/// Settings for the deterministic-response cache
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct CacheConfig {
+    /// Cache responses in the actor's store (requires a store; one is created if `store_id` is unset)
+    #[serde(default)]
+    pub enabled: bool,
+    
+    /// Also cache requests with a non-zero temperature
+    #[serde(default)]
+    pub cache_nondeterministic: bool,
+}
+
+/// Verbosity of the actor's logs, from quietest to noisiest
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, PartialOrd, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum LogLevel {
+    Off,
+    Error,
+    #[default]
+    Info,
+    /// Adds request and response bodies, with message content redacted
+    Debug,
+    /// Adds unredacted bodies (development only)
+    Trace,
+}
+
+/// Logging settings
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct LoggingConfig {
+    #[serde(default)]
+    pub level: LogLevel,
+
+    /// Longest body that is logged before truncation (0 disables truncation)
+    #[serde(default = "default_max_body_chars")]
+    pub max_body_chars: usize,
+}
+
+fn default_max_body_chars() -> usize {
+    2000
+}
+
+impl Default for LoggingConfig {
+    fn default() -> Self {
+        Self {
+            level: LogLevel::default(),
+            max_body_chars: default_max_body_chars(),
         }
     }
 }
@@ -51,68 +247,351 @@ pub struct Config {
     /// The default OpenAI model to use
     pub default_model: String,
     
-    /// Base URL for the API (defaults to OpenAI's API)
+    /// Model names that mean "use `default_model`" (an empty model always does)
+    #[serde(default = "default_model_sentinels")]
+    pub default_model_sentinels: Vec<String>,
+    
+    /// API the upstream speaks (defaults to a generic OpenAI-compatible server)
+    #[serde(default)]
+    pub provider: ProviderKind,
+    
+    /// Base URL for the API (defaults to the provider's public API)
     pub base_url: Option<String>,
     
-    /// Environment variable name for the API key (defaults to "OPENAI_API_KEY")
+    /// Environment variable name for the API key (defaults to "OPENAI_API_KEY",
+    /// "MOONSHOT_API_KEY" or "ANTHROPIC_API_KEY" depending on the provider)
     pub api_key_env: Option<String>,
     
-    /// Content format to use for requests
+    /// Content format to use for requests (defaults to array content, or for
+    /// the `moonshot` provider to string content except for requests with images)
     #[serde(default)]
-    pub content_format: ContentFormat,
+    pub content_format: Option<ContentFormat>,
     
     /// Maximum number of items to keep in the optional cache
     pub max_cache_size: Option<usize>,
     
+    /// Response cache settings (disabled by default)
+    #[serde(default)]
+    pub cache: CacheConfig,
+    
     /// Request timeout in milliseconds
     pub timeout_ms: u32,
     
     /// Retry configuration for failed requests
     pub retry_config: RetryConfig,
+    
+    /// Upstreams tried in order when the primary endpoint above fails
+    #[serde(default)]
+    pub fallbacks: Vec<UpstreamConfig>,
+    
+    /// When to stop sending requests to a failing upstream
+    #[serde(default)]
+    pub circuit_breaker: CircuitBreakerConfig,
+    
+    /// How long a model list fetched from the provider stays valid, in milliseconds
+    #[serde(default = "default_model_cache_ttl_ms")]
+    pub model_cache_ttl_ms: u64,
+    
+    /// Which models may be requested and how they are validated
+    #[serde(default)]
+    pub model_policy: ModelPolicy,
+
+    /// Log level and body redaction settings
+    #[serde(default)]
+    pub logging: LoggingConfig,
+
+    /// Encoding of error strings sent to callers
+    #[serde(default)]
+    pub error_format: ErrorFormat,
+
+    /// Caller identification and quotas
+    #[serde(default)]
+    pub usage: UsageConfig,
+
+    /// Model prices and the spending budget
+    #[serde(default)]
+    pub pricing: PricingConfig,
+
+    /// Checking of returned tool calls against the request's tools
+    #[serde(default)]
+    pub tool_validation: ToolValidationConfig,
+
+    /// How tool schemas are presented to the model
+    #[serde(default)]
+    pub tool_schema: ToolSchemaConfig,
+}
+
+fn default_model_sentinels() -> Vec<String> {
+    vec!["default".to_string()]
+}
+
+fn default_model_cache_ttl_ms() -> u64 {
+    3_600_000 // 1 hour
+}
+
+impl Config {
+    /// Substitute `default_model` when the request leaves the model blank or uses a sentinel
+    pub fn model_or_default(&self, requested: &str) -> String {
+        let requested = requested.trim();
+        if requested.is_empty() || self.default_model_sentinels.iter().any(|s| s == requested) {
+            self.default_model.clone()
+        } else {
+            requested.to_string()
+        }
+    }
+
+    /// The primary endpoint described by the top-level fields
+    pub fn primary_upstream(&self) -> UpstreamConfig {
+        UpstreamConfig {
+            name: "primary".to_string(),
+            provider: self.provider,
+            base_url: self.base_url.clone(),
+            api_key_env: self.api_key_env.clone(),
+            content_format: self.content_format.clone(),
+            models: HashMap::new(),
+        }
+    }
+
+    /// Every upstream in failover order, primary first
+    pub fn upstreams(&self) -> Vec<UpstreamConfig> {
+        std::iter::once(self.primary_upstream())
+            .chain(self.fallbacks.iter().cloned())
+            .collect()
+    }
 }
 
 impl Default for Config {
     fn default() -> Self {
         Self {
             default_model: "gpt-4o".to_string(),
+            default_model_sentinels: default_model_sentinels(),
+            provider: ProviderKind::default(),
             base_url: None,  // Will default to OpenAI's API
             api_key_env: None,  // Will default to "OPENAI_API_KEY"
-            content_format: ContentFormat::default(),
+            content_format: None,
             max_cache_size: Some(100),
+            cache: CacheConfig::default(),
             timeout_ms: 30000,  // 30 seconds
             retry_config: RetryConfig::default(),
+            fallbacks: Vec::new(),
+            circuit_breaker: CircuitBreakerConfig::default(),
+            model_cache_ttl_ms: default_model_cache_ttl_ms(),
+            model_policy: ModelPolicy::default(),
+            logging: LoggingConfig::default(),
+            error_format: ErrorFormat::default(),
+            usage: UsageConfig::default(),
+            pricing: PricingConfig::default(),
+            tool_validation: ToolValidationConfig::default(),
+            tool_schema: ToolSchemaConfig::default(),
         }
     }
 }
 
+/// Where a cached model catalog came from
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum CatalogSource {
+    /// Fetched from the provider's `/models` endpoint
+    Remote,
+    /// Built-in list used while the endpoint is unavailable
+    Static,
+}
+
+/// Cached list of models the provider serves
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ModelCatalog {
+    pub models: Vec<OpenAIModelInfo>,
+    pub source: CatalogSource,
+    /// Timestamp (ms since epoch) after which the catalog must be refreshed
+    pub expires_at: u64,
+}
+
+impl ModelCatalog {
+    pub fn is_fresh(&self, now: u64) -> bool {
+        now < self.expires_at
+    }
+}
+
 /// Main state for the openai-proxy actor
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct State {
     /// Actor ID
     pub id: String,
     
-    /// OpenAI API key
-    pub api_key: String,
+    /// Environment variable the API key is read from on every request
+    ///
+    /// The key itself is never stored, since state is recorded in the event chain.
+    pub api_key_env: String,
+    
+    /// Masked form of the key seen at init (see `api_key_fingerprint`), used
+    /// to notice key rotation
+    pub api_key_fingerprint: String,
     
     /// Actor configuration
     pub config: Config,
     
     /// Store ID (if using runtime store)
     pub store_id: Option<String>,
+    
+    /// Completion requests waiting to be streamed, keyed by channel ID
+    #[serde(default)]
+    pub pending_streams: HashMap<String, CompletionRequest>,
+
+    /// Callers of open channels, keyed by channel ID, from the `request_id`
+    /// their requests carry
+    #[serde(default)]
+    pub channel_callers: HashMap<String, String>,
+    
+    /// Models reported by the provider, refreshed after `model_cache_ttl_ms`
+    #[serde(default)]
+    pub model_catalog: Option<ModelCatalog>,
+    
+    /// Labels of cached responses, least recently used first
+    #[serde(default)]
+    pub cache_index: Vec<String>,
+    
+    /// Circuit breakers keyed by upstream name
+    #[serde(default)]
+    pub circuit_breakers: HashMap<String, CircuitBreaker>,
+    
+    /// Token and request counters per model and caller
+    #[serde(default)]
+    pub usage: UsageLedger,
 }
 
 impl State {
     pub fn new(
         id: String,
-        api_key: String,
+        api_key_env: String,
+        api_key: &str,
         store_id: Option<String>,
         config: Option<Config>,
     ) -> Self {
         Self {
             id,
-            api_key,
+            api_key_env,
+            api_key_fingerprint: api_key_fingerprint(api_key),
             config: config.unwrap_or_default(),
             store_id,
+            pending_streams: HashMap::new(),
+            channel_callers: HashMap::new(),
+            model_catalog: None,
+            cache_index: Vec::new(),
+            circuit_breakers: HashMap::new(),
+            usage: UsageLedger::default(),
         }
     }
+
+    /// Read the API key from the environment
+    pub fn api_key(&self) -> Result<String, OpenAIError> {
+        self.api_key_from(&self.api_key_env)
+    }
+
+    /// Read an API key from the given environment variable
+    ///
+    /// Only the primary key was seen at init, so rotation is only reported for it.
+    pub fn api_key_from(&self, env_var: &str) -> Result<String, OpenAIError> {
+        let key = environment::get_var(env_var).ok_or_else(|| {
+            logging::error(&format!(
+                "API key no longer present in environment variable: {}",
+                env_var
+            ));
+            OpenAIError::MissingApiKey {
+                env_var: env_var.to_string(),
+            }
+        })?;
+
+        if env_var == self.api_key_env && api_key_fingerprint(&key) != self.api_key_fingerprint {
+            logging::info(&format!(
+                "API key in {} has changed since init",
+                self.api_key_env
+            ));
+        }
+
+        Ok(key)
+    }
+}
+
+/// Keys shorter than this are only fingerprinted by their length
+const MIN_MASKED_KEY_CHARS: usize = 20;
+
+/// A masked API key, as provider dashboards show it: `sk-...` followed by
+/// its last four characters
+///
+/// State is recorded in the event chain, so only these few characters are
+/// kept; they are not enough to recover or guess the key. A rotated key that
+/// happens to share them goes unnoticed.
+pub fn api_key_fingerprint(api_key: &str) -> String {
+    let chars: Vec<char> = api_key.chars().collect();
+    if chars.len() < MIN_MASKED_KEY_CHARS {
+        return format!("<{} chars>", chars.len());
+    }
+    let prefix: String = chars[..3].iter().collect();
+    let suffix: String = chars[chars.len() - 4..].iter().collect();
+    format!("{}...{}", prefix, suffix)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_api_key_fingerprint() {
+        let key = "sk-0123456789abcdefghijWXYZ";
+        assert_eq!(api_key_fingerprint(key), "sk-...WXYZ");
+        assert!(!api_key_fingerprint(key).contains("0123"));
+        assert_eq!(api_key_fingerprint("short-key"), "<9 chars>");
+    }
+
+    #[test]
+    fn test_jittered_delay() {
+        // Existing configs keep the plain backoff schedule
+        let mut config = RetryConfig::default();
+        assert_eq!(config.jitter, JitterStrategy::None);
+        assert_eq!(config.jittered_delay(4000, 2000, 0.3), 4000);
+
+        config.jitter = JitterStrategy::Full;
+        assert_eq!(config.jittered_delay(4000, 2000, 0.25), 1000);
+
+        config.jitter = JitterStrategy::Equal;
+        assert_eq!(config.jittered_delay(4000, 2000, 0.5), 3000);
+
+        // Between initial_delay_ms (1000) and 3x the previous delay
+        config.jitter = JitterStrategy::Decorrelated;
+        assert_eq!(config.jittered_delay(4000, 2000, 0.0), 1000);
+        assert_eq!(config.jittered_delay(4000, 2000, 0.5), 3500);
+
+        // Capped at max_delay_ms
+        assert_eq!(config.jittered_delay(4000, 20_000, 0.99), 30_000);
+    }
+
+    #[test]
+    fn test_fallback_upstreams() {
+        let config: Config = serde_json::from_value(serde_json::json!({
+            "default_model": "kimi-k2-0711-preview",
+            "provider": "moonshot",
+            "base_url": null,
+            "api_key_env": null,
+            "max_cache_size": null,
+            "timeout_ms": 30000,
+            "retry_config": RetryConfig::default(),
+            "fallbacks": [{
+                "name": "openrouter",
+                "base_url": "https://openrouter.ai/api/v1",
+                "api_key_env": "OPENROUTER_API_KEY",
+                "models": { "kimi-k2-0711-preview": "moonshotai/kimi-k2" }
+            }]
+        }))
+        .unwrap();
+
+        let upstreams = config.upstreams();
+        assert_eq!(upstreams.len(), 2);
+        assert_eq!(upstreams[0].name, "primary");
+        assert_eq!(upstreams[0].api_key_env(), "MOONSHOT_API_KEY");
+        assert_eq!(upstreams[0].model_for("kimi-k2-0711-preview"), "kimi-k2-0711-preview");
+
+        assert_eq!(upstreams[1].provider, ProviderKind::Compatible);
+        assert_eq!(upstreams[1].api_key_env(), "OPENROUTER_API_KEY");
+        assert_eq!(upstreams[1].model_for("kimi-k2-0711-preview"), "moonshotai/kimi-k2");
+        assert_eq!(upstreams[1].model_for("moonshot-v1-8k"), "moonshot-v1-8k");
+    }
 }
@@ -0,0 +1,373 @@
+// Streaming support for OpenAI-compatible chat completions
+//
+// Providers stream completions as Server-Sent Events where every `data:` line
+// carries a `chat.completion.chunk` object. This module parses those lines,
+// turns them into incremental events for the calling actor, and assembles the
+// final response so the channel can finish with a regular CompletionResponse.
+
+use crate::bindings::colinrozzi::genai_types::types::CompletionResponse;
+use crate::types::api::OpenAIUsage;
+use crate::types::conversion::{
+    OpenAIContent, OpenAIContentItem, OpenAIFunctionCall, OpenAIMessage, OpenAIToolCall,
+};
+use crate::types::response::{OpenAIChoice, OpenAICompletionResponse, OpenAIError};
+use serde::{Deserialize, Serialize};
+
+/// Marker sent by OpenAI-compatible providers after the last chunk
+const DONE_MARKER: &str = "[DONE]";
+
+/// How far past the tool calls seen so far a new tool call index may skip
+///
+/// Indexes normally count up from 0; a gap this large means a broken stream,
+/// and growing the list to fit it could use any amount of memory.
+const MAX_TOOL_CALL_INDEX_GAP: usize = 16;
+
+/// Events sent to the calling actor over a message-server channel
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub enum StreamEvent {
+    /// A piece of assistant text
+    ContentDelta(String),
+    /// A fragment of a tool call; `arguments` is a partial JSON string
+    ToolCallDelta {
+        index: u32,
+        id: Option<String>,
+        name: Option<String>,
+        arguments: String,
+    },
+    /// The assembled completion, including token usage
+    Completion(CompletionResponse),
+    /// The stream failed; no further events will follow
+    Error(String),
+}
+
+/// A single `chat.completion.chunk` object from the SSE stream
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct OpenAIStreamChunk {
+    #[serde(default)]
+    pub id: Option<String>,
+    #[serde(default)]
+    pub created: Option<i64>,
+    #[serde(default)]
+    pub model: Option<String>,
+    #[serde(default)]
+    pub choices: Vec<OpenAIStreamChoice>,
+    /// Sent by OpenAI on the final chunk when `stream_options.include_usage` is set
+    #[serde(default)]
+    pub usage: Option<OpenAIUsage>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct OpenAIStreamChoice {
+    #[serde(default)]
+    pub index: u32,
+    #[serde(default)]
+    pub delta: OpenAIStreamDelta,
+    #[serde(default)]
+    pub finish_reason: Option<String>,
+    /// Moonshot reports usage on the final choice instead of the chunk
+    #[serde(default)]
+    pub usage: Option<OpenAIUsage>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct OpenAIStreamDelta {
+    #[serde(default)]
+    pub role: Option<String>,
+    #[serde(default)]
+    pub content: Option<String>,
+    #[serde(default)]
+    pub tool_calls: Option<Vec<OpenAIToolCallDelta>>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct OpenAIToolCallDelta {
+    #[serde(default)]
+    pub index: u32,
+    #[serde(default)]
+    pub id: Option<String>,
+    #[serde(rename = "type", default)]
+    pub tool_type: Option<String>,
+    #[serde(default)]
+    pub function: Option<OpenAIFunctionCallDelta>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct OpenAIFunctionCallDelta {
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub arguments: Option<String>,
+}
+
+/// Extract the payloads of all `data:` events from an SSE body
+///
+/// Multi-line events are joined with newlines as described by the SSE spec,
+/// comment lines are skipped, and parsing stops at the `[DONE]` marker.
+pub fn parse_sse_data(body: &str) -> Vec<String> {
+    let mut events = Vec::new();
+    let mut current: Vec<&str> = Vec::new();
+
+    for line in body.lines() {
+        let line = line.trim_end_matches('\r');
+
+        if line.is_empty() {
+            if !current.is_empty() {
+                events.push(current.join("\n"));
+                current.clear();
+            }
+            continue;
+        }
+
+        if let Some(data) = line.strip_prefix("data:") {
+            current.push(data.strip_prefix(' ').unwrap_or(data));
+        }
+        // Ignore comments (":") and other fields such as "event:" or "id:"
+    }
+
+    if !current.is_empty() {
+        events.push(current.join("\n"));
+    }
+
+    events
+        .into_iter()
+        .take_while(|data| data.trim() != DONE_MARKER)
+        .collect()
+}
+
+/// Express a non-streamed completion as the deltas a stream would have produced
+pub fn completion_deltas(completion: &OpenAICompletionResponse) -> Vec<StreamEvent> {
+    let mut events = Vec::new();
+
+    let message = match completion.choices.first() {
+        Some(choice) => &choice.message,
+        None => return events,
+    };
+
+    if let Some(content) = &message.content {
+        for item in content.items() {
+            if let OpenAIContentItem::Text { text } = item {
+                events.push(StreamEvent::ContentDelta(text.clone()));
+            }
+        }
+    }
+
+    for (index, call) in message.tool_calls.iter().flatten().enumerate() {
+        let arguments = match &call.function.arguments {
+            serde_json::Value::String(arguments) => arguments.clone(),
+            other => other.to_string(),
+        };
+        events.push(StreamEvent::ToolCallDelta {
+            index: index as u32,
+            id: Some(call.id.clone()),
+            name: Some(call.function.name.clone()),
+            arguments,
+        });
+    }
+
+    events
+}
+
+#[derive(Debug, Clone, Default)]
+struct ToolCallBuilder {
+    id: String,
+    name: String,
+    arguments: String,
+}
+
+/// Assembles streamed chunks into a complete response
+#[derive(Debug, Default)]
+pub struct StreamAccumulator {
+    id: Option<String>,
+    created: Option<i64>,
+    model: Option<String>,
+    content: String,
+    tool_calls: Vec<ToolCallBuilder>,
+    finish_reason: Option<String>,
+    usage: Option<OpenAIUsage>,
+}
+
+impl StreamAccumulator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Fold a chunk into the accumulated response, returning the events it produced
+    pub fn push_chunk(&mut self, chunk: OpenAIStreamChunk) -> Result<Vec<StreamEvent>, OpenAIError> {
+        let mut events = Vec::new();
+
+        if self.id.is_none() {
+            self.id = chunk.id;
+        }
+        if self.created.is_none() {
+            self.created = chunk.created;
+        }
+        if self.model.is_none() {
+            self.model = chunk.model;
+        }
+        if chunk.usage.is_some() {
+            self.usage = chunk.usage;
+        }
+
+        // Only the first choice is surfaced, matching the non-streaming conversion
+        for choice in chunk.choices.into_iter().filter(|c| c.index == 0) {
+            if let Some(text) = choice.delta.content {
+                if !text.is_empty() {
+                    self.content.push_str(&text);
+                    events.push(StreamEvent::ContentDelta(text));
+                }
+            }
+
+            for delta in choice.delta.tool_calls.unwrap_or_default() {
+                let index = delta.index as usize;
+                if index > self.tool_calls.len() + MAX_TOOL_CALL_INDEX_GAP {
+                    return Err(OpenAIError::InvalidResponse(format!(
+                        "Tool call index {} in stream after {} tool calls",
+                        index,
+                        self.tool_calls.len()
+                    )));
+                }
+                if self.tool_calls.len() <= index {
+                    self.tool_calls.resize(index + 1, ToolCallBuilder::default());
+                }
+                let builder = &mut self.tool_calls[index];
+
+                if let Some(id) = &delta.id {
+                    builder.id = id.clone();
+                }
+                let (name, arguments) = match delta.function {
+                    Some(function) => (function.name, function.arguments.unwrap_or_default()),
+                    None => (None, String::new()),
+                };
+                if let Some(name) = &name {
+                    builder.name.push_str(name);
+                }
+                builder.arguments.push_str(&arguments);
+
+                events.push(StreamEvent::ToolCallDelta {
+                    index: delta.index,
+                    id: delta.id,
+                    name,
+                    arguments,
+                });
+            }
+
+            if choice.finish_reason.is_some() {
+                self.finish_reason = choice.finish_reason;
+            }
+            if choice.usage.is_some() {
+                self.usage = choice.usage;
+            }
+        }
+
+        Ok(events)
+    }
+
+    /// Build the final response from everything received so far
+    pub fn into_response(self) -> OpenAICompletionResponse {
+        let tool_calls: Vec<OpenAIToolCall> = self
+            .tool_calls
+            .into_iter()
+            .map(|call| OpenAIToolCall {
+                id: call.id,
+                tool_type: "function".to_string(),
+                function: OpenAIFunctionCall {
+                    name: call.name,
+                    arguments: serde_json::Value::String(call.arguments),
+                },
+            })
+            .collect();
+
+        OpenAICompletionResponse {
+            id: self.id.unwrap_or_default(),
+            object: "chat.completion".to_string(),
+            created: self.created.unwrap_or_default(),
+            model: self.model.unwrap_or_default(),
+            choices: vec![OpenAIChoice {
+                index: 0,
+                message: OpenAIMessage {
+                    role: "assistant".to_string(),
+                    content: if self.content.is_empty() {
+                        None
+                    } else {
+                        Some(OpenAIContent::from_text(self.content))
+                    },
+                    tool_calls: if tool_calls.is_empty() {
+                        None
+                    } else {
+                        Some(tool_calls)
+                    },
+                    tool_call_id: None,
+                    name: None,
+                    audio: None,
+                    refusal: None,
+                },
+                logprobs: None,
+                finish_reason: self.finish_reason.unwrap_or_else(|| "stop".to_string()),
+            }],
+            usage: self.usage.unwrap_or(OpenAIUsage {
+                prompt_tokens: 0,
+                completion_tokens: 0,
+                total_tokens: 0,
+            }),
+            service_tier: None,
+            system_fingerprint: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_sse_data_stops_at_done() {
+        let body = "data: {\"a\":1}\n\n: keep-alive\n\ndata: {\"b\":2}\n\ndata: [DONE]\n\n";
+        let events = parse_sse_data(body);
+        assert_eq!(events, vec!["{\"a\":1}".to_string(), "{\"b\":2}".to_string()]);
+    }
+
+    #[test]
+    fn test_accumulator_assembles_text_and_tool_calls() {
+        let chunks = [
+            r#"{"id":"c1","model":"kimi-k2-0711-preview","choices":[{"index":0,"delta":{"role":"assistant","content":"Hel"}}]}"#,
+            r#"{"id":"c1","choices":[{"index":0,"delta":{"content":"lo"}}]}"#,
+            r#"{"id":"c1","choices":[{"index":0,"delta":{"tool_calls":[{"index":0,"id":"call_1","type":"function","function":{"name":"search","arguments":"{\"q\":"}}]}}]}"#,
+            r#"{"id":"c1","choices":[{"index":0,"delta":{"tool_calls":[{"index":0,"function":{"arguments":"\"moon\"}"}}]},"finish_reason":"tool_calls","usage":{"prompt_tokens":5,"completion_tokens":7,"total_tokens":12}}]}"#,
+        ];
+
+        let mut accumulator = StreamAccumulator::new();
+        let mut event_count = 0;
+        for chunk in chunks {
+            let chunk: OpenAIStreamChunk = serde_json::from_str(chunk).unwrap();
+            event_count += accumulator.push_chunk(chunk).unwrap().len();
+        }
+        assert_eq!(event_count, 4);
+
+        // A second call may skip ahead a little, but not arbitrarily far
+        let chunk = |index: u32| -> OpenAIStreamChunk {
+            serde_json::from_value(serde_json::json!({
+                "choices": [{ "index": 0, "delta": { "tool_calls": [{ "index": index, "function": { "arguments": "" } }] } }]
+            }))
+            .unwrap()
+        };
+        assert!(accumulator.push_chunk(chunk(3)).is_ok());
+        assert!(matches!(
+            accumulator.push_chunk(chunk(u32::MAX)),
+            Err(OpenAIError::InvalidResponse(_))
+        ));
+        assert!(accumulator.push_chunk(chunk(4 + MAX_TOOL_CALL_INDEX_GAP as u32)).is_ok());
+
+        let response = accumulator.into_response();
+        assert_eq!(response.id, "c1");
+        assert_eq!(response.usage.total_tokens, 12);
+
+        let choice = &response.choices[0];
+        assert_eq!(choice.finish_reason, "tool_calls");
+        let tool_calls = choice.message.tool_calls.as_ref().unwrap();
+        assert_eq!(tool_calls[0].function.name, "search");
+        assert_eq!(
+            tool_calls[0].function.arguments,
+            serde_json::Value::String("{\"q\":\"moon\"}".to_string())
+        );
+    }
+}
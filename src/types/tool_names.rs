@@ -0,0 +1,138 @@
+// Tool names in the form providers accept
+//
+// OpenAI-compatible APIs (and Anthropic) only take tool names matching
+// `^[a-zA-Z0-9_-]{1,64}$`, while MCP tool names often contain dots or slashes
+// (`github.search/issues`) or run longer. Names that don't fit are replaced
+// with a safe name when a request is converted, and tool calls in the response
+// are mapped back, so callers only ever see their own names.
+
+use crate::hash::fnv1a_64;
+use std::collections::HashMap;
+
+/// The longest tool name providers accept
+pub const MAX_TOOL_NAME_LEN: usize = 64;
+
+/// Whether providers accept `name` as it is
+pub fn is_valid_tool_name(name: &str) -> bool {
+    !name.is_empty()
+        && name.len() <= MAX_TOOL_NAME_LEN
+        && name.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'_' || b == b'-')
+}
+
+/// The mapping between a request's tool names and the names sent upstream
+///
+/// Valid names are sent unchanged. Any other name becomes its allowed
+/// characters, cut short if needed, followed by a hash of the original:
+/// `github.search/issues` is sent as `github_search_issues_` and 8 hex digits. The
+/// mapping only depends on the names themselves, so a conversation keeps the
+/// same names from one request to the next.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ToolNames {
+    to_provider: HashMap<String, String>,
+    to_original: HashMap<String, String>,
+}
+
+impl ToolNames {
+    pub fn new<'a>(names: impl IntoIterator<Item = &'a str>) -> Self {
+        let names: Vec<&str> = names.into_iter().collect();
+        let mut map = Self::default();
+
+        // Names that are already valid keep their name, so they are reserved first
+        let mut taken: Vec<String> = names
+            .iter()
+            .filter(|name| is_valid_tool_name(name))
+            .map(|name| name.to_string())
+            .collect();
+
+        for name in names.into_iter().filter(|name| !is_valid_tool_name(name)) {
+            if map.to_provider.contains_key(name) {
+                continue;
+            }
+            let base = safe_name(name);
+            let mut provider_name = base.clone();
+            let mut attempt = 1;
+            while taken.contains(&provider_name) {
+                attempt += 1;
+                let suffix = format!("_{}", attempt);
+                provider_name = format!("{}{}", truncate(&base, MAX_TOOL_NAME_LEN - suffix.len()), suffix);
+            }
+            taken.push(provider_name.clone());
+            map.to_provider.insert(name.to_string(), provider_name.clone());
+            map.to_original.insert(provider_name, name.to_string());
+        }
+        map
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.to_provider.is_empty()
+    }
+
+    /// The name to send upstream for a tool
+    ///
+    /// Tools missing from the request (e.g. ones used earlier in the
+    /// conversation) are made safe the same way, without a collision check.
+    pub fn provider_name(&self, name: &str) -> String {
+        match self.to_provider.get(name) {
+            Some(provider_name) => provider_name.clone(),
+            None if is_valid_tool_name(name) => name.to_string(),
+            None => safe_name(name),
+        }
+    }
+
+    /// The caller's name for a tool the provider called by `provider_name`
+    pub fn original_name(&self, provider_name: &str) -> String {
+        self.to_original
+            .get(provider_name)
+            .cloned()
+            .unwrap_or_else(|| provider_name.to_string())
+    }
+}
+
+/// A valid name derived from `name`, made distinct by a hash of it
+fn safe_name(name: &str) -> String {
+    let hash = format!("_{:08x}", fnv1a_64(name.as_bytes()) as u32);
+    let cleaned: String = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' { c } else { '_' })
+        .collect();
+    format!("{}{}", truncate(&cleaned, MAX_TOOL_NAME_LEN - hash.len()), hash)
+}
+
+/// `name` cut to at most `len` bytes; safe names are ASCII, so any cut is a boundary
+fn truncate(name: &str, len: usize) -> &str {
+    &name[..name.len().min(len)]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tool_name_mapping() {
+        let long = "a".repeat(80);
+        let names = ToolNames::new(["get_weather", "github.search/issues", "github_search/issues", long.as_str()]);
+
+        assert_eq!(names.provider_name("get_weather"), "get_weather");
+        let issues = names.provider_name("github.search/issues");
+        let issues_2 = names.provider_name("github_search/issues");
+        assert!(issues.starts_with("github_search_issues_"));
+        assert_ne!(issues, issues_2);
+        assert_eq!(names.provider_name(&long).len(), MAX_TOOL_NAME_LEN);
+
+        for name in ["get_weather", "github.search/issues", "github_search/issues", long.as_str()] {
+            let provider_name = names.provider_name(name);
+            assert!(is_valid_tool_name(&provider_name), "{}", provider_name);
+            assert_eq!(names.original_name(&provider_name), name);
+        }
+
+        // A valid name that happens to equal a generated one keeps it
+        let names = ToolNames::new([issues.as_str(), "github.search/issues"]);
+        assert_eq!(names.provider_name(&issues), issues);
+        assert_eq!(names.provider_name("github.search/issues"), format!("{}_2", issues));
+        assert_eq!(names.original_name(&format!("{}_2", issues)), "github.search/issues");
+
+        // The same tools always get the same names
+        assert_eq!(ToolNames::new(["x.y"]), ToolNames::new(["x.y"]));
+        assert_eq!(names.original_name("unknown_tool"), "unknown_tool");
+    }
+}
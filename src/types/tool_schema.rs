@@ -0,0 +1,456 @@
+// Tool schemas in the shape each provider accepts
+//
+// MCP tools carry arbitrary JSON Schema. OpenAI takes it as is, except for
+// `strict` functions, which need closed objects with every property required
+// and reject some keywords. Moonshot understands a common subset without
+// `$ref`. `check_schema` rejects schemas no provider could use when a request
+// is converted; `normalize_tools` rewrites the rest for one provider as the
+// request body is built, and `remove_strict_nulls` undoes what that means for
+// the arguments that come back.
+
+use crate::types::api::{OpenAICompletionRequest, OpenAITool};
+use crate::types::conversion::DecodedArguments;
+use crate::types::json_schema;
+use crate::types::response::OpenAICompletionResponse;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+
+/// How tool schemas are presented to the model
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct ToolSchemaConfig {
+    /// Mark functions `strict` for OpenAI, so arguments always match the schema
+    #[serde(default)]
+    pub strict: bool,
+
+    /// Append each tool's `output_schema` to its description
+    #[serde(default)]
+    pub describe_output: bool,
+}
+
+/// The JSON Schema a provider accepts in tool parameters
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SchemaDialect {
+    /// Schemas as supplied; `strict` is not sent
+    Full,
+    /// Schemas as supplied, with `strict` functions made strict-compatible
+    OpenAI,
+    /// Moonshot's subset: `$ref`s inlined and uncommon keywords removed
+    Basic,
+}
+
+/// Keywords OpenAI rejects in `strict` functions
+const STRICT_UNSUPPORTED: &[&str] = &[
+    "default",
+    "minLength",
+    "maxLength",
+    "minItems",
+    "maxItems",
+    "minProperties",
+    "maxProperties",
+    "patternProperties",
+    "unevaluatedProperties",
+    "propertyNames",
+    "dependentRequired",
+    "dependentSchemas",
+    "not",
+    "if",
+    "then",
+    "else",
+    "examples",
+];
+
+/// The only keywords kept in the `Basic` dialect
+const BASIC_KEYWORDS: &[&str] = &[
+    "type",
+    "description",
+    "title",
+    "properties",
+    "required",
+    "items",
+    "enum",
+    "anyOf",
+    "additionalProperties",
+    "default",
+    "minimum",
+    "maximum",
+    "minLength",
+    "maxLength",
+    "minItems",
+    "maxItems",
+    "pattern",
+    "format",
+];
+
+/// Keywords that only carry metadata for other tooling
+const METADATA_KEYWORDS: &[&str] = &["$schema", "$id", "$comment"];
+
+/// How deep `$ref`s are inlined before a recursive schema is cut off
+const MAX_REF_DEPTH: usize = 8;
+
+/// Reject a tool input schema no provider could use
+///
+/// The schema must describe an object, and every `$ref` must point inside it.
+pub fn check_schema(schema: &Value) -> Result<(), String> {
+    let Value::Object(root) = schema else {
+        return Err("the schema must be a JSON object".to_string());
+    };
+    match root.get("type") {
+        None => {}
+        Some(Value::String(name)) if name == "object" => {}
+        Some(other) => return Err(format!("the schema must describe an object, not {}", other)),
+    }
+
+    let mut error = None;
+    visit(schema, &mut |node| {
+        if let Some(reference) = node.get("$ref").and_then(Value::as_str) {
+            let resolved = reference
+                .strip_prefix('#')
+                .and_then(|pointer| schema.pointer(pointer));
+            if resolved.is_none() && error.is_none() {
+                error = Some(format!("$ref '{}' does not point to a definition in the schema", reference));
+            }
+        }
+    });
+    error.map_or(Ok(()), Err)
+}
+
+/// Rewrite the tools of a request for a provider's dialect
+pub fn normalize_tools(tools: &mut [OpenAITool], dialect: SchemaDialect) {
+    for tool in tools {
+        let strict = dialect == SchemaDialect::OpenAI && tool.function.strict == Some(true);
+        if !strict {
+            tool.function.strict = None;
+        }
+
+        let parameters = &mut tool.function.parameters;
+        if let Value::Object(root) = parameters {
+            root.entry("type").or_insert_with(|| json!("object"));
+            root.entry("properties").or_insert_with(|| json!({}));
+        }
+
+        match dialect {
+            SchemaDialect::Full => {}
+            SchemaDialect::OpenAI if strict => walk_mut(parameters, &mut make_strict),
+            SchemaDialect::OpenAI => {}
+            SchemaDialect::Basic => {
+                *parameters = inline_refs(parameters, parameters, 0);
+                walk_mut(parameters, &mut make_basic);
+            }
+        }
+    }
+}
+
+/// Apply the `tool_schema` settings to converted tools
+pub fn configure_tools(tools: &mut [OpenAITool], config: &ToolSchemaConfig) {
+    for tool in tools {
+        if config.strict {
+            tool.function.strict = Some(true);
+        }
+        if let (true, Some(output_schema)) = (config.describe_output, &tool.function.output_schema) {
+            let returns = format!("Returns JSON matching this schema: {}", output_schema);
+            tool.function.description = Some(match tool.function.description.take() {
+                Some(description) => format!("{}\n\n{}", description, returns),
+                None => returns,
+            });
+        }
+    }
+}
+
+/// Drop the `null`s `strict` functions send for arguments they left out
+///
+/// Strict schemas make optional properties required but nullable, so a model
+/// that means to leave one out sends `null`. Tools and `tool_validation` only
+/// know the original schema, where that property is optional and usually
+/// can't be null, so such `null`s are removed. Properties whose original
+/// schema accepts `null` keep it. Only arguments that decoded cleanly are
+/// rewritten; strict output never needs repairing.
+pub fn remove_strict_nulls(request: &OpenAICompletionRequest, completion: &mut OpenAICompletionResponse) {
+    let tools = request.tools.as_deref().unwrap_or_default();
+    for choice in &mut completion.choices {
+        for call in choice.message.tool_calls.iter_mut().flatten() {
+            let Some(tool) = tools
+                .iter()
+                .find(|tool| tool.function.name == call.function.name && tool.function.strict == Some(true))
+            else {
+                continue;
+            };
+            let DecodedArguments::Valid(mut arguments) = call.function.decode_arguments() else {
+                continue;
+            };
+            let parameters = &tool.function.parameters;
+            if remove_optional_nulls(&inline_refs(parameters, parameters, 0), &mut arguments) {
+                call.function.arguments = Value::String(arguments.to_string());
+            }
+        }
+    }
+}
+
+/// Remove `null`s for optional properties that don't accept them, returning
+/// whether anything was removed
+fn remove_optional_nulls(schema: &Value, value: &mut Value) -> bool {
+    let mut removed = false;
+    match value {
+        Value::Object(object) => {
+            let Some(properties) = schema.get("properties").and_then(Value::as_object) else {
+                return false;
+            };
+            let required: Vec<&str> = schema
+                .get("required")
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+                .filter_map(Value::as_str)
+                .collect();
+            object.retain(|name, value| {
+                let omitted = value.is_null()
+                    && !required.contains(&name.as_str())
+                    && properties
+                        .get(name)
+                        .is_some_and(|property| !json_schema::validate(property, &Value::Null).is_empty());
+                removed |= omitted;
+                !omitted
+            });
+            for (name, value) in object.iter_mut() {
+                if let Some(property) = properties.get(name) {
+                    removed |= remove_optional_nulls(property, value);
+                }
+            }
+        }
+        Value::Array(items) => {
+            if let Some(item_schema) = schema.get("items").filter(|items| items.is_object()) {
+                for item in items {
+                    removed |= remove_optional_nulls(item_schema, item);
+                }
+            }
+        }
+        _ => {}
+    }
+    removed
+}
+
+/// Close every object and require all its properties, as `strict` demands
+///
+/// Properties that were optional become nullable instead.
+fn make_strict(node: &mut Map<String, Value>) {
+    merge_all_of(node);
+    if let Some(one_of) = node.remove("oneOf") {
+        node.insert("anyOf".to_string(), one_of);
+    }
+    for keyword in STRICT_UNSUPPORTED.iter().chain(METADATA_KEYWORDS) {
+        node.remove(*keyword);
+    }
+
+    let required: Vec<String> = node
+        .get("required")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(|name| name.as_str().map(str::to_string))
+        .collect();
+    let Some(Value::Object(properties)) = node.get_mut("properties") else {
+        return;
+    };
+    for (name, property) in properties.iter_mut() {
+        if !required.contains(name) {
+            make_nullable(property);
+        }
+    }
+    let names: Vec<Value> = properties.keys().map(|name| json!(name)).collect();
+    node.insert("required".to_string(), Value::Array(names));
+    node.insert("additionalProperties".to_string(), json!(false));
+}
+
+fn make_nullable(schema: &mut Value) {
+    let Value::Object(node) = schema else {
+        return;
+    };
+    if let Some(Value::Array(options)) = node.get_mut("enum") {
+        if !options.contains(&Value::Null) {
+            options.push(Value::Null);
+        }
+    }
+    match node.get_mut("type") {
+        Some(Value::String(name)) if name != "null" => {
+            let name = std::mem::take(name);
+            node.insert("type".to_string(), json!([name, "null"]));
+        }
+        Some(Value::Array(names)) => {
+            if !names.contains(&json!("null")) {
+                names.push(json!("null"));
+            }
+        }
+        Some(_) => {}
+        // Untyped alternatives get a null branch; anything else is wrapped
+        None => {
+            let branches = node
+                .iter_mut()
+                .find(|(keyword, _)| *keyword == "anyOf" || *keyword == "oneOf")
+                .and_then(|(_, branches)| branches.as_array_mut());
+            match branches {
+                Some(branches) => branches.push(json!({ "type": "null" })),
+                None => *schema = json!({ "anyOf": [schema.clone(), { "type": "null" }] }),
+            }
+        }
+    }
+}
+
+/// Reduce a schema node to the keywords Moonshot understands
+fn make_basic(node: &mut Map<String, Value>) {
+    merge_all_of(node);
+    if let Some(one_of) = node.remove("oneOf") {
+        node.insert("anyOf".to_string(), one_of);
+    }
+    if let Some(constant) = node.remove("const") {
+        node.insert("enum".to_string(), json!([constant]));
+    }
+    node.retain(|keyword, _| BASIC_KEYWORDS.contains(&keyword.as_str()));
+}
+
+/// Fold `allOf` branches into the node that holds them
+fn merge_all_of(node: &mut Map<String, Value>) {
+    let Some(Value::Array(branches)) = node.remove("allOf") else {
+        return;
+    };
+    for branch in branches {
+        let Value::Object(branch) = branch else { continue };
+        for (keyword, value) in branch {
+            match (keyword.as_str(), node.get_mut(&keyword), value) {
+                ("properties", Some(Value::Object(properties)), Value::Object(more)) => properties.extend(more),
+                ("required", Some(Value::Array(required)), Value::Array(more)) => {
+                    for name in more {
+                        if !required.contains(&name) {
+                            required.push(name);
+                        }
+                    }
+                }
+                (_, Some(_), _) => {}
+                (_, None, value) => {
+                    node.insert(keyword, value);
+                }
+            }
+        }
+    }
+}
+
+/// Replace local `$ref`s with the schemas they point to
+///
+/// Recursive schemas are cut off after `MAX_REF_DEPTH` references with an
+/// unconstrained object.
+fn inline_refs(root: &Value, schema: &Value, depth: usize) -> Value {
+    match schema {
+        Value::Object(node) => {
+            if let Some(reference) = node.get("$ref").and_then(Value::as_str) {
+                let target = reference.strip_prefix('#').and_then(|pointer| root.pointer(pointer));
+                let mut resolved = match target {
+                    Some(target) if depth < MAX_REF_DEPTH => inline_refs(root, target, depth + 1),
+                    _ => json!({ "type": "object" }),
+                };
+                // Keywords next to the `$ref` (usually a description) take precedence
+                if let Value::Object(resolved_node) = &mut resolved {
+                    for (keyword, value) in node.iter().filter(|(keyword, _)| *keyword != "$ref") {
+                        resolved_node.insert(keyword.clone(), inline_refs(root, value, depth));
+                    }
+                }
+                return resolved;
+            }
+            Value::Object(
+                node.iter()
+                    .filter(|(keyword, _)| *keyword != "$defs" && *keyword != "definitions")
+                    .map(|(keyword, value)| (keyword.clone(), inline_refs(root, value, depth)))
+                    .collect(),
+            )
+        }
+        Value::Array(items) => Value::Array(items.iter().map(|item| inline_refs(root, item, depth)).collect()),
+        other => other.clone(),
+    }
+}
+
+/// Call `f` on every schema node, parents before their subschemas
+fn walk_mut(schema: &mut Value, f: &mut dyn FnMut(&mut Map<String, Value>)) {
+    let Value::Object(node) = schema else {
+        return;
+    };
+    f(node);
+    for (keyword, value) in node.iter_mut() {
+        for child in subschemas(keyword, value) {
+            walk_mut(child, f);
+        }
+    }
+}
+
+fn visit(schema: &Value, f: &mut dyn FnMut(&Map<String, Value>)) {
+    let mut schema = schema.clone();
+    walk_mut(&mut schema, &mut |node| f(node));
+}
+
+/// The subschemas held by a keyword's value
+fn subschemas<'a>(keyword: &str, value: &'a mut Value) -> Vec<&'a mut Value> {
+    match (keyword, value) {
+        ("properties" | "patternProperties" | "$defs" | "definitions", Value::Object(schemas)) => {
+            schemas.values_mut().collect()
+        }
+        ("anyOf" | "oneOf" | "allOf" | "prefixItems" | "items", Value::Array(schemas)) => schemas.iter_mut().collect(),
+        ("items" | "additionalProperties" | "not", value @ Value::Object(_)) => vec![value],
+        _ => Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::api::OpenAIFunction;
+
+    fn tool(parameters: Value, strict: bool) -> OpenAITool {
+        OpenAITool {
+            tool_type: "function".to_string(),
+            function: OpenAIFunction {
+                name: "plan_trip".to_string(),
+                description: None,
+                parameters,
+                strict: strict.then_some(true),
+                output_schema: None,
+            },
+        }
+    }
+
+    #[test]
+    fn test_schema_dialects() {
+        let schema = json!({
+            "$schema": "https://json-schema.org/draft/2020-12/schema",
+            "type": "object",
+            "properties": {
+                "city": { "type": "string", "minLength": 1 },
+                "stop": { "$ref": "#/$defs/stop", "description": "First stop" },
+                "mode": { "oneOf": [{ "const": "car" }, { "const": "train" }] }
+            },
+            "required": ["city"],
+            "$defs": { "stop": { "type": "object", "properties": { "name": { "type": "string" } } } }
+        });
+        assert!(check_schema(&schema).is_ok());
+        assert!(check_schema(&json!({ "type": "string" })).is_err());
+        assert!(check_schema(&json!({ "properties": { "a": { "$ref": "#/$defs/missing" } } })).is_err());
+
+        let mut tools = vec![tool(schema.clone(), true)];
+        normalize_tools(&mut tools, SchemaDialect::OpenAI);
+        let strict = &tools[0].function.parameters;
+        assert_eq!(tools[0].function.strict, Some(true));
+        assert_eq!(strict["additionalProperties"], json!(false));
+        assert_eq!(strict["required"], json!(["city", "mode", "stop"]));
+        assert_eq!(strict["properties"]["city"], json!({ "type": "string" }));
+        assert_eq!(strict["properties"]["mode"]["anyOf"][2], json!({ "type": "null" }));
+        assert_eq!(strict["$defs"]["stop"]["required"], json!(["name"]));
+        assert!(strict.get("$schema").is_none());
+
+        let mut tools = vec![tool(schema, true)];
+        normalize_tools(&mut tools, SchemaDialect::Basic);
+        let basic = &tools[0].function.parameters;
+        assert_eq!(tools[0].function.strict, None);
+        assert!(basic.get("$defs").is_none());
+        assert_eq!(
+            basic["properties"]["stop"],
+            json!({ "type": "object", "properties": { "name": { "type": "string" } }, "description": "First stop" })
+        );
+        assert_eq!(basic["properties"]["mode"], json!({ "anyOf": [{ "enum": ["car"] }, { "enum": ["train"] }] }));
+        assert_eq!(basic["properties"]["city"], json!({ "type": "string", "minLength": 1 }));
+    }
+}
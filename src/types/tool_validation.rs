@@ -0,0 +1,372 @@
+// Checking the tool calls a model returns
+//
+// A tool call is a problem when it names a tool the request didn't supply,
+// when its arguments can't be decoded, or when they don't match the tool's
+// `input_schema` (see `json_schema`). What happens then is set by
+// `Config.tool_validation.mode`.
+
+use crate::types::api::OpenAICompletionRequest;
+use crate::types::conversion::{DecodedArguments, OpenAIContent, OpenAIMessage, ToolCallWarning, ToolCallWarningKind};
+use crate::types::json_schema;
+use crate::types::response::{OpenAICompletionResponse, OpenAIError};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ToolValidationMode {
+    /// Tool calls are not checked
+    #[default]
+    Off,
+    /// The response is returned as is, with the problems in its
+    /// `tool_call_warnings`
+    Warn,
+    /// Problems fail the request with an `invalid_tool_call` error
+    Error,
+    /// The model is shown the problems and asked again, up to `max_retries`
+    /// times, before failing like `error`. Streamed completions can't be
+    /// taken back once sent, so they fail straight away.
+    Retry,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ToolValidationConfig {
+    #[serde(default)]
+    pub mode: ToolValidationMode,
+
+    /// Extra requests `retry` mode may make for one completion
+    #[serde(default = "default_max_retries")]
+    pub max_retries: u32,
+}
+
+fn default_max_retries() -> u32 {
+    2
+}
+
+impl Default for ToolValidationConfig {
+    fn default() -> Self {
+        Self {
+            mode: ToolValidationMode::default(),
+            max_retries: default_max_retries(),
+        }
+    }
+}
+
+/// Everything wrong with one tool call
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ToolCallProblem {
+    pub id: String,
+    pub name: String,
+    pub errors: Vec<String>,
+}
+
+impl std::fmt::Display for ToolCallProblem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} ({}): {}", self.name, self.id, self.errors.join("; "))
+    }
+}
+
+/// Check the tool calls of a completion against the tools in its request
+///
+/// Problems name tools by the caller's names (see `ToolNames`).
+pub fn find_problems(request: &OpenAICompletionRequest, completion: &OpenAICompletionResponse) -> Vec<ToolCallProblem> {
+    let tools = request.tools.as_deref().unwrap_or_default();
+    let Some(choice) = completion.choices.first() else {
+        return Vec::new();
+    };
+
+    choice
+        .message
+        .tool_calls
+        .iter()
+        .flatten()
+        .filter_map(|call| {
+            let errors = match tools.iter().find(|tool| tool.function.name == call.function.name) {
+                None => vec![unknown_tool(request, &call.function.name)],
+                Some(tool) => match call.function.decode_arguments() {
+                    DecodedArguments::Invalid(error) => vec![format!("arguments could not be decoded: {}", error)],
+                    decoded => json_schema::validate(&tool.function.parameters, &decoded.into_value().unwrap_or_default()),
+                },
+            };
+            (!errors.is_empty()).then(|| ToolCallProblem {
+                id: call.id.clone(),
+                name: request.tool_names.original_name(&call.function.name),
+                errors,
+            })
+        })
+        .collect()
+}
+
+fn unknown_tool(request: &OpenAICompletionRequest, name: &str) -> String {
+    let available: Vec<String> = request
+        .tools
+        .iter()
+        .flatten()
+        .map(|tool| request.tool_names.original_name(&tool.function.name))
+        .collect();
+    format!(
+        "tool '{}' is not in the request's tools ({})",
+        request.tool_names.original_name(name),
+        if available.is_empty() { "none supplied".to_string() } else { available.join(", ") }
+    )
+}
+
+impl ToolValidationConfig {
+    /// Apply `mode` to a completion whose tool calls have `problems`
+    ///
+    /// Retrying is up to the caller; here `retry` behaves like `error`.
+    pub fn resolve(
+        &self,
+        completion: OpenAICompletionResponse,
+        problems: Vec<ToolCallProblem>,
+    ) -> Result<OpenAICompletionResponse, OpenAIError> {
+        if problems.is_empty() {
+            return Ok(completion);
+        }
+
+        match self.mode {
+            // Warnings are added when the response is sent (see `warnings`)
+            ToolValidationMode::Off | ToolValidationMode::Warn => Ok(completion),
+            ToolValidationMode::Error | ToolValidationMode::Retry => Err(OpenAIError::InvalidToolCall {
+                problems: problems.iter().map(ToString::to_string).collect(),
+                completion: Box::new(completion),
+            }),
+        }
+    }
+
+    /// Check a completion's tool calls and apply `mode`
+    pub fn check(
+        &self,
+        request: &OpenAICompletionRequest,
+        completion: OpenAICompletionResponse,
+    ) -> Result<OpenAICompletionResponse, OpenAIError> {
+        if matches!(self.mode, ToolValidationMode::Off | ToolValidationMode::Warn) {
+            return Ok(completion);
+        }
+        let problems = find_problems(request, &completion);
+        self.resolve(completion, problems)
+    }
+
+    /// The problems `warn` mode reports with a completion
+    pub fn warnings(
+        &self,
+        request: &OpenAICompletionRequest,
+        completion: &OpenAICompletionResponse,
+    ) -> Vec<ToolCallWarning> {
+        if self.mode != ToolValidationMode::Warn {
+            return Vec::new();
+        }
+        find_problems(request, completion)
+            .into_iter()
+            .map(|problem| ToolCallWarning {
+                id: problem.id,
+                name: problem.name,
+                kind: ToolCallWarningKind::InvalidToolCall,
+                errors: problem.errors,
+            })
+            .collect()
+    }
+}
+
+/// The request that shows the model its invalid tool calls and asks again
+///
+/// Every call in the assistant turn gets a tool message, as providers require;
+/// valid calls are reported as not run so the model repeats them. The model
+/// only knows the tools by the names they were sent under, so those are used.
+pub fn retry_request(
+    request: &OpenAICompletionRequest,
+    completion: &OpenAICompletionResponse,
+    problems: &[ToolCallProblem],
+) -> OpenAICompletionRequest {
+    let mut request = request.clone();
+    let Some(choice) = completion.choices.first() else {
+        return request;
+    };
+
+    let tools: Vec<&str> = request.tools.iter().flatten().map(|tool| tool.function.name.as_str()).collect();
+    request.messages.push(choice.message.clone());
+    for call in choice.message.tool_calls.iter().flatten() {
+        let text = match problems.iter().find(|problem| problem.id == call.id) {
+            Some(_) if !tools.contains(&call.function.name.as_str()) => format!(
+                "Error: there is no tool named '{}', so this call was not run. Use one of: {}.",
+                call.function.name,
+                tools.join(", ")
+            ),
+            Some(problem) => format!(
+                "Error: this tool call was rejected and not run. {}. Call the tool again with corrected arguments.",
+                problem.errors.join("; ")
+            ),
+            None => "This tool call was not run because another call in the same turn was invalid. Call it again.".to_string(),
+        };
+        request.messages.push(OpenAIMessage {
+            role: "tool".to_string(),
+            content: Some(OpenAIContent::from_text(text)),
+            tool_calls: None,
+            tool_call_id: Some(call.id.clone()),
+            name: None,
+            audio: None,
+            refusal: None,
+        });
+    }
+    request
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::api::{OpenAIFunction, OpenAITool};
+    use crate::types::conversion::{OpenAIFunctionCall, OpenAIToolCall};
+    use crate::types::response::OpenAIChoice;
+    use crate::types::tool_names::ToolNames;
+    use crate::types::tool_schema::{configure_tools, remove_strict_nulls, ToolSchemaConfig};
+
+    fn completion(calls: &[(&str, &str, &str)]) -> OpenAICompletionResponse {
+        OpenAICompletionResponse {
+            id: "c1".to_string(),
+            object: "chat.completion".to_string(),
+            created: 0,
+            model: "kimi-k2-0711-preview".to_string(),
+            choices: vec![OpenAIChoice {
+                index: 0,
+                message: OpenAIMessage {
+                    role: "assistant".to_string(),
+                    content: None,
+                    tool_calls: Some(
+                        calls
+                            .iter()
+                            .map(|(id, name, arguments)| OpenAIToolCall {
+                                id: id.to_string(),
+                                tool_type: "function".to_string(),
+                                function: OpenAIFunctionCall {
+                                    name: name.to_string(),
+                                    arguments: serde_json::json!(arguments),
+                                },
+                            })
+                            .collect(),
+                    ),
+                    tool_call_id: None,
+                    name: None,
+                    audio: None,
+                    refusal: None,
+                },
+                logprobs: None,
+                finish_reason: "tool_calls".to_string(),
+            }],
+            usage: Default::default(),
+            service_tier: None,
+            system_fingerprint: None,
+        }
+    }
+
+    #[test]
+    fn test_tool_call_problems() {
+        let request: OpenAICompletionRequest = serde_json::from_value(serde_json::json!({
+            "model": "kimi-k2-0711-preview",
+            "messages": [],
+        }))
+        .unwrap();
+        let request = OpenAICompletionRequest {
+            tools: Some(vec![OpenAITool {
+                tool_type: "function".to_string(),
+                function: OpenAIFunction {
+                    name: "get_weather".to_string(),
+                    description: None,
+                    parameters: serde_json::json!({
+                        "type": "object",
+                        "properties": { "location": { "type": "string" } },
+                        "required": ["location"]
+                    }),
+                    strict: None,
+                    output_schema: None,
+                },
+            }]),
+            ..request
+        };
+
+        let completion = completion(&[
+            ("call_1", "get_weather", r#"{"location": "Paris"}"#),
+            ("call_2", "get_weather", r#"{"city": "Rome"}"#),
+            ("call_3", "get_forecast", "{}"),
+        ]);
+        let problems = find_problems(&request, &completion);
+        assert_eq!(
+            problems.iter().map(ToString::to_string).collect::<Vec<_>>(),
+            [
+                "get_weather (call_2): arguments: missing required property 'location'",
+                "get_forecast (call_3): tool 'get_forecast' is not in the request's tools (get_weather)",
+            ]
+        );
+
+        // Warn leaves the content alone and reports the problems beside it
+        let warn = ToolValidationConfig { mode: ToolValidationMode::Warn, ..Default::default() };
+        let warned = warn.check(&request, completion.clone()).unwrap();
+        assert!(warned.choices[0].message.content.is_none());
+        let warnings = warn.warnings(&request, &completion);
+        assert_eq!(warnings.len(), 2);
+        assert_eq!(warnings[1].kind, ToolCallWarningKind::InvalidToolCall);
+
+        let error = ToolValidationConfig { mode: ToolValidationMode::Error, ..Default::default() };
+        assert!(matches!(
+            error.resolve(completion.clone(), problems.clone()),
+            Err(OpenAIError::InvalidToolCall { problems, completion }) if problems.len() == 2 && completion.id == "c1"
+        ));
+
+        // Unknown-tool errors list the caller's names, not the ones sent upstream
+        let mut renamed = request.clone();
+        renamed.tool_names = ToolNames::new(["weather.get"]);
+        renamed.tools.as_mut().unwrap()[0].function.name = renamed.tool_names.provider_name("weather.get");
+        assert_eq!(
+            find_problems(&renamed, &completion)[0].errors,
+            ["tool 'get_weather' is not in the request's tools (weather.get)"]
+        );
+
+        // One tool message per call, after the assistant turn
+        let retry = retry_request(&request, &completion, &problems);
+        let roles: Vec<&str> = retry.messages.iter().map(|message| message.role.as_str()).collect();
+        assert_eq!(roles, ["assistant", "tool", "tool", "tool"]);
+        assert_eq!(retry.messages[2].tool_call_id.as_deref(), Some("call_2"));
+    }
+
+    #[test]
+    fn test_strict_tool_calls() {
+        let mut request: OpenAICompletionRequest = serde_json::from_value(serde_json::json!({
+            "model": "gpt-4o",
+            "messages": [],
+            "tools": [{ "type": "function", "function": {
+                "name": "get_weather",
+                "parameters": {
+                    "type": "object",
+                    "properties": {
+                        "location": { "type": "string" },
+                        "unit": { "type": "string", "enum": ["c", "f"] },
+                        "note": { "type": ["string", "null"] },
+                        "days": { "type": "array", "items": {
+                            "type": "object",
+                            "properties": { "day": { "type": "integer" }, "hour": { "type": "integer" } },
+                            "required": ["day"]
+                        } }
+                    },
+                    "required": ["location"]
+                }
+            } }]
+        }))
+        .unwrap();
+        configure_tools(request.tools.as_mut().unwrap(), &ToolSchemaConfig { strict: true, ..Default::default() });
+
+        // Strict schemas make the model send null for arguments it leaves out
+        let mut completion = completion(&[(
+            "call_1",
+            "get_weather",
+            r#"{"location": "Paris", "unit": null, "note": null, "days": [{"day": 1, "hour": null}]}"#,
+        )]);
+        let validation = ToolValidationConfig { mode: ToolValidationMode::Error, ..Default::default() };
+        assert_eq!(find_problems(&request, &completion).len(), 1);
+
+        remove_strict_nulls(&request, &mut completion);
+        let arguments = completion.choices[0].message.tool_calls.as_ref().unwrap()[0].function.decode_arguments();
+        assert_eq!(
+            arguments.into_value().unwrap(),
+            serde_json::json!({ "location": "Paris", "note": null, "days": [{ "day": 1 }] })
+        );
+        assert!(validation.check(&request, completion).is_ok());
+    }
+}
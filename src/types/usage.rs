@@ -0,0 +1,309 @@
+// Token accounting and per-caller quotas
+//
+// Usage from every completion served by an upstream is totalled per model and
+// per caller in actor state. A caller is the prefix of the message-server
+// request ID (or a channel request's `request_id`) before `caller_delimiter`,
+// so `planner:42` and `planner:43` both count towards `planner`. Day and month
+// periods are UTC.
+// Estimated spend (see `pricing`) is totalled alongside the token counts.
+
+use crate::date::{civil_from_days, days_from_civil};
+use crate::types::api::OpenAIUsage;
+use crate::types::pricing::{Budget, BudgetPeriod};
+use crate::types::response::OpenAIError;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+const MS_PER_DAY: u64 = 86_400_000;
+
+/// Caller name for IDs without a `caller_delimiter`
+pub const ANONYMOUS_CALLER: &str = "anonymous";
+
+/// Request and token limits for one period
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct QuotaLimit {
+    pub max_requests: Option<u64>,
+    /// Input plus output tokens
+    pub max_tokens: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct Quotas {
+    #[serde(default)]
+    pub daily: Option<QuotaLimit>,
+    #[serde(default)]
+    pub monthly: Option<QuotaLimit>,
+}
+
+/// How callers are identified and what they may use
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct UsageConfig {
+    /// Separates the caller name from the rest of a request ID
+    #[serde(default = "default_caller_delimiter")]
+    pub caller_delimiter: String,
+
+    /// Limits applied to every caller
+    #[serde(default)]
+    pub quotas: Quotas,
+
+    /// Limits for specific callers, replacing `quotas`
+    #[serde(default)]
+    pub caller_quotas: HashMap<String, Quotas>,
+}
+
+fn default_caller_delimiter() -> String {
+    ":".to_string()
+}
+
+impl Default for UsageConfig {
+    fn default() -> Self {
+        Self {
+            caller_delimiter: default_caller_delimiter(),
+            quotas: Quotas::default(),
+            caller_quotas: HashMap::new(),
+        }
+    }
+}
+
+impl UsageConfig {
+    /// The caller a request or channel ID belongs to
+    pub fn caller(&self, request_id: &str) -> String {
+        match request_id.split_once(self.caller_delimiter.as_str()) {
+            Some((caller, _)) if !caller.is_empty() => caller.to_string(),
+            _ => ANONYMOUS_CALLER.to_string(),
+        }
+    }
+
+    fn quotas_for(&self, caller: &str) -> &Quotas {
+        self.caller_quotas.get(caller).unwrap_or(&self.quotas)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+pub struct UsageTotals {
+    pub requests: u64,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    /// Estimated spend in USD; completions from unpriced models add nothing
+    #[serde(default)]
+    pub cost_usd: f64,
+}
+
+impl UsageTotals {
+    pub fn tokens(&self) -> u64 {
+        self.input_tokens.saturating_add(self.output_tokens)
+    }
+
+    fn add(&mut self, usage: &OpenAIUsage, cost_usd: f64) {
+        self.requests = self.requests.saturating_add(1);
+        self.input_tokens = self.input_tokens.saturating_add(usage.prompt_tokens as u64);
+        self.output_tokens = self.output_tokens.saturating_add(usage.completion_tokens as u64);
+        self.cost_usd += cost_usd;
+    }
+}
+
+/// Usage within one calendar period
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct PeriodUsage {
+    /// `YYYY-MM-DD` for days, `YYYY-MM` for months
+    pub period: String,
+    #[serde(flatten)]
+    pub totals: UsageTotals,
+}
+
+impl PeriodUsage {
+    /// Totals for `period`, or zero if these are from an earlier one
+    fn totals_in(&self, period: &str) -> UsageTotals {
+        if self.period == period {
+            self.totals
+        } else {
+            UsageTotals::default()
+        }
+    }
+
+    fn add(&mut self, period: &str, usage: &OpenAIUsage, cost_usd: f64) {
+        if self.period != period {
+            *self = PeriodUsage {
+                period: period.to_string(),
+                totals: UsageTotals::default(),
+            };
+        }
+        self.totals.add(usage, cost_usd);
+    }
+}
+
+/// All-time, current day and current month totals
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct UsageSummary {
+    pub total: UsageTotals,
+    pub day: PeriodUsage,
+    pub month: PeriodUsage,
+}
+
+impl UsageSummary {
+    fn add(&mut self, usage: &OpenAIUsage, cost_usd: f64, now_ms: u64) {
+        let (day, month) = periods(now_ms);
+        self.total.add(usage, cost_usd);
+        self.day.add(&day, usage, cost_usd);
+        self.month.add(&month, usage, cost_usd);
+    }
+}
+
+/// Usage counters kept in actor state
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct UsageLedger {
+    /// Every caller's usage combined
+    #[serde(default)]
+    pub overall: UsageSummary,
+    #[serde(default)]
+    pub by_model: HashMap<String, UsageTotals>,
+    #[serde(default)]
+    pub by_caller: HashMap<String, UsageSummary>,
+}
+
+impl UsageLedger {
+    /// Record the usage and estimated cost of a completion served for `caller`
+    pub fn record(&mut self, caller: &str, model: &str, usage: &OpenAIUsage, cost_usd: f64, now_ms: u64) {
+        self.overall.add(usage, cost_usd, now_ms);
+        self.by_model.entry(model.to_string()).or_default().add(usage, cost_usd);
+        self.by_caller
+            .entry(caller.to_string())
+            .or_default()
+            .add(usage, cost_usd, now_ms);
+    }
+
+    /// Reject a request if the caller has used up a daily or monthly quota
+    ///
+    /// Token counts are only known after a completion, so the request that
+    /// crosses a token limit is allowed and the next one is rejected.
+    pub fn check_quota(&self, config: &UsageConfig, caller: &str, now_ms: u64) -> Result<(), OpenAIError> {
+        let quotas = config.quotas_for(caller);
+        let usage = self.by_caller.get(caller).cloned().unwrap_or_default();
+        let (day, month) = periods(now_ms);
+
+        let checks = [
+            ("daily", &quotas.daily, usage.day.totals_in(&day), next_day_ms(now_ms)),
+            ("monthly", &quotas.monthly, usage.month.totals_in(&month), next_month_ms(now_ms)),
+        ];
+        for (period, limit, used, resets_at) in checks {
+            let Some(limit) = limit else { continue };
+            let exceeded = [
+                ("requests", used.requests, limit.max_requests),
+                ("tokens", used.tokens(), limit.max_tokens),
+            ]
+            .into_iter()
+            .find(|(_, used, max)| max.is_some_and(|max| *used >= max));
+
+            if let Some((unit, used, Some(max))) = exceeded {
+                return Err(OpenAIError::QuotaExceeded {
+                    caller: caller.to_string(),
+                    period: period.to_string(),
+                    unit: unit.to_string(),
+                    used,
+                    limit: max,
+                    retry_after: (resets_at - now_ms).div_ceil(1000),
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Reject a request once estimated spend has reached the budget
+    pub fn check_budget(&self, budget: &Budget, now_ms: u64) -> Result<(), OpenAIError> {
+        let (day, month) = periods(now_ms);
+        let (spent, resets_at) = match budget.period {
+            BudgetPeriod::Total => (self.overall.total.cost_usd, None),
+            BudgetPeriod::Daily => (self.overall.day.totals_in(&day).cost_usd, Some(next_day_ms(now_ms))),
+            BudgetPeriod::Monthly => (
+                self.overall.month.totals_in(&month).cost_usd,
+                Some(next_month_ms(now_ms)),
+            ),
+        };
+
+        if spent < budget.max_usd {
+            return Ok(());
+        }
+        Err(OpenAIError::BudgetExceeded {
+            period: budget.period,
+            spent_usd: spent,
+            max_usd: budget.max_usd,
+            retry_after: resets_at.map(|resets_at| (resets_at - now_ms).div_ceil(1000)),
+        })
+    }
+}
+
+/// The current UTC day (`YYYY-MM-DD`) and month (`YYYY-MM`)
+fn periods(now_ms: u64) -> (String, String) {
+    let (year, month, day) = civil_from_days((now_ms / MS_PER_DAY) as i64);
+    (
+        format!("{:04}-{:02}-{:02}", year, month, day),
+        format!("{:04}-{:02}", year, month),
+    )
+}
+
+/// Start of the next UTC day, in ms since the epoch
+fn next_day_ms(now_ms: u64) -> u64 {
+    (now_ms / MS_PER_DAY + 1) * MS_PER_DAY
+}
+
+/// Start of the next UTC month, in ms since the epoch
+fn next_month_ms(now_ms: u64) -> u64 {
+    let (year, month, _) = civil_from_days((now_ms / MS_PER_DAY) as i64);
+    let (year, month) = if month == 12 { (year + 1, 1) } else { (year, month + 1) };
+    days_from_civil(year, month, 1) as u64 * MS_PER_DAY
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn usage(prompt_tokens: u32, completion_tokens: u32) -> OpenAIUsage {
+        OpenAIUsage {
+            prompt_tokens,
+            completion_tokens,
+            total_tokens: prompt_tokens + completion_tokens,
+        }
+    }
+
+    #[test]
+    fn test_quota_periods() {
+        let config = UsageConfig {
+            quotas: Quotas {
+                daily: Some(QuotaLimit { max_requests: Some(2), max_tokens: None }),
+                monthly: Some(QuotaLimit { max_requests: None, max_tokens: Some(1000) }),
+            },
+            ..UsageConfig::default()
+        };
+        assert_eq!(config.caller("planner:42"), "planner");
+        assert_eq!(config.caller("6f1c0d2e"), ANONYMOUS_CALLER);
+
+        // 2024-01-31T23:00:00Z
+        let now = days_from_civil(2024, 1, 31) as u64 * MS_PER_DAY + 23 * 3_600_000;
+        let mut ledger = UsageLedger::default();
+        ledger.record("planner", "kimi-k2-0711-preview", &usage(100, 50), 0.0, now);
+        assert!(ledger.check_quota(&config, "planner", now).is_ok());
+        ledger.record("planner", "kimi-k2-0711-preview", &usage(100, 50), 0.0, now);
+
+        match ledger.check_quota(&config, "planner", now) {
+            Err(OpenAIError::QuotaExceeded { period, unit, retry_after, .. }) => {
+                assert_eq!((period.as_str(), unit.as_str()), ("daily", "requests"));
+                assert_eq!(retry_after, 3600);
+            }
+            other => panic!("expected a daily quota error, got {:?}", other),
+        }
+        assert!(ledger.check_quota(&config, "reviewer", now).is_ok());
+
+        // The next day starts a new month too
+        let tomorrow = now + 3_600_000;
+        assert!(ledger.check_quota(&config, "planner", tomorrow).is_ok());
+        ledger.record("planner", "kimi-k2-0711-preview", &usage(900, 100), 0.0, tomorrow);
+        assert_eq!(ledger.by_caller["planner"].month.period, "2024-02");
+        assert_eq!(ledger.by_caller["planner"].total.requests, 3);
+        assert_eq!(ledger.by_model["kimi-k2-0711-preview"].tokens(), 1300);
+        assert!(matches!(
+            ledger.check_quota(&config, "planner", tomorrow),
+            Err(OpenAIError::QuotaExceeded { .. })
+        ));
+    }
+}